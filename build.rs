@@ -15,10 +15,12 @@ enum SourceType {
 enum ShaderType {
 	Vertex,
 	Fragment,
+	Compute,
 }
 
 const VERTEX_EXTENSION: &str = "vert";
 const FRAGMENT_EXTENSION: &str = "frag";
+const COMPUTE_EXTENSION: &str = "comp";
 
 const HLSL_EXTENSION: &str = "hlsl";
 const GLSL_EXTENSION: &str = "glsl";
@@ -28,6 +30,7 @@ const GLSL_DEFINE: &str = "GLSL";
 
 const VERTEX_DEFINE: &str = "VERTEX";
 const FRAGMENT_DEFINE: &str = "FRAGMENT";
+const COMPUTE_DEFINE: &str = "COMPUTE";
 
 const GEN_FOLDER: &str = "gen";
 const SHADERS_FOLDER: &str = "shaders";
@@ -153,6 +156,11 @@ fn main() -> Result<()> {
 		[(FRAGMENT_DEFINE, None)].as_slice(),
 	)?;
 
+	let shaderc_compute_options = gen_shaderc_common_options(
+		include_handler.clone(),
+		[(COMPUTE_DEFINE, None)].as_slice(),
+	)?;
+
 	fs::create_dir_all(GEN_FOLDER)?;
 	fs::remove_dir_all(GEN_FOLDER)?;
 
@@ -191,6 +199,10 @@ fn main() -> Result<()> {
 							let shader = (source_type, ShaderType::Fragment);
 							Some(shader)
 						}
+						COMPUTE_EXTENSION => {
+							let shader = (source_type, ShaderType::Compute);
+							Some(shader)
+						}
 						_ => None,
 					})
 			});
@@ -218,6 +230,9 @@ fn main() -> Result<()> {
 						ShaderType::Fragment => {
 							("ps_6_0", [(FRAGMENT_DEFINE, None)].as_slice())
 						}
+						ShaderType::Compute => {
+							("cs_6_0", [(COMPUTE_DEFINE, None)].as_slice())
+						}
 					};
 
 					let defines =
@@ -260,6 +275,10 @@ fn main() -> Result<()> {
 							shaderc::ShaderKind::Fragment,
 							&shaderc_fragment_options,
 						),
+						ShaderType::Compute => (
+							shaderc::ShaderKind::Compute,
+							&shaderc_compute_options,
+						),
 					};
 
 					let result = shaderc_compiler.compile_into_spirv(
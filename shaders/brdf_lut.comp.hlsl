@@ -0,0 +1,71 @@
+static const float PI = 3.14159265f;
+static const uint SAMPLE_COUNT = 512;
+
+[[vk::binding(1)]] [[vk::image_format("rgba16f")]] RWTexture2D<float4> BrdfLutImage : register(u1);
+
+float2 hammersley(uint i, uint count)
+{
+    return float2(float(i) / float(count), reversebits(i) * 2.3283064365386963e-10f);
+}
+
+float3 importance_sample_ggx(float2 xi, float3 normal, float alpha)
+{
+    float phi = 2.0f * PI * xi.x;
+    float cos_theta = sqrt((1.0f - xi.y) / (1.0f + (alpha * alpha - 1.0f) * xi.y));
+    float sin_theta = sqrt(1.0f - cos_theta * cos_theta);
+
+    float3 up = abs(normal.z) < 0.999f ? float3(0.0f, 0.0f, 1.0f) : float3(1.0f, 0.0f, 0.0f);
+    float3 tangent = normalize(cross(up, normal));
+    float3 bitangent = cross(normal, tangent);
+
+    return normalize(tangent * cos(phi) * sin_theta + bitangent * sin(phi) * sin_theta + normal * cos_theta);
+}
+
+float geometry_schlick_ggx(float n_dot_x, float k)
+{
+    return n_dot_x / (n_dot_x * (1.0f - k) + k);
+}
+
+// Split sum scale and bias of specular F0, indexed by n_dot_v and roughness
+[numthreads(8, 8, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    uint width, height;
+    BrdfLutImage.GetDimensions(width, height);
+
+    if (id.x >= width || id.y >= height)
+    {
+        return;
+    }
+
+    float n_dot_v = (id.x + 0.5f) / width;
+    float roughness = (id.y + 0.5f) / height;
+    float alpha = roughness * roughness;
+    float k = alpha * 0.5f;
+
+    float3 normal = float3(0.0f, 0.0f, 1.0f);
+    float3 view_direction = float3(sqrt(1.0f - n_dot_v * n_dot_v), 0.0f, n_dot_v);
+
+    float2 scale_bias = 0.0f;
+
+    for (uint i = 0; i < SAMPLE_COUNT; ++i)
+    {
+        float3 half_vector = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, alpha);
+        float3 light_direction = normalize(2.0f * dot(view_direction, half_vector) * half_vector - view_direction);
+
+        float n_dot_l = saturate(light_direction.z);
+        float n_dot_h = saturate(half_vector.z);
+        float v_dot_h = saturate(dot(view_direction, half_vector));
+
+        if (n_dot_l > 0.0f)
+        {
+            float geometry = geometry_schlick_ggx(n_dot_v, k) * geometry_schlick_ggx(n_dot_l, k);
+            float visibility = geometry * v_dot_h / (n_dot_h * n_dot_v);
+            float fresnel = pow(1.0f - v_dot_h, 5.0f);
+
+            scale_bias += float2((1.0f - fresnel) * visibility, fresnel * visibility);
+        }
+    }
+
+    BrdfLutImage[id.xy] = float4(scale_bias / SAMPLE_COUNT, 0.0f, 1.0f);
+}
@@ -0,0 +1,38 @@
+static const float PI = 3.14159265f;
+
+[[vk::combinedImageSampler]] [[vk::binding(0)]] Texture2D EquirectTexture : register(t0);
+[[vk::combinedImageSampler]] [[vk::binding(0)]] SamplerState EquirectSampler : register(s0);
+[[vk::binding(1)]] [[vk::image_format("rgba16f")]] RWTexture2DArray<float4> CubeImage : register(u1);
+
+// Direction through texel center of cube face, per Vulkan cube map layout
+float3 cube_direction(uint3 id, float size)
+{
+    float2 uv = (float2(id.xy) + 0.5f) / size * 2.0f - 1.0f;
+
+    switch (id.z)
+    {
+    case 0: return normalize(float3(1.0f, -uv.y, -uv.x));
+    case 1: return normalize(float3(-1.0f, -uv.y, uv.x));
+    case 2: return normalize(float3(uv.x, 1.0f, uv.y));
+    case 3: return normalize(float3(uv.x, -1.0f, -uv.y));
+    case 4: return normalize(float3(uv.x, -uv.y, 1.0f));
+    default: return normalize(float3(-uv.x, -uv.y, -1.0f));
+    }
+}
+
+[numthreads(8, 8, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    uint width, height, layers;
+    CubeImage.GetDimensions(width, height, layers);
+
+    if (id.x >= width || id.y >= height)
+    {
+        return;
+    }
+
+    float3 direction = cube_direction(id, width);
+    float2 uv = float2(atan2(direction.z, direction.x) / (2.0f * PI) + 0.5f, acos(clamp(direction.y, -1.0f, 1.0f)) / PI);
+
+    CubeImage[id] = float4(EquirectTexture.SampleLevel(EquirectSampler, uv, 0.0f).rgb, 1.0f);
+}
@@ -1,16 +1,298 @@
-// #include "geometry_common_hlsl"
+struct ViewProjection
+{
+    matrix VP;
+    matrix InverseVP;
+    float3 CameraPosition;
+    uint LightCount;
+    uint ShadowFilterRadius;
+};
+
+static const uint DIRECTIONAL_LIGHT = 0;
+
+static const uint MASK_ALPHA_MODE = 1;
+static const uint BLEND_ALPHA_MODE = 2;
+
+struct Light
+{
+    float3 Position;
+    // Zero for infinite range
+    float Range;
+    float3 Direction;
+    uint Type;
+    // Color premultiplied by intensity
+    float3 Color;
+    float SpotScale;
+    float SpotOffset;
+    // Shadow map layer, negative when light casts no shadow
+    int ShadowIndex;
+    matrix ShadowMatrix;
+};
+
+struct Material
+{
+    float4 BaseColorFactor;
+    float3 EmissiveFactor;
+    float NormalScale;
+    float MetallicFactor;
+    float RoughnessFactor;
+    float OcclusionStrength;
+    // Texture coordinate set, negative when texture is absent
+    int BaseColorTexCoord;
+    int MetallicRoughnessTexCoord;
+    int NormalTexCoord;
+    int OcclusionTexCoord;
+    int EmissiveTexCoord;
+    uint AlphaMode;
+    float AlphaCutoff;
+};
+
+[[vk::binding(0, 0)]] ConstantBuffer<ViewProjection> ViewProjectionCB : register(b0, space0);
+[[vk::binding(1, 0)]] StructuredBuffer<Light> Lights : register(t1, space0);
+[[vk::combinedImageSampler]] [[vk::binding(2, 0)]] Texture2DArray ShadowMaps : register(t2, space0);
+[[vk::combinedImageSampler]] [[vk::binding(2, 0)]] SamplerComparisonState ShadowSampler : register(s2, space0);
+[[vk::binding(0, 1)]] ConstantBuffer<Material> MaterialCB : register(b0, space1);
+
+[[vk::combinedImageSampler]] [[vk::binding(1, 1)]] Texture2D BaseColorTexture : register(t1, space1);
+[[vk::combinedImageSampler]] [[vk::binding(1, 1)]] SamplerState BaseColorSampler : register(s1, space1);
+[[vk::combinedImageSampler]] [[vk::binding(2, 1)]] Texture2D MetallicRoughnessTexture : register(t2, space1);
+[[vk::combinedImageSampler]] [[vk::binding(2, 1)]] SamplerState MetallicRoughnessSampler : register(s2, space1);
+[[vk::combinedImageSampler]] [[vk::binding(3, 1)]] Texture2D NormalTexture : register(t3, space1);
+[[vk::combinedImageSampler]] [[vk::binding(3, 1)]] SamplerState NormalSampler : register(s3, space1);
+[[vk::combinedImageSampler]] [[vk::binding(4, 1)]] Texture2D OcclusionTexture : register(t4, space1);
+[[vk::combinedImageSampler]] [[vk::binding(4, 1)]] SamplerState OcclusionSampler : register(s4, space1);
+[[vk::combinedImageSampler]] [[vk::binding(5, 1)]] Texture2D EmissiveTexture : register(t5, space1);
+[[vk::combinedImageSampler]] [[vk::binding(5, 1)]] SamplerState EmissiveSampler : register(s5, space1);
 
-// float4 main( SFragmentInput IN ): SV_Target0
-// {
-// 	return IN.Color;
-// }
+[[vk::combinedImageSampler]] [[vk::binding(0, 2)]] TextureCube IrradianceTexture : register(t0, space2);
+[[vk::combinedImageSampler]] [[vk::binding(0, 2)]] SamplerState IrradianceSampler : register(s0, space2);
+[[vk::combinedImageSampler]] [[vk::binding(1, 2)]] TextureCube PrefilteredTexture : register(t1, space2);
+[[vk::combinedImageSampler]] [[vk::binding(1, 2)]] SamplerState PrefilteredSampler : register(s1, space2);
+[[vk::combinedImageSampler]] [[vk::binding(2, 2)]] Texture2D BrdfLutTexture : register(t2, space2);
+[[vk::combinedImageSampler]] [[vk::binding(2, 2)]] SamplerState BrdfLutSampler : register(s2, space2);
 
 struct PixelShaderInput
 {
-    [[vk::location(0)]] float4 Color: COLOR;
+    [[vk::location(0)]] float3 WorldPosition : POSITION;
+    [[vk::location(1)]] float3 Normal : NORMAL;
+    [[vk::location(2)]] float4 Tangent : TANGENT;
+    [[vk::location(3)]] float2 TexCoord0 : TEXCOORD0;
+    [[vk::location(4)]] float2 TexCoord1 : TEXCOORD1;
+    bool IsFrontFace : SV_IsFrontFace;
 };
 
-float4 main( PixelShaderInput IN ): SV_Target0
+static const float PI = 3.14159265f;
+static const float MIN_ROUGHNESS = 0.04f;
+static const float3 DIELECTRIC_SPECULAR = float3(0.04f, 0.04f, 0.04f);
+
+
+float3 fresnel_schlick(float3 f0, float v_dot_h)
+{
+    return f0 + (1.0f - f0) * pow(1.0f - v_dot_h, 5.0f);
+}
+
+// Trowbridge-Reitz GGX normal distribution
+float distribution_ggx(float n_dot_h, float alpha)
+{
+    float alpha2 = alpha * alpha;
+    float denominator = n_dot_h * n_dot_h * (alpha2 - 1.0f) + 1.0f;
+
+    return alpha2 / (PI * denominator * denominator);
+}
+
+// Height correlated Smith visibility, includes BRDF denominator
+float visibility_smith_ggx(float n_dot_l, float n_dot_v, float alpha)
+{
+    float alpha2 = alpha * alpha;
+    float ggx_v = n_dot_l * sqrt(n_dot_v * n_dot_v * (1.0f - alpha2) + alpha2);
+    float ggx_l = n_dot_v * sqrt(n_dot_l * n_dot_l * (1.0f - alpha2) + alpha2);
+    float ggx = ggx_v + ggx_l;
+
+    return ggx > 0.0f ? 0.5f / ggx : 0.0f;
+}
+
+// Windowed inverse square falloff from KHR_lights_punctual
+float range_attenuation(float distance, float range)
+{
+    float inverse_square = 1.0f / max(distance * distance, 1e-4f);
+
+    if (range <= 0.0f)
+    {
+        return inverse_square;
+    }
+
+    float ratio = distance / range;
+
+    return saturate(1.0f - ratio * ratio * ratio * ratio) * inverse_square;
+}
+
+// Incoming radiance and direction towards light
+float3 light_radiance(Light light, float3 position, out float3 light_direction)
+{
+    if (light.Type == DIRECTIONAL_LIGHT)
+    {
+        light_direction = -light.Direction;
+        return light.Color;
+    }
+
+    float3 to_light = light.Position - position;
+    float distance = length(to_light);
+    light_direction = to_light / max(distance, 1e-4f);
+
+    // Point lights have zero scale and unit offset, so no cone falloff
+    float spot = saturate(dot(light.Direction, -light_direction) * light.SpotScale + light.SpotOffset);
+
+    return light.Color * range_attenuation(distance, light.Range) * spot * spot;
+}
+
+// Percentage closer filtering over square kernel, points outside of light
+// frustum are lit
+float shadow_factor(Light light, float3 position)
+{
+    if (light.ShadowIndex < 0)
+    {
+        return 1.0f;
+    }
+
+    float4 clip_position = mul(light.ShadowMatrix, float4(position, 1.0f));
+    float3 ndc = clip_position.xyz / clip_position.w;
+
+    if (clip_position.w <= 0.0f || ndc.z > 1.0f)
+    {
+        return 1.0f;
+    }
+
+    uint width, height, layers;
+    ShadowMaps.GetDimensions(width, height, layers);
+
+    float2 uv = ndc.xy * 0.5f + 0.5f;
+    float2 texel_size = 1.0f / float2(width, height);
+    int radius = ViewProjectionCB.ShadowFilterRadius;
+
+    float lit = 0.0f;
+
+    for (int y = -radius; y <= radius; ++y)
+    {
+        for (int x = -radius; x <= radius; ++x)
+        {
+            float3 location = float3(uv + float2(x, y) * texel_size, light.ShadowIndex);
+            lit += ShadowMaps.SampleCmpLevelZero(ShadowSampler, location, ndc.z);
+        }
+    }
+
+    float kernel_size = 2 * radius + 1;
+
+    return lit / (kernel_size * kernel_size);
+}
+
+float2 tex_coord(PixelShaderInput IN, int set)
+{
+    return set == 1 ? IN.TexCoord1 : IN.TexCoord0;
+}
+
+// Absent textures are bound to white default texture
+float4 sample_texture(Texture2D tex, SamplerState tex_sampler, PixelShaderInput IN, int set)
+{
+    return tex.Sample(tex_sampler, tex_coord(IN, max(set, 0)));
+}
+
+float4 main(PixelShaderInput IN) : SV_Target0
 {
-    return IN.Color;
-}
\ No newline at end of file
+    float4 base_color = MaterialCB.BaseColorFactor * sample_texture(BaseColorTexture, BaseColorSampler, IN, MaterialCB.BaseColorTexCoord);
+
+    // Only blended materials keep their alpha, masked ones are cut out
+    if (MaterialCB.AlphaMode == MASK_ALPHA_MODE)
+    {
+        clip(base_color.a - MaterialCB.AlphaCutoff);
+    }
+
+    if (MaterialCB.AlphaMode != BLEND_ALPHA_MODE)
+    {
+        base_color.a = 1.0f;
+    }
+
+    // Roughness is stored in green and metalness in blue channel
+    float4 metallic_roughness = sample_texture(MetallicRoughnessTexture, MetallicRoughnessSampler, IN, MaterialCB.MetallicRoughnessTexCoord);
+    float metallic = MaterialCB.MetallicFactor * metallic_roughness.b;
+    float roughness = clamp(MaterialCB.RoughnessFactor * metallic_roughness.g, MIN_ROUGHNESS, 1.0f);
+
+    float occlusion_sample = sample_texture(OcclusionTexture, OcclusionSampler, IN, MaterialCB.OcclusionTexCoord).r;
+    float occlusion = 1.0f + MaterialCB.OcclusionStrength * (occlusion_sample - 1.0f);
+
+    float3 emissive = MaterialCB.EmissiveFactor * sample_texture(EmissiveTexture, EmissiveSampler, IN, MaterialCB.EmissiveTexCoord).rgb;
+
+    float normal_length = length(IN.Normal);
+
+    // Primitives without normals get zeroed attribute, so shade them unlit
+    if (normal_length == 0.0f)
+    {
+        return float4(base_color.rgb + emissive, base_color.a);
+    }
+
+    float3 normal = IN.Normal / normal_length;
+
+    // Back faces are visible only for double sided materials
+    if (!IN.IsFrontFace)
+    {
+        normal = -normal;
+    }
+
+    float tangent_length = length(IN.Tangent.xyz);
+
+    // Normal map needs tangents, primitives without them keep vertex normal
+    if (MaterialCB.NormalTexCoord >= 0 && tangent_length > 0.0f)
+    {
+        float3 tangent = IN.Tangent.xyz / tangent_length;
+        tangent = normalize(tangent - normal * dot(normal, tangent));
+        float3 bitangent = cross(normal, tangent) * (IN.Tangent.w < 0.0f ? -1.0f : 1.0f);
+
+        float3 tangent_normal = sample_texture(NormalTexture, NormalSampler, IN, MaterialCB.NormalTexCoord).xyz * 2.0f - 1.0f;
+        tangent_normal.xy *= MaterialCB.NormalScale;
+
+        normal = normalize(tangent_normal.x * tangent + tangent_normal.y * bitangent + tangent_normal.z * normal);
+    }
+
+    float3 view_direction = normalize(ViewProjectionCB.CameraPosition - IN.WorldPosition);
+    float n_dot_v = max(dot(normal, view_direction), 1e-4f);
+
+    float alpha = roughness * roughness;
+
+    float3 diffuse_color = base_color.rgb * (1.0f - metallic);
+    float3 f0 = lerp(DIELECTRIC_SPECULAR, base_color.rgb, metallic);
+
+    float3 color = 0.0f;
+
+    for (uint i = 0; i < ViewProjectionCB.LightCount; ++i)
+    {
+        float3 light_direction;
+        float3 radiance = light_radiance(Lights[i], IN.WorldPosition, light_direction);
+        radiance *= shadow_factor(Lights[i], IN.WorldPosition);
+
+        float3 half_vector = normalize(light_direction + view_direction);
+
+        float n_dot_l = saturate(dot(normal, light_direction));
+        float n_dot_h = saturate(dot(normal, half_vector));
+        float v_dot_h = saturate(dot(view_direction, half_vector));
+
+        float3 fresnel = fresnel_schlick(f0, v_dot_h);
+        float3 diffuse = (1.0f - fresnel) * diffuse_color / PI;
+        float3 specular = fresnel * distribution_ggx(n_dot_h, alpha) * visibility_smith_ggx(n_dot_l, n_dot_v, alpha);
+
+        color += (diffuse + specular) * radiance * n_dot_l;
+    }
+
+    // Image based lighting, prefiltered levels map roughness linearly
+    uint prefiltered_width, prefiltered_height, prefiltered_levels;
+    PrefilteredTexture.GetDimensions(0, prefiltered_width, prefiltered_height, prefiltered_levels);
+
+    float3 reflection = reflect(-view_direction, normal);
+    float3 irradiance = IrradianceTexture.Sample(IrradianceSampler, normal).rgb;
+    float3 prefiltered = PrefilteredTexture.SampleLevel(PrefilteredSampler, reflection, roughness * (prefiltered_levels - 1)).rgb;
+    float2 brdf = BrdfLutTexture.Sample(BrdfLutSampler, float2(n_dot_v, roughness)).rg;
+
+    color += (irradiance * diffuse_color + prefiltered * (f0 * brdf.x + brdf.y)) * occlusion;
+    color += emissive;
+
+    // Color attachment is sRGB, so blending happens in linear space and
+    // encoding on write
+    return float4(color, base_color.a);
+}
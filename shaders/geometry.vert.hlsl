@@ -1,87 +1,109 @@
-// #include "geometry_common_hlsl"
-
-// struct SVertexData {
-// 	_location(0) float3 Position: POSITION;
-// 	_location(1) float3 Color: COLOR;
-// 	_location(2) float3 Normal: NORMAL;
-// 	_location(3) float2 TexCoord_0: TEXCOORD_0;
-// };
-
-// struct SViewProjection {
-// 	matrix mt;
-// };
-
-// _binding(0)  ConstantBuffer<SViewProjection> view_projection : register(b0);
-
-// SVertexOutput main(SVertexData IN) {
-// 	SVertexOutput OUT;
-
-// 	OUT.Position = mul(view_projection.mt, float4(IN.Position, 1.0f));
-
-// 	if (has_color) {
-// 		OUT.Color = float4(IN.Color, 1.0f);
-// 	}
-
-// 	if (has_normal) {
-// 		OUT.Normal = IN.Normal;
-// 	}
-
-// 	if (has_tex_coord_0) {
-// 		OUT.TexCoord_0 = IN.TexCoord_0;
-// 	}
-
-// 	return OUT;
-// }
-
 struct ViewProjection
 {
     matrix VP;
+    matrix InverseVP;
+    float3 CameraPosition;
+    uint LightCount;
+    uint ShadowFilterRadius;
+};
+
+struct Model
+{
+    matrix M;
+    // Offset of node skin in joint matrices, negative for unskinned nodes
+    int JointOffset;
+    // Node weights in morph weights and primitive targets in morph deltas,
+    // no targets when count is zero
+    uint WeightOffset;
+    uint DeltaOffset;
+    uint TargetCount;
+    uint VertexCount;
 };
 
-[[vk::binding(0)]] ConstantBuffer<ViewProjection> ViewProjectionCB : register(b0);
+[[vk::binding(0, 0)]] ConstantBuffer<ViewProjection> ViewProjectionCB : register(b0, space0);
+[[vk::binding(3, 0)]] StructuredBuffer<float4x4> JointMatrices : register(t3, space0);
+// Position, normal and tangent delta of each vertex, target after target
+[[vk::binding(4, 0)]] StructuredBuffer<float4> MorphDeltas : register(t4, space0);
+[[vk::binding(5, 0)]] StructuredBuffer<float> MorphWeights : register(t5, space0);
+[[vk::push_constant]] Model ModelPC;
 
-struct VertexPosColor
+struct VertexInput
 {
-    uint id: SV_VertexID;
+    [[vk::location(0)]] float3 Position : POSITION;
+    [[vk::location(1)]] float3 Normal : NORMAL;
+    [[vk::location(2)]] float4 Tangent : TANGENT;
+    [[vk::location(3)]] float2 TexCoord0 : TEXCOORD0;
+    [[vk::location(4)]] float2 TexCoord1 : TEXCOORD1;
+    [[vk::location(5)]] uint4 Joints : BLENDINDICES0;
+    [[vk::location(6)]] float4 Weights : BLENDWEIGHT0;
+    uint VertexIndex : SV_VertexID;
 };
- 
+
 struct VertexShaderOutput
 {
-    [[vk::location(0)]] float4 Color    : COLOR;
+    [[vk::location(0)]] float3 WorldPosition : POSITION;
+    [[vk::location(1)]] float3 Normal : NORMAL;
+    [[vk::location(2)]] float4 Tangent : TANGENT;
+    [[vk::location(3)]] float2 TexCoord0 : TEXCOORD0;
+    [[vk::location(4)]] float2 TexCoord1 : TEXCOORD1;
     float4 Position : SV_Position;
 };
 
-static float offset = 0.3;
-static float depth = 0.1;
-static float3 color0 = float3(0.0, 0.0, 1.0);
-static float3 color1 = float3(0.0, 1.0, 0.0);
-
-static float3 positions[] = {
-    float3(0.0, 0.0, 0.0),
-    float3(1.0, 0.0, 0.0),
-    float3(0.0, 1.0, 0.0),
-    float3(0.0 + 0.3, 1.0 + offset, depth),
-    float3(1.0 + 0.3, 0.0 + offset, depth),
-    float3(0.0 + 0.3, 0.0 + offset, depth)
-};
+float4x4 skin_matrix(uint4 joints, float4 weights, uint offset)
+{
+    return weights.x * JointMatrices[offset + joints.x]
+        + weights.y * JointMatrices[offset + joints.y]
+        + weights.z * JointMatrices[offset + joints.z]
+        + weights.w * JointMatrices[offset + joints.w];
+}
+
+// Cofactor matrix is inverse transpose scaled by determinant, sign keeps
+// normals of mirrored transforms outward
+float3x3 normal_matrix(float3x3 m)
+{
+    float3x3 cofactor = float3x3(
+        cross(m[1], m[2]),
+        cross(m[2], m[0]),
+        cross(m[0], m[1]));
 
-static float3 colors[] = {
-    color0,
-    color0,
-    color0,
-    color1,
-    color1,
-    color1
-};
+    return cofactor * sign(determinant(m));
+}
 
-VertexShaderOutput main(VertexPosColor IN)
+VertexShaderOutput main(VertexInput IN)
 {
     VertexShaderOutput OUT;
 
-    float3 position = positions[IN.id];
-    float3 color = colors[IN.id];
-    OUT.Position = mul(ViewProjectionCB.VP, float4(position, 1.0f));
-    OUT.Color = float4(color, 1.0f);
- 
+    float3 morphedPosition = IN.Position;
+    float3 morphedNormal = IN.Normal;
+    float3 morphedTangent = IN.Tangent.xyz;
+
+    for (uint target = 0; target < ModelPC.TargetCount; target++)
+    {
+        float weight = MorphWeights[ModelPC.WeightOffset + target];
+        uint delta = ModelPC.DeltaOffset
+            + 3 * (target * ModelPC.VertexCount + IN.VertexIndex);
+
+        morphedPosition += weight * MorphDeltas[delta].xyz;
+        morphedNormal += weight * MorphDeltas[delta + 1].xyz;
+        morphedTangent += weight * MorphDeltas[delta + 2].xyz;
+    }
+
+    float4x4 model = ModelPC.M;
+
+    if (ModelPC.JointOffset >= 0)
+    {
+        model = mul(model, skin_matrix(IN.Joints, IN.Weights, ModelPC.JointOffset));
+    }
+
+    float4 position = mul(model, float4(morphedPosition, 1.0f));
+
+    OUT.Position = mul(ViewProjectionCB.VP, position);
+    OUT.WorldPosition = position.xyz;
+    OUT.Normal = mul(normal_matrix((float3x3)model), morphedNormal);
+    // Handedness is kept in w
+    OUT.Tangent = float4(mul((float3x3)model, morphedTangent), IN.Tangent.w);
+    OUT.TexCoord0 = IN.TexCoord0;
+    OUT.TexCoord1 = IN.TexCoord1;
+
     return OUT;
-}
\ No newline at end of file
+}
@@ -0,0 +1,65 @@
+static const float PI = 3.14159265f;
+static const float SAMPLE_DELTA = 0.025f;
+
+struct Parameters
+{
+    float Roughness;
+    float SourceLod;
+};
+
+[[vk::combinedImageSampler]] [[vk::binding(0)]] TextureCube EnvironmentTexture : register(t0);
+[[vk::combinedImageSampler]] [[vk::binding(0)]] SamplerState EnvironmentSampler : register(s0);
+[[vk::binding(1)]] [[vk::image_format("rgba16f")]] RWTexture2DArray<float4> IrradianceImage : register(u1);
+[[vk::push_constant]] Parameters ParametersPC;
+
+// Direction through texel center of cube face, per Vulkan cube map layout
+float3 cube_direction(uint3 id, float size)
+{
+    float2 uv = (float2(id.xy) + 0.5f) / size * 2.0f - 1.0f;
+
+    switch (id.z)
+    {
+    case 0: return normalize(float3(1.0f, -uv.y, -uv.x));
+    case 1: return normalize(float3(-1.0f, -uv.y, uv.x));
+    case 2: return normalize(float3(uv.x, 1.0f, uv.y));
+    case 3: return normalize(float3(uv.x, -1.0f, -uv.y));
+    case 4: return normalize(float3(uv.x, -uv.y, 1.0f));
+    default: return normalize(float3(-uv.x, -uv.y, -1.0f));
+    }
+}
+
+// Cosine weighted hemisphere integral, scaled so that diffuse lighting is
+// irradiance multiplied by albedo
+[numthreads(8, 8, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    uint width, height, layers;
+    IrradianceImage.GetDimensions(width, height, layers);
+
+    if (id.x >= width || id.y >= height)
+    {
+        return;
+    }
+
+    float3 normal = cube_direction(id, width);
+    float3 up = abs(normal.y) < 0.999f ? float3(0.0f, 1.0f, 0.0f) : float3(1.0f, 0.0f, 0.0f);
+    float3 right = normalize(cross(up, normal));
+    up = cross(normal, right);
+
+    float3 irradiance = 0.0f;
+    float sample_count = 0.0f;
+
+    for (float phi = 0.0f; phi < 2.0f * PI; phi += SAMPLE_DELTA)
+    {
+        for (float theta = 0.0f; theta < 0.5f * PI; theta += SAMPLE_DELTA)
+        {
+            float3 tangent_direction = float3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            float3 direction = tangent_direction.x * right + tangent_direction.y * up + tangent_direction.z * normal;
+
+            irradiance += EnvironmentTexture.SampleLevel(EnvironmentSampler, direction, ParametersPC.SourceLod).rgb * cos(theta) * sin(theta);
+            sample_count += 1.0f;
+        }
+    }
+
+    IrradianceImage[id] = float4(PI * irradiance / sample_count, 1.0f);
+}
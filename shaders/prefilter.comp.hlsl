@@ -0,0 +1,107 @@
+static const float PI = 3.14159265f;
+static const uint SAMPLE_COUNT = 256;
+
+struct Parameters
+{
+    float Roughness;
+    float SourceLod;
+};
+
+[[vk::combinedImageSampler]] [[vk::binding(0)]] TextureCube EnvironmentTexture : register(t0);
+[[vk::combinedImageSampler]] [[vk::binding(0)]] SamplerState EnvironmentSampler : register(s0);
+[[vk::binding(1)]] [[vk::image_format("rgba16f")]] RWTexture2DArray<float4> PrefilteredImage : register(u1);
+[[vk::push_constant]] Parameters ParametersPC;
+
+// Direction through texel center of cube face, per Vulkan cube map layout
+float3 cube_direction(uint3 id, float size)
+{
+    float2 uv = (float2(id.xy) + 0.5f) / size * 2.0f - 1.0f;
+
+    switch (id.z)
+    {
+    case 0: return normalize(float3(1.0f, -uv.y, -uv.x));
+    case 1: return normalize(float3(-1.0f, -uv.y, uv.x));
+    case 2: return normalize(float3(uv.x, 1.0f, uv.y));
+    case 3: return normalize(float3(uv.x, -1.0f, -uv.y));
+    case 4: return normalize(float3(uv.x, -uv.y, 1.0f));
+    default: return normalize(float3(-uv.x, -uv.y, -1.0f));
+    }
+}
+
+float2 hammersley(uint i, uint count)
+{
+    return float2(float(i) / float(count), reversebits(i) * 2.3283064365386963e-10f);
+}
+
+float3 importance_sample_ggx(float2 xi, float3 normal, float alpha)
+{
+    float phi = 2.0f * PI * xi.x;
+    float cos_theta = sqrt((1.0f - xi.y) / (1.0f + (alpha * alpha - 1.0f) * xi.y));
+    float sin_theta = sqrt(1.0f - cos_theta * cos_theta);
+
+    float3 up = abs(normal.z) < 0.999f ? float3(0.0f, 0.0f, 1.0f) : float3(1.0f, 0.0f, 0.0f);
+    float3 tangent = normalize(cross(up, normal));
+    float3 bitangent = cross(normal, tangent);
+
+    return normalize(tangent * cos(phi) * sin_theta + bitangent * sin(phi) * sin_theta + normal * cos_theta);
+}
+
+float distribution_ggx(float n_dot_h, float alpha)
+{
+    float alpha2 = alpha * alpha;
+    float denominator = n_dot_h * n_dot_h * (alpha2 - 1.0f) + 1.0f;
+
+    return alpha2 / (PI * denominator * denominator);
+}
+
+// GGX importance sampled environment with view direction equal to normal,
+// samples are taken from lower resolution levels to reduce noise
+[numthreads(8, 8, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    uint width, height, layers;
+    PrefilteredImage.GetDimensions(width, height, layers);
+
+    if (id.x >= width || id.y >= height)
+    {
+        return;
+    }
+
+    float3 normal = cube_direction(id, width);
+
+    if (ParametersPC.Roughness == 0.0f)
+    {
+        PrefilteredImage[id] = float4(EnvironmentTexture.SampleLevel(EnvironmentSampler, normal, 0.0f).rgb, 1.0f);
+        return;
+    }
+
+    uint source_width, source_height, source_levels;
+    EnvironmentTexture.GetDimensions(0, source_width, source_height, source_levels);
+
+    float alpha = ParametersPC.Roughness * ParametersPC.Roughness;
+    float texel_solid_angle = 4.0f * PI / (6.0f * source_width * source_width);
+
+    float3 color = 0.0f;
+    float total_weight = 0.0f;
+
+    for (uint i = 0; i < SAMPLE_COUNT; ++i)
+    {
+        float3 half_vector = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, alpha);
+        float3 light_direction = normalize(2.0f * dot(normal, half_vector) * half_vector - normal);
+
+        float n_dot_l = dot(normal, light_direction);
+
+        if (n_dot_l > 0.0f)
+        {
+            float n_dot_h = saturate(dot(normal, half_vector));
+            float pdf = distribution_ggx(n_dot_h, alpha) * 0.25f + 1e-4f;
+            float sample_solid_angle = 1.0f / (SAMPLE_COUNT * pdf + 1e-4f);
+            float lod = max(0.5f * log2(sample_solid_angle / texel_solid_angle), 0.0f);
+
+            color += EnvironmentTexture.SampleLevel(EnvironmentSampler, light_direction, lod).rgb * n_dot_l;
+            total_weight += n_dot_l;
+        }
+    }
+
+    PrefilteredImage[id] = float4(color / total_weight, 1.0f);
+}
@@ -0,0 +1,57 @@
+struct Shadow
+{
+    matrix MVP;
+    // Offset of node skin in joint matrices, negative for unskinned nodes
+    int JointOffset;
+    // Node weights in morph weights and primitive targets in morph deltas,
+    // no targets when count is zero
+    uint WeightOffset;
+    uint DeltaOffset;
+    uint TargetCount;
+    uint VertexCount;
+};
+
+[[vk::binding(3, 0)]] StructuredBuffer<float4x4> JointMatrices : register(t3, space0);
+[[vk::binding(4, 0)]] StructuredBuffer<float4> MorphDeltas : register(t4, space0);
+[[vk::binding(5, 0)]] StructuredBuffer<float> MorphWeights : register(t5, space0);
+[[vk::push_constant]] Shadow ShadowPC;
+
+struct VertexInput
+{
+    [[vk::location(0)]] float3 Position : POSITION;
+    [[vk::location(5)]] uint4 Joints : BLENDINDICES0;
+    [[vk::location(6)]] float4 Weights : BLENDWEIGHT0;
+    uint VertexIndex : SV_VertexID;
+};
+
+float4x4 skin_matrix(uint4 joints, float4 weights, uint offset)
+{
+    return weights.x * JointMatrices[offset + joints.x]
+        + weights.y * JointMatrices[offset + joints.y]
+        + weights.z * JointMatrices[offset + joints.z]
+        + weights.w * JointMatrices[offset + joints.w];
+}
+
+float4 main(VertexInput IN) : SV_Position
+{
+    float3 morphedPosition = IN.Position;
+
+    // Only position deltas matter for depth
+    for (uint target = 0; target < ShadowPC.TargetCount; target++)
+    {
+        float weight = MorphWeights[ShadowPC.WeightOffset + target];
+        uint delta = ShadowPC.DeltaOffset
+            + 3 * (target * ShadowPC.VertexCount + IN.VertexIndex);
+
+        morphedPosition += weight * MorphDeltas[delta].xyz;
+    }
+
+    float4 position = float4(morphedPosition, 1.0f);
+
+    if (ShadowPC.JointOffset >= 0)
+    {
+        position = mul(skin_matrix(IN.Joints, IN.Weights, ShadowPC.JointOffset), position);
+    }
+
+    return mul(ShadowPC.MVP, position);
+}
@@ -0,0 +1,30 @@
+struct ViewProjection
+{
+    matrix VP;
+    matrix InverseVP;
+    float3 CameraPosition;
+    uint LightCount;
+    uint ShadowFilterRadius;
+};
+
+[[vk::binding(0, 0)]] ConstantBuffer<ViewProjection> ViewProjectionCB : register(b0, space0);
+
+[[vk::combinedImageSampler]] [[vk::binding(3, 2)]] TextureCube EnvironmentTexture : register(t3, space2);
+[[vk::combinedImageSampler]] [[vk::binding(3, 2)]] SamplerState EnvironmentSampler : register(s3, space2);
+
+struct PixelShaderInput
+{
+    [[vk::location(0)]] float2 ClipPosition : TEXCOORD0;
+};
+
+float4 main(PixelShaderInput IN) : SV_Target0
+{
+    // Two unprojected points give view ray for both perspective and
+    // orthographic projections, including infinite far plane
+    float4 near = mul(ViewProjectionCB.InverseVP, float4(IN.ClipPosition, 0.0f, 1.0f));
+    float4 middle = mul(ViewProjectionCB.InverseVP, float4(IN.ClipPosition, 0.5f, 1.0f));
+
+    float3 direction = normalize(middle.xyz / middle.w - near.xyz / near.w);
+
+    return float4(EnvironmentTexture.SampleLevel(EnvironmentSampler, direction, 0.0f).rgb, 1.0f);
+}
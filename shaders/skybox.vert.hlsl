@@ -0,0 +1,18 @@
+struct VertexShaderOutput
+{
+    [[vk::location(0)]] float2 ClipPosition : TEXCOORD0;
+    float4 Position : SV_Position;
+};
+
+// Fullscreen triangle at far plane, generated from vertex index
+VertexShaderOutput main(uint vertex_id : SV_VertexID)
+{
+    VertexShaderOutput OUT;
+
+    float2 position = float2((vertex_id << 1) & 2, vertex_id & 2) * 2.0f - 1.0f;
+
+    OUT.Position = float4(position, 1.0f, 1.0f);
+    OUT.ClipPosition = position;
+
+    return OUT;
+}
@@ -0,0 +1,162 @@
+use crate::gltf_wrapper::Aabb;
+
+const ORBIT_SPEED: f32 = 0.01; // radians per pixel
+const ZOOM_FACTOR: f32 = 0.9; // distance scale per wheel step
+const MIN_DISTANCE: f32 = 1e-3;
+const ALTITUDE_EPSILON: f32 = 1e-3;
+const NEAR_RATIO: f32 = 1e-3; // min near plane relative to scene radius
+const FRAMING_MARGIN: f32 = 1.1;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+	Left,
+	Middle,
+	Right,
+}
+
+#[derive(Clone, Copy)]
+pub struct OrbitCamera {
+	target: glm::Vec3,
+	distance: f32,
+	azimuth: f32,
+	altitude: f32,
+	fov: f32,
+	scene_center: glm::Vec3,
+	scene_radius: f32,
+}
+
+impl OrbitCamera {
+	pub fn new(bounds: &Aabb) -> Self {
+		let mut camera = Self {
+			target: glm::Vec3::zeros(),
+			distance: 1.0,
+			azimuth: 0.0,
+			altitude: std::f32::consts::FRAC_PI_2,
+			fov: 90.0_f32.to_radians(),
+			scene_center: glm::Vec3::zeros(),
+			scene_radius: 1.0,
+		};
+
+		camera.frame(bounds);
+		camera
+	}
+
+	// Keeps orientation, fits bounding sphere into vertical field of view
+	pub fn frame(&mut self, bounds: &Aabb) {
+		let radius = bounds.radius().max(MIN_DISTANCE);
+
+		self.scene_center = bounds.center();
+		self.scene_radius = radius;
+		self.target = self.scene_center;
+		self.distance = FRAMING_MARGIN * radius / (self.fov * 0.5).sin();
+	}
+
+	pub fn position(&self) -> glm::Vec3 {
+		let y = self.altitude.cos();
+		let altitude_sin = self.altitude.sin();
+		let x = self.azimuth.sin() * altitude_sin;
+		let z = self.azimuth.cos() * altitude_sin;
+
+		self.target + glm::vec3(x, y, z) * self.distance
+	}
+
+	pub fn view(&self) -> glm::Mat4 {
+		let up = glm::vec3(0.0, 1.0, 0.0);
+
+		glm::look_at(&self.position(), &self.target, &up)
+	}
+
+	pub fn projection(&self, width: f32, height: f32) -> glm::Mat4 {
+		// Clip planes enclose the scene wherever the camera is
+		let scene_distance =
+			glm::distance(&self.position(), &self.scene_center);
+		let far = scene_distance + self.scene_radius;
+		let near = (scene_distance - self.scene_radius)
+			.max(self.scene_radius * NEAR_RATIO);
+
+		glm::perspective_fov_rh_zo(self.fov, width, height, near, far)
+	}
+
+	pub fn orbit(&mut self, dx: f32, dy: f32) {
+		self.azimuth -= dx * ORBIT_SPEED;
+		self.altitude = (self.altitude - dy * ORBIT_SPEED)
+			.clamp(ALTITUDE_EPSILON, std::f32::consts::PI - ALTITUDE_EPSILON);
+	}
+
+	// Moves target so that the point under the cursor follows it
+	pub fn pan(&mut self, dx: f32, dy: f32, viewport_height: f32) {
+		let view = self.view();
+		let right = glm::vec3(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+		let up = glm::vec3(view[(1, 0)], view[(1, 1)], view[(1, 2)]);
+
+		let world_per_pixel =
+			2.0 * self.distance * (self.fov * 0.5).tan() / viewport_height;
+
+		self.target += (up * dy - right * dx) * world_per_pixel;
+	}
+
+	pub fn zoom(&mut self, steps: f32) {
+		self.distance =
+			(self.distance * ZOOM_FACTOR.powf(steps)).max(MIN_DISTANCE);
+	}
+}
+
+pub struct CameraController {
+	camera: OrbitCamera,
+	initial_camera: OrbitCamera,
+	cursor_position: Option<glm::Vec2>,
+	pressed_button: Option<MouseButton>,
+}
+
+impl CameraController {
+	pub fn new(camera: OrbitCamera) -> Self {
+		Self {
+			camera,
+			initial_camera: camera,
+			cursor_position: None,
+			pressed_button: None,
+		}
+	}
+
+	pub fn camera(&self) -> &OrbitCamera {
+		&self.camera
+	}
+
+	pub fn cursor_moved(&mut self, x: f32, y: f32, viewport_height: f32) {
+		let position = glm::vec2(x, y);
+
+		if let Some(last_position) = self.cursor_position {
+			let delta = position - last_position;
+
+			match self.pressed_button {
+				Some(MouseButton::Left) => self.camera.orbit(delta.x, delta.y),
+				Some(MouseButton::Middle) => {
+					self.camera.pan(delta.x, delta.y, viewport_height)
+				}
+				_ => (),
+			}
+		}
+
+		self.cursor_position = Some(position);
+	}
+
+	pub fn mouse_button(&mut self, button: MouseButton, pressed: bool) {
+		if pressed {
+			self.pressed_button.get_or_insert(button);
+		} else if self.pressed_button == Some(button) {
+			self.pressed_button = None;
+		}
+	}
+
+	pub fn mouse_wheel(&mut self, steps: f32) {
+		self.camera.zoom(steps);
+	}
+
+	pub fn reset(&mut self) {
+		self.camera = self.initial_camera;
+	}
+
+	pub fn frame(&mut self, bounds: &Aabb) {
+		self.camera.frame(bounds);
+	}
+}
@@ -0,0 +1,289 @@
+use {
+	super::{Node, Transform},
+	anyhow::{anyhow, Result},
+	gltf::animation::util::ReadOutputs,
+	std::collections::HashMap,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Property {
+	Translation,
+	Rotation,
+	Scale,
+	Weights,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Interpolation {
+	Step,
+	Linear,
+	CubicSpline,
+}
+
+// Keyframe values are flattened, each one has width components. Cubic
+// spline keyframes hold in-tangent, value and out-tangent in a row
+struct Channel {
+	node: usize,
+	property: Property,
+	interpolation: Interpolation,
+	times: Vec<f32>,
+	values: Vec<f32>,
+	width: usize,
+}
+
+impl Channel {
+	fn new(
+		channel: &gltf::animation::Channel,
+		buffers: &[Vec<u8>],
+		node: usize,
+	) -> Result<Self> {
+		let reader =
+			channel.reader(|buffer| Some(buffers[buffer.index()].as_slice()));
+
+		let times = reader
+			.read_inputs()
+			.ok_or_else(|| anyhow!("Animation channel has no inputs!"))?
+			.collect::<Vec<_>>();
+
+		let outputs = reader
+			.read_outputs()
+			.ok_or_else(|| anyhow!("Animation channel has no outputs!"))?;
+
+		let interpolation = match channel.sampler().interpolation() {
+			gltf::animation::Interpolation::Step => Interpolation::Step,
+			gltf::animation::Interpolation::Linear => Interpolation::Linear,
+			gltf::animation::Interpolation::CubicSpline => {
+				Interpolation::CubicSpline
+			}
+		};
+
+		let values_per_key = match interpolation {
+			Interpolation::CubicSpline => 3,
+			_ => 1,
+		};
+
+		let (property, values, width) = match outputs {
+			ReadOutputs::Translations(translations) => {
+				(Property::Translation, translations.flatten().collect(), 3)
+			}
+			ReadOutputs::Rotations(rotations) => {
+				let rotations = rotations.into_f32().flatten().collect();
+				(Property::Rotation, rotations, 4)
+			}
+			ReadOutputs::Scales(scales) => {
+				(Property::Scale, scales.flatten().collect(), 3)
+			}
+			// One weight per morph target in each keyframe value
+			ReadOutputs::MorphTargetWeights(weights) => {
+				let weights = weights.into_f32().collect::<Vec<_>>();
+				let keyframe_values = (times.len() * values_per_key).max(1);
+				let width = weights.len() / keyframe_values;
+
+				(Property::Weights, weights, width)
+			}
+		};
+
+		if times.is_empty()
+			|| width == 0
+			|| values.len() != times.len() * values_per_key * width
+		{
+			return Err(anyhow!(
+				"Animation channel has {} inputs and {} output components!",
+				times.len(),
+				values.len()
+			));
+		}
+
+		let channel = Self {
+			node,
+			property,
+			interpolation,
+			times,
+			values,
+			width,
+		};
+
+		Ok(channel)
+	}
+
+	fn element(&self, index: usize) -> &[f32] {
+		&self.values[index * self.width..(index + 1) * self.width]
+	}
+
+	fn value(&self, key: usize) -> &[f32] {
+		match self.interpolation {
+			Interpolation::CubicSpline => self.element(3 * key + 1),
+			_ => self.element(key),
+		}
+	}
+
+	// Scale with negative component product turns the node inside out
+	fn mirrors(&self) -> bool {
+		self.property == Property::Scale
+			&& (0..self.times.len())
+				.any(|key| self.value(key).iter().product::<f32>() < 0.0)
+	}
+
+	// Time outside of keyframes is clamped to the first or last one
+	fn sample(&self, time: f32) -> Vec<f32> {
+		let next = self.times.partition_point(|&key_time| key_time <= time);
+
+		if next == 0 {
+			return self.value(0).to_vec();
+		}
+
+		if next == self.times.len() {
+			return self.value(next - 1).to_vec();
+		}
+
+		let previous = next - 1;
+		let delta = self.times[next] - self.times[previous];
+		let t = (time - self.times[previous]) / delta;
+
+		let from = self.value(previous);
+		let to = self.value(next);
+
+		match self.interpolation {
+			Interpolation::Step => from.to_vec(),
+			Interpolation::Linear if self.property == Property::Rotation => {
+				let from = quat_from_slice(from);
+				let to = quat_from_slice(to);
+
+				glm::quat_slerp(&from, &to, t).coords.as_slice().to_vec()
+			}
+			Interpolation::Linear => from
+				.iter()
+				.zip(to)
+				.map(|(from, to)| from + (to - from) * t)
+				.collect(),
+			Interpolation::CubicSpline => {
+				let t2 = t * t;
+				let t3 = t2 * t;
+
+				let out_tangent = self.element(3 * previous + 2);
+				let in_tangent = self.element(3 * next);
+
+				(0..self.width)
+					.map(|i| {
+						from[i] * (2.0 * t3 - 3.0 * t2 + 1.0)
+							+ out_tangent[i] * delta * (t3 - 2.0 * t2 + t)
+							+ to[i] * (-2.0 * t3 + 3.0 * t2)
+							+ in_tangent[i] * delta * (t3 - t2)
+					})
+					.collect()
+			}
+		}
+	}
+
+	fn apply(&self, time: f32, node: &mut Node) {
+		let value = self.sample(time);
+
+		if self.property == Property::Weights {
+			node.set_weights(&value);
+			return;
+		}
+
+		// Animated nodes are not allowed to have matrix transforms
+		let (mut translation, mut rotation, mut scale) =
+			match *node.local_transform() {
+				Transform::Decomposed {
+					translation,
+					rotation,
+					scale,
+				} => (translation, rotation, scale),
+				Transform::Matrix(_) => (
+					glm::Vec3::zeros(),
+					glm::Quat::identity(),
+					glm::vec3(1.0, 1.0, 1.0),
+				),
+			};
+
+		match self.property {
+			Property::Translation => translation = glm::make_vec3(&value),
+			// Cubic spline doesn't keep rotations normalized
+			Property::Rotation => {
+				rotation = glm::quat_normalize(&quat_from_slice(&value))
+			}
+			Property::Scale => scale = glm::make_vec3(&value),
+			Property::Weights => unreachable!(),
+		}
+
+		node.set_local_transform(Transform::Decomposed {
+			translation,
+			rotation,
+			scale,
+		});
+	}
+}
+
+fn quat_from_slice(value: &[f32]) -> glm::Quat {
+	glm::quat(value[0], value[1], value[2], value[3])
+}
+
+pub struct Animation {
+	name: Option<String>,
+	channels: Vec<Channel>,
+	duration: f32,
+}
+
+impl Animation {
+	// Maps glTF node index to scene node index, channels targeting nodes
+	// of other scenes are dropped
+	pub fn new(
+		animation: &gltf::Animation,
+		buffers: &[Vec<u8>],
+		node_indices: &HashMap<usize, usize>,
+	) -> Result<Self> {
+		log::info!(
+			"Creating animation; index: {}, name: {}",
+			animation.index(),
+			animation.name().unwrap_or_default()
+		);
+
+		let channels = animation
+			.channels()
+			.filter_map(|ref channel| {
+				let node =
+					node_indices.get(&channel.target().node().index())?;
+				Some(Channel::new(channel, buffers, *node))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let duration = channels
+			.iter()
+			.filter_map(|channel| channel.times.last().copied())
+			.fold(0.0, f32::max);
+
+		let animation = Self {
+			name: animation.name().map(str::to_owned),
+			channels,
+			duration,
+		};
+
+		Ok(animation)
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	// Time of the last keyframe in seconds
+	pub fn duration(&self) -> f32 {
+		self.duration
+	}
+
+	// Nodes which get negative scale in some keyframe
+	pub fn mirrored_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+		self.channels
+			.iter()
+			.filter(|channel| channel.mirrors())
+			.map(|channel| channel.node)
+	}
+
+	// Updates local transforms only, world ones are left to the scene
+	pub fn apply(&self, time: f32, nodes: &mut [Node]) {
+		for channel in &self.channels {
+			channel.apply(time, &mut nodes[channel.node]);
+		}
+	}
+}
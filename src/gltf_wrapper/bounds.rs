@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Result};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+	pub min: glm::Vec3,
+	pub max: glm::Vec3,
+}
+
+impl Aabb {
+	pub fn new(min: glm::Vec3, max: glm::Vec3) -> Self {
+		Self { min, max }
+	}
+
+	// POSITION accessors are required to provide min and max
+	pub fn from_accessor(accessor: &gltf::Accessor) -> Result<Self> {
+		let min = accessor
+			.min()
+			.ok_or_else(|| anyhow!("Position accessor has no min bound!"))?;
+
+		let max = accessor
+			.max()
+			.ok_or_else(|| anyhow!("Position accessor has no max bound!"))?;
+
+		let aabb = Self {
+			min: Self::json_to_vec3(&min)?,
+			max: Self::json_to_vec3(&max)?,
+		};
+
+		Ok(aabb)
+	}
+
+	pub fn union(&self, other: &Self) -> Self {
+		Self {
+			min: glm::min2(&self.min, &other.min),
+			max: glm::max2(&self.max, &other.max),
+		}
+	}
+
+	pub fn transform(&self, matrix: &glm::Mat4) -> Self {
+		let corners = (0..8).map(|corner| {
+			let pick =
+				|bit, min, max| if corner & bit == 0 { min } else { max };
+
+			let x = pick(1, self.min.x, self.max.x);
+			let y = pick(2, self.min.y, self.max.y);
+			let z = pick(4, self.min.z, self.max.z);
+
+			let corner = matrix * glm::vec4(x, y, z, 1.0);
+			glm::vec4_to_vec3(&corner)
+		});
+
+		corners
+			.map(|corner| Self::new(corner, corner))
+			.reduce(|aabb, corner| aabb.union(&corner))
+			.unwrap()
+	}
+
+	pub fn center(&self) -> glm::Vec3 {
+		(self.min + self.max) * 0.5
+	}
+
+	pub fn radius(&self) -> f32 {
+		glm::distance(&self.min, &self.max) * 0.5
+	}
+
+	fn json_to_vec3(value: &gltf::json::Value) -> Result<glm::Vec3> {
+		let component = |index: usize| {
+			value
+				.get(index)
+				.and_then(gltf::json::Value::as_f64)
+				.map(|component| component as f32)
+				.ok_or_else(|| anyhow!("Invalid accessor bound: {value}"))
+		};
+
+		Ok(glm::vec3(component(0)?, component(1)?, component(2)?))
+	}
+}
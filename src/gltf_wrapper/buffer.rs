@@ -0,0 +1,68 @@
+use {
+	super::uri,
+	anyhow::{anyhow, bail, Context, Result},
+	gltf::Gltf,
+	std::path::Path,
+};
+
+pub fn load_buffers(gltf: &Gltf, base_dir: &Path) -> Result<Vec<Vec<u8>>> {
+	gltf.buffers()
+		.map(|ref buffer| load_buffer(buffer, gltf.blob.as_deref(), base_dir))
+		.collect()
+}
+
+fn load_buffer(
+	buffer: &gltf::Buffer,
+	blob: Option<&[u8]>,
+	base_dir: &Path,
+) -> Result<Vec<u8>> {
+	let length = buffer.length();
+
+	// Spec requires at least one byte, empty data can't back GPU buffer
+	if length == 0 {
+		bail!("Buffer {} is empty", buffer.index());
+	}
+
+	let mut data = match buffer.source() {
+		gltf::buffer::Source::Uri(uri) => {
+			let data = uri::read(uri, base_dir).with_context(|| {
+				format!("Unable to load buffer {}", buffer.index())
+			})?;
+
+			if data.bytes.len() < length {
+				bail!(
+					"Buffer {} is shorter than declared {} bytes: {} bytes",
+					buffer.index(),
+					length,
+					data.bytes.len()
+				);
+			}
+
+			data.bytes
+		}
+		gltf::buffer::Source::Bin => {
+			let blob = blob.ok_or_else(|| {
+				anyhow!(
+					"Buffer {} refers to BIN chunk, but file has none",
+					buffer.index()
+				)
+			})?;
+
+			// BIN chunk is allowed to be padded with up to 3 bytes
+			if blob.len() < length || blob.len() > length + 3 {
+				bail!(
+					"BIN chunk length {} mismatches buffer {} length {}",
+					blob.len(),
+					buffer.index(),
+					length
+				);
+			}
+
+			blob.to_vec()
+		}
+	};
+
+	data.truncate(length);
+
+	Ok(data)
+}
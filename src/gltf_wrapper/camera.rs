@@ -0,0 +1,101 @@
+use anyhow::Result;
+
+#[derive(Clone, Copy)]
+pub enum Projection {
+	Perspective {
+		aspect_ratio: Option<f32>,
+		yfov: f32,
+		znear: f32,
+		zfar: Option<f32>,
+	},
+	Orthographic {
+		xmag: f32,
+		ymag: f32,
+		znear: f32,
+		zfar: f32,
+	},
+}
+
+pub struct Camera {
+	name: Option<String>,
+	projection: Projection,
+}
+
+impl Camera {
+	pub fn new(camera: &gltf::Camera) -> Result<Self> {
+		log::info!(
+			"Creating camera; index: {}, name: {}",
+			camera.index(),
+			camera.name().unwrap_or_default()
+		);
+
+		let projection = match camera.projection() {
+			gltf::camera::Projection::Perspective(perspective) => {
+				Projection::Perspective {
+					aspect_ratio: perspective.aspect_ratio(),
+					yfov: perspective.yfov(),
+					znear: perspective.znear(),
+					zfar: perspective.zfar(),
+				}
+			}
+			gltf::camera::Projection::Orthographic(orthographic) => {
+				Projection::Orthographic {
+					xmag: orthographic.xmag(),
+					ymag: orthographic.ymag(),
+					znear: orthographic.znear(),
+					zfar: orthographic.zfar(),
+				}
+			}
+		};
+
+		let camera = Self {
+			name: camera.name().map(str::to_owned),
+			projection,
+		};
+
+		Ok(camera)
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	// None when the viewport aspect ratio should be used
+	pub fn aspect_ratio(&self) -> Option<f32> {
+		match self.projection {
+			Projection::Perspective { aspect_ratio, .. } => aspect_ratio,
+			Projection::Orthographic { xmag, ymag, .. } => Some(xmag / ymag),
+		}
+	}
+
+	pub fn projection_matrix(&self, viewport_aspect_ratio: f32) -> glm::Mat4 {
+		match self.projection {
+			Projection::Perspective {
+				aspect_ratio,
+				yfov,
+				znear,
+				zfar,
+			} => {
+				let aspect_ratio =
+					aspect_ratio.unwrap_or(viewport_aspect_ratio);
+
+				match zfar {
+					Some(zfar) => {
+						glm::perspective_rh_zo(aspect_ratio, yfov, znear, zfar)
+					}
+					None => glm::infinite_perspective_rh_zo(
+						aspect_ratio,
+						yfov,
+						znear,
+					),
+				}
+			}
+			Projection::Orthographic {
+				xmag,
+				ymag,
+				znear,
+				zfar,
+			} => glm::ortho_rh_zo(-xmag, xmag, -ymag, ymag, znear, zfar),
+		}
+	}
+}
@@ -0,0 +1,71 @@
+use {
+	super::uri,
+	anyhow::{anyhow, Context, Result},
+	std::path::Path,
+};
+
+pub struct ImageData {
+	pub mime_type: Option<String>,
+	pub bytes: Vec<u8>,
+}
+
+impl ImageData {
+	// Decodes PNG or JPEG data into RGBA8 pixels, format is guessed from
+	// content when mime type is unknown
+	pub fn decode(&self) -> Result<::image::RgbaImage> {
+		let format = match self.mime_type.as_deref() {
+			Some("image/png") => Some(::image::ImageFormat::Png),
+			Some("image/jpeg") => Some(::image::ImageFormat::Jpeg),
+			_ => None,
+		};
+
+		let image = match format {
+			Some(format) => {
+				::image::load_from_memory_with_format(&self.bytes, format)?
+			}
+			None => ::image::load_from_memory(&self.bytes)?,
+		};
+
+		Ok(image.into_rgba8())
+	}
+}
+
+pub fn load_image(
+	image: &gltf::Image,
+	buffers: &[Vec<u8>],
+	base_dir: &Path,
+) -> Result<ImageData> {
+	let image_data = match image.source() {
+		gltf::image::Source::View { view, mime_type } => {
+			let begin = view.offset();
+			let end = begin + view.length();
+
+			let bytes = buffers[view.buffer().index()]
+				.get(begin..end)
+				.ok_or_else(|| {
+					anyhow!(
+						"Image {} buffer view is out of buffer bounds",
+						image.index()
+					)
+				})?
+				.to_vec();
+
+			ImageData {
+				mime_type: Some(mime_type.to_owned()),
+				bytes,
+			}
+		}
+		gltf::image::Source::Uri { uri, mime_type } => {
+			let data = uri::read(uri, base_dir).with_context(|| {
+				format!("Unable to load image {}", image.index())
+			})?;
+
+			ImageData {
+				mime_type: mime_type.map(str::to_owned).or(data.mime_type),
+				bytes: data.bytes,
+			}
+		}
+	};
+
+	Ok(image_data)
+}
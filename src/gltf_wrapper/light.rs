@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+#[derive(Clone, Copy)]
+pub enum LightKind {
+	Directional,
+	Point,
+	Spot {
+		inner_cone_angle: f32,
+		outer_cone_angle: f32,
+	},
+}
+
+// Light instances take position and orientation from their node, pointing
+// along local -Z
+pub struct Light {
+	color: glm::Vec3,
+	intensity: f32,
+	range: Option<f32>,
+	kind: LightKind,
+}
+
+impl Light {
+	pub fn new(light: &gltf::khr_lights_punctual::Light) -> Result<Self> {
+		log::info!(
+			"Creating light; index: {}, name: {}",
+			light.index(),
+			light.name().unwrap_or_default()
+		);
+
+		let kind = match light.kind() {
+			gltf::khr_lights_punctual::Kind::Directional => {
+				LightKind::Directional
+			}
+			gltf::khr_lights_punctual::Kind::Point => LightKind::Point,
+			gltf::khr_lights_punctual::Kind::Spot {
+				inner_cone_angle,
+				outer_cone_angle,
+			} => LightKind::Spot {
+				inner_cone_angle,
+				outer_cone_angle,
+			},
+		};
+
+		let light = Self {
+			color: light.color().into(),
+			intensity: light.intensity(),
+			range: light.range(),
+			kind,
+		};
+
+		Ok(light)
+	}
+
+	pub fn color(&self) -> &glm::Vec3 {
+		&self.color
+	}
+
+	pub fn intensity(&self) -> f32 {
+		self.intensity
+	}
+
+	// None means infinite range
+	pub fn range(&self) -> Option<f32> {
+		self.range
+	}
+
+	pub fn kind(&self) -> LightKind {
+		self.kind
+	}
+}
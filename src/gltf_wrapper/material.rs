@@ -0,0 +1,182 @@
+use anyhow::Result;
+
+// Opaque ignores alpha, Mask discards below cutoff, Blend draws in sorted pass
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlphaMode {
+	Opaque,
+	Mask,
+	Blend,
+}
+
+// Texture is an index into scene textures
+#[derive(Clone, Copy)]
+pub struct TextureInfo {
+	pub texture: usize,
+	pub tex_coord: u32,
+}
+
+pub struct Material {
+	base_color_factor: glm::Vec4,
+	base_color_texture: Option<TextureInfo>,
+	metallic_factor: f32,
+	roughness_factor: f32,
+	metallic_roughness_texture: Option<TextureInfo>,
+	normal_texture: Option<TextureInfo>,
+	normal_scale: f32,
+	occlusion_texture: Option<TextureInfo>,
+	occlusion_strength: f32,
+	emissive_factor: glm::Vec3,
+	emissive_texture: Option<TextureInfo>,
+	double_sided: bool,
+	alpha_mode: AlphaMode,
+	alpha_cutoff: f32,
+}
+
+impl Material {
+	// Index is None for the default material of primitives without one.
+	// Maps glTF texture and its sRGB usage to scene texture index
+	pub fn new(
+		material: &gltf::Material,
+		mut add_texture: impl FnMut(&gltf::Texture, bool) -> Result<usize>,
+	) -> Result<Self> {
+		log::info!(
+			"Creating material; index: {:?}, name: {}",
+			material.index(),
+			material.name().unwrap_or_default()
+		);
+
+		let pbr = material.pbr_metallic_roughness();
+
+		let mut texture_info =
+			|texture: &gltf::Texture, tex_coord, srgb| -> Result<_> {
+				let info = TextureInfo {
+					texture: add_texture(texture, srgb)?,
+					tex_coord,
+				};
+
+				Ok(info)
+			};
+
+		let base_color_texture = pbr
+			.base_color_texture()
+			.map(|info| texture_info(&info.texture(), info.tex_coord(), true))
+			.transpose()?;
+
+		let metallic_roughness_texture = pbr
+			.metallic_roughness_texture()
+			.map(|info| texture_info(&info.texture(), info.tex_coord(), false))
+			.transpose()?;
+
+		let normal_texture = material
+			.normal_texture()
+			.map(|normal| -> Result<_> {
+				let info =
+					texture_info(&normal.texture(), normal.tex_coord(), false)?;
+
+				Ok((info, normal.scale()))
+			})
+			.transpose()?;
+
+		let occlusion_texture = material
+			.occlusion_texture()
+			.map(|occlusion| -> Result<_> {
+				let info = texture_info(
+					&occlusion.texture(),
+					occlusion.tex_coord(),
+					false,
+				)?;
+
+				Ok((info, occlusion.strength()))
+			})
+			.transpose()?;
+
+		let emissive_texture = material
+			.emissive_texture()
+			.map(|info| texture_info(&info.texture(), info.tex_coord(), true))
+			.transpose()?;
+
+		let alpha_mode = match material.alpha_mode() {
+			gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+			gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+			gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+		};
+
+		let material = Self {
+			base_color_factor: pbr.base_color_factor().into(),
+			base_color_texture,
+			metallic_factor: pbr.metallic_factor(),
+			roughness_factor: pbr.roughness_factor(),
+			metallic_roughness_texture,
+			normal_texture: normal_texture.map(|(info, _)| info),
+			normal_scale: normal_texture.map_or(1.0, |(_, scale)| scale),
+			occlusion_texture: occlusion_texture.map(|(info, _)| info),
+			occlusion_strength: occlusion_texture
+				.map_or(1.0, |(_, strength)| strength),
+			emissive_factor: material.emissive_factor().into(),
+			emissive_texture,
+			double_sided: material.double_sided(),
+			alpha_mode,
+			// Cutoff defaults to a half in the spec
+			alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+		};
+
+		Ok(material)
+	}
+
+	pub fn base_color_factor(&self) -> &glm::Vec4 {
+		&self.base_color_factor
+	}
+
+	pub fn base_color_texture(&self) -> Option<&TextureInfo> {
+		self.base_color_texture.as_ref()
+	}
+
+	pub fn metallic_factor(&self) -> f32 {
+		self.metallic_factor
+	}
+
+	pub fn roughness_factor(&self) -> f32 {
+		self.roughness_factor
+	}
+
+	pub fn metallic_roughness_texture(&self) -> Option<&TextureInfo> {
+		self.metallic_roughness_texture.as_ref()
+	}
+
+	pub fn normal_texture(&self) -> Option<&TextureInfo> {
+		self.normal_texture.as_ref()
+	}
+
+	pub fn normal_scale(&self) -> f32 {
+		self.normal_scale
+	}
+
+	pub fn occlusion_texture(&self) -> Option<&TextureInfo> {
+		self.occlusion_texture.as_ref()
+	}
+
+	pub fn occlusion_strength(&self) -> f32 {
+		self.occlusion_strength
+	}
+
+	pub fn emissive_factor(&self) -> &glm::Vec3 {
+		&self.emissive_factor
+	}
+
+	pub fn emissive_texture(&self) -> Option<&TextureInfo> {
+		self.emissive_texture.as_ref()
+	}
+
+	pub fn double_sided(&self) -> bool {
+		self.double_sided
+	}
+
+	pub fn alpha_mode(&self) -> AlphaMode {
+		self.alpha_mode
+	}
+
+	// Used by Mask mode only
+	pub fn alpha_cutoff(&self) -> f32 {
+		self.alpha_cutoff
+	}
+}
@@ -1,56 +1,57 @@
 use {
-    super::{
-        obsydian::{
-            CommandBuffer,
-            IntoSurface,
-        },
-        GraphicsCore,
-        Primitive,
-    },
-
-    anyhow::Result,
+	super::{Aabb, Primitive},
+	anyhow::Result,
 };
 
-pub struct Mesh
-{
-    primiteves: Vec<Primitive>,
+pub struct Mesh {
+	primitives: Vec<Primitive>,
 }
 
-impl Mesh
-{
-    pub fn new<SurfaceOwner: IntoSurface>(
-        mesh: &gltf::Mesh,
-        graphics_core: &GraphicsCore<SurfaceOwner>,
-    ) -> Result<Self>
-    {
-        log::info!("Creating mesh; index: {}, name: {}", mesh.index(), mesh.name().unwrap_or_default());
-
-        let primiteves = mesh.primitives().map(
-            |ref primitive|
-            {
-                Primitive::new(
-                    primitive,
-                    &graphics_core
-                )
-            }
-        ).collect::<Result<Vec<_>>>()?;
-
-        let mesh = Self
-        {
-            primiteves,
-        };
-
-        Ok(mesh)
-    }
-
-    pub fn render(
-        &self,
-        command_buffer: &CommandBuffer,
-    )
-    {
-        for primiteve in &self.primiteves
-        {
-            primiteve.render(command_buffer)
-        }
-    }
-}
\ No newline at end of file
+impl Mesh {
+	// Maps glTF primitive material to scene material index
+	pub fn new(
+		mesh: &gltf::Mesh,
+		buffers: &[Vec<u8>],
+		mut add_material: impl FnMut(&gltf::Material) -> Result<usize>,
+	) -> Result<Self> {
+		log::info!(
+			"Creating mesh; index: {}, name: {}",
+			mesh.index(),
+			mesh.name().unwrap_or_default()
+		);
+
+		let primitives = mesh
+			.primitives()
+			.map(|ref primitive| {
+				let material = add_material(&primitive.material())?;
+				Primitive::new(primitive, buffers, material)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let mesh = Self { primitives };
+
+		Ok(mesh)
+	}
+
+	pub fn primitives(&self) -> &[Primitive] {
+		&self.primitives
+	}
+
+	pub fn primitives_mut(&mut self) -> &mut [Primitive] {
+		&mut self.primitives
+	}
+
+	// All primitives of a mesh have the same number of morph targets
+	pub fn morph_target_count(&self) -> usize {
+		self.primitives
+			.first()
+			.map_or(0, |primitive| primitive.morph_targets().len())
+	}
+
+	pub fn bounds(&self) -> Option<Aabb> {
+		self.primitives
+			.iter()
+			.map(|primitive| *primitive.bounds())
+			.reduce(|bounds, other| bounds.union(&other))
+	}
+}
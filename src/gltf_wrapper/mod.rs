@@ -1,3 +1,36 @@
+mod animation;
+mod bounds;
+mod buffer;
+mod camera;
+mod image;
+mod light;
+mod material;
+mod mesh;
+mod node;
+mod primitive;
 mod scene;
+mod skin;
+mod sparse;
+mod tangent_space;
+mod texture;
+mod uri;
 
+pub use animation::Animation;
+pub use bounds::Aabb;
+pub use buffer::load_buffers;
+pub use camera::Camera;
+pub use image::load_image;
+pub use image::ImageData;
+pub use light::Light;
+pub use light::LightKind;
+pub use material::AlphaMode;
+pub use material::Material;
+pub use material::TextureInfo;
+pub use mesh::Mesh;
+pub use node::Node;
+pub use node::Transform;
+pub use primitive::Primitive;
 pub use scene::Scene;
+pub use skin::Skin;
+pub use sparse::resolve_sparse_accessors;
+pub use texture::Texture;
@@ -1,90 +1,155 @@
-use {
-    super::{
-        obsydian::{
-            IntoSurface,
-            Buffer,
-            GraphicsPipeline,
-            CommandBuffer,
-        },
-        Mesh,
-        Scene,
-        GraphicsCore,
-        PrimitivePipelineInfo,
-        Primitive,
-    },
-
-    ash::{
-        vk,
-    },
-
-    std::rc::Rc,
-    anyhow::Result,
-
-    gltf::Gltf,
-};
-
-pub struct Node
-{
-    mesh: Option<Mesh>,
-    children: Vec<Node>,
+use anyhow::Result;
+
+#[derive(Clone, Copy)]
+pub enum Transform {
+	Matrix(glm::Mat4),
+	Decomposed {
+		translation: glm::Vec3,
+		rotation: glm::Quat,
+		scale: glm::Vec3,
+	},
+}
+
+impl Transform {
+	pub fn new(transform: gltf::scene::Transform) -> Self {
+		match transform {
+			gltf::scene::Transform::Matrix { matrix } => {
+				let matrix = glm::make_mat4(matrix.concat().as_slice());
+				Transform::Matrix(matrix)
+			}
+			gltf::scene::Transform::Decomposed {
+				translation,
+				rotation: [x, y, z, w],
+				scale,
+			} => Transform::Decomposed {
+				translation: glm::make_vec3(&translation),
+				rotation: glm::quat(x, y, z, w),
+				scale: glm::make_vec3(&scale),
+			},
+		}
+	}
+
+	pub fn matrix(&self) -> glm::Mat4 {
+		match self {
+			Transform::Matrix(matrix) => *matrix,
+			Transform::Decomposed {
+				translation,
+				rotation,
+				scale,
+			} => {
+				glm::translation(translation)
+					* glm::quat_to_mat4(rotation)
+					* glm::scaling(scale)
+			}
+		}
+	}
+}
+
+pub struct Node {
+	index: usize,
+	name: Option<String>,
+	parent: Option<usize>,
+	mesh: Option<usize>,
+	camera: Option<usize>,
+	light: Option<usize>,
+	skin: Option<usize>,
+	weights: Vec<f32>,
+	local_transform: Transform,
+	world_transform: glm::Mat4,
 }
 
-impl Node
-{
-    pub fn new<SurfaceOwner: IntoSurface>(
-        node: &gltf::Node,
-        graphics_core: &GraphicsCore<SurfaceOwner>,
-    )
-    -> Result<Self>
-    {
-        log::info!("Creating node; index: {}, name: {}", node.index(), node.name().unwrap_or_default());
-
-        let mesh = match node.mesh()
-        {
-            Some(ref mesh) => {
-                let mesh = Mesh::new(
-                    mesh,
-                    &graphics_core
-                )?;
-                Some(mesh)
-            },
-            None => {
-                None
-            }
-        };
-
-        let children = node.children().map(
-            |ref node|
-            {
-                Node::new(
-                    node,
-                    graphics_core
-                )
-            }
-        ).collect::<Result<Vec<_>>>()?;
-
-        let node = Self
-        {
-            mesh,
-            children,
-        };
-
-        Ok(node)
-    }
-
-    pub fn render(
-        &self,
-        command_buffer: &CommandBuffer,
-    )
-    {
-        if let Some(mesh) = &self.mesh
-        {
-            mesh.render(command_buffer)
-        }
-
-        for node in &self.children
-        {
-            node.render(command_buffer)
-        }
-    }
-}
\ No newline at end of file
+impl Node {
+	pub fn new(
+		node: &gltf::Node,
+		parent: Option<usize>,
+		mesh: Option<usize>,
+		camera: Option<usize>,
+		light: Option<usize>,
+		skin: Option<usize>,
+	) -> Result<Self> {
+		log::info!(
+			"Creating node; index: {}, name: {}",
+			node.index(),
+			node.name().unwrap_or_default()
+		);
+
+		let local_transform = Transform::new(node.transform());
+
+		// Node weights override default ones of its mesh
+		let weights = node
+			.weights()
+			.or_else(|| node.mesh().and_then(|mesh| mesh.weights()))
+			.unwrap_or_default()
+			.to_vec();
+
+		let node = Self {
+			index: node.index(),
+			name: node.name().map(str::to_owned),
+			parent,
+			mesh,
+			camera,
+			light,
+			skin,
+			weights,
+			local_transform,
+			world_transform: local_transform.matrix(),
+		};
+
+		Ok(node)
+	}
+
+	pub fn index(&self) -> usize {
+		self.index
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	pub fn parent(&self) -> Option<usize> {
+		self.parent
+	}
+
+	pub fn mesh(&self) -> Option<usize> {
+		self.mesh
+	}
+
+	pub fn camera(&self) -> Option<usize> {
+		self.camera
+	}
+
+	pub fn light(&self) -> Option<usize> {
+		self.light
+	}
+
+	// Skinned meshes ignore node transform, joints place their vertices
+	pub fn skin(&self) -> Option<usize> {
+		self.skin
+	}
+
+	// Morph target weights, missing ones are zero
+	pub fn weights(&self) -> &[f32] {
+		&self.weights
+	}
+
+	pub fn set_weights(&mut self, weights: &[f32]) {
+		self.weights.clear();
+		self.weights.extend_from_slice(weights);
+	}
+
+	pub fn local_transform(&self) -> &Transform {
+		&self.local_transform
+	}
+
+	pub fn set_local_transform(&mut self, transform: Transform) {
+		self.local_transform = transform;
+	}
+
+	pub fn world_transform(&self) -> &glm::Mat4 {
+		&self.world_transform
+	}
+
+	pub(super) fn set_world_transform(&mut self, transform: glm::Mat4) {
+		self.world_transform = transform;
+	}
+}
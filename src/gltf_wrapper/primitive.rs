@@ -1,352 +1,718 @@
 use {
-    // super::{
-    //     obsydian::{
-    //         GraphicsPipeline,
-    //         IntoSurface,
-    //         CommandBuffer,
-    //     },
-    //     GraphicsCore,
-    //     PrimitivePipelineInfo,
-    // },
-
-    crate::obsydian::CommandBuffer,
-
-    gltf::{
-        accessor::{
-            Dimensions,
-            DataType,
-            Accessor,
-        },
-        buffer::{
-            View
-        },
-        Semantic,
-    },
-
-    ash::vk::{
-        self,
-        Format,
-    },
-    std::rc::Rc,
-    anyhow::Result,
+	super::{tangent_space, Aabb},
+	anyhow::{anyhow, bail, Result},
+	ash::vk::{self, Format},
+	gltf::{
+		accessor::{Accessor, DataType, Dimensions},
+		buffer::View,
+		Semantic,
+	},
+	std::collections::HashMap,
 };
 
-pub struct Primitive
-{
-    // pipeline: Rc<GraphicsPipeline>,
+pub struct VertexAttribute {
+	pub location: u32,
+	pub format: vk::Format,
+	pub buffer: usize,
+	pub offset: usize,
+	pub stride: usize,
+	// Size of one element in bytes
+	pub size: usize,
 }
 
-impl Primitive
-{
-    const MAX_ACCESSORS : usize = 8;
-
-    pub fn new(
-        primitive: &gltf::Primitive,
-        // graphics_core: &GraphicsCore<SurfaceOwner>,
-    ) -> Result<Self>
-    {
-        log::info!("Creating primitive; index: {}", primitive.index());
-
-        let topology = Self::topology(primitive)?;
-
-        use std::mem::{self, MaybeUninit};
-
-        let mut binding_descriptions: [
-            MaybeUninit<vk::VertexInputBindingDescription>;
-            Self::MAX_ACCESSORS
-        ] = unsafe {
-            MaybeUninit::uninit().assume_init()
-        };
-
-        let mut attribute_descriptions: [
-            MaybeUninit<vk::VertexInputAttributeDescription>;
-            Self::MAX_ACCESSORS
-        ] = unsafe {
-            MaybeUninit::uninit().assume_init()
-        };
-
-        let mut index = 0_usize;
-
-        // positions
-        let positions = match primitive.get(&Semantic::Positions)
-        {
-            Some(positions) => positions,
-            None => Err(anyhow::anyhow!("Primitive has no positions!"))?
-        };
-
-        let (binding_description, attribute_description) = Self::description(
-            &positions,
-            index as _,
-            GraphicsCore::<SurfaceOwner>::POSITIONS_LOCATION,
-        )?;
-
-        binding_descriptions[index] = MaybeUninit::new(binding_description);
-        attribute_descriptions[index] = MaybeUninit::new(attribute_description);
-        index += 1;
-
-        // normals
-        if let Some(normals) = primitive.get(&Semantic::Normals)
-        {
-            let (binding_description, attribute_description) = Self::description(
-                &normals,
-                index as _,
-                GraphicsCore::<SurfaceOwner>::NORMALS_LOCATION,
-            )?;
-
-            binding_descriptions[index] = MaybeUninit::new(binding_description);
-            attribute_descriptions[index] = MaybeUninit::new(attribute_description);
-            index += 1;
-        }
-
-        // tex_coords
-        if let Some(tex_coords) = primitive.get(&Semantic::TexCoords(0))
-        {
-            let (binding_description, attribute_description) = Self::description(
-                &tex_coords,
-                index as _,
-                GraphicsCore::<SurfaceOwner>::TEX_COORDS_LOCATION,
-            )?;
-
-            binding_descriptions[index] = MaybeUninit::new(binding_description);
-            attribute_descriptions[index] = MaybeUninit::new(attribute_description);
-            index += 1;
-        }
-
-        TODO: process all accessors
-
-        let binding_descriptions = unsafe { 
-            mem::transmute::<_, [vk::VertexInputBindingDescription; Self::MAX_ACCESSORS]>(binding_descriptions)
-        };
-
-        let attribute_descriptions = unsafe { 
-            mem::transmute::<_, [vk::VertexInputAttributeDescription; Self::MAX_ACCESSORS]>(attribute_descriptions)
-        };
-
-        let vertex_input_state_create_info = vk::PipelineVertexInputStateCreateInfo::builder()
-            .vertex_binding_descriptions(&binding_descriptions[0..index])
-            .vertex_attribute_descriptions(&attribute_descriptions[0..index])
-            .build();
-
-        let pipeline_info = PrimitivePipelineInfo::new(
-            topology,
-            vertex_input_state_create_info,
-        );
-
-        let pipeline = graphics_core.acquire_primitive_pipeline(&pipeline_info)?;
-
-        let primitive = Self
-        {
-            // pipeline,
-        };
-
-        Ok(primitive)
-    }
-
-    // pub fn pipeline(&self)
-    // -> &GraphicsPipeline
-    // {
-    //     &self.pipeline
-    // }
-
-    // pub fn render(
-    //     &self,
-    //     command_buffer: &CommandBuffer,
-    // )
-    // {
-    //     let pipeline: &GraphicsPipeline = &self.pipeline;
-    //     command_buffer.bind_pipeline(pipeline);
-
-        
-
-    // }
-
-    fn description(
-        accessor: &Accessor,
-        binding: u32,
-        location: u32,
-    ) -> Result<(
-        vk::VertexInputBindingDescription,
-        vk::VertexInputAttributeDescription
-    )>
-    {
-        let buffer_view = Self::buffer_view(accessor)?;
-
-        let buffer_index = buffer_view.buffer().index();
-        let stride = match buffer_view.stride()
-        {
-            Some(stride) => stride,
-            None => accessor.size(),
-        };
-
-        let binding_description = vk::VertexInputBindingDescription::builder()
-            .binding(buffer_index as _)
-            .stride(stride as _)
-            .input_rate(vk::VertexInputRate::VERTEX)
-            .build();
-
-        let data_type = accessor.data_type();
-        let dimensions = accessor.dimensions();
-        let format = Self::attribute_format(
-            data_type, 
-            dimensions
-        )?;
-
-        let offset = accessor.offset() + buffer_view.offset();
-        
-        let attribute_desctiption = vk::VertexInputAttributeDescription::builder()
-            .binding(binding)
-            .location(location)
-            .format(format)
-            .offset(offset as _)
-            .build();
-
-        let description = (binding_description, attribute_desctiption);
-        Ok(description)
-    }
-
-    fn buffer_view<'a>(accessor: &Accessor<'a>
-    ) -> Result<View<'a>>
-    {
-        let buffer_view = accessor.view().ok_or(
-            anyhow::anyhow!("Sparse accessor is unsupported!")
-        )?;
-
-        Ok(buffer_view)
-    }
-
-    fn binding_description(accessor: &Accessor)
-    -> Result<vk::VertexInputBindingDescription>
-    {
-        let buffer_view = Self::buffer_view(accessor)?;
-
-        let buffer_index = buffer_view.buffer().index();
-        let stride = match buffer_view.stride()
-        {
-            Some(stride) => stride,
-            None => accessor.size(),
-        };
-
-        let description = vk::VertexInputBindingDescription::builder()
-            .binding(buffer_index as _)
-            .stride(stride as _)
-            .input_rate(vk::VertexInputRate::VERTEX)
-            .build();
-
-        Ok(description)
-    }
-
-    fn attribute_description(
-        accessor: &Accessor,
-        binding: u32,
-        location: u32,
-    ) -> Result<vk::VertexInputAttributeDescription>
-    {
-        let buffer_view = Self::buffer_view(accessor)?;
-
-        let data_type = accessor.data_type();
-        let dimensions = accessor.dimensions();
-        let format = Self::attribute_format(
-            data_type, 
-            dimensions
-        )?;
-
-        let offset = accessor.offset() + buffer_view.offset();
-        
-        let desctiption = vk::VertexInputAttributeDescription::builder()
-            .binding(binding)
-            .location(location)
-            .format(format)
-            .offset(offset as _)
-            .build();
-
-        Ok(desctiption)
-    }
-
-    fn topology(primitive: &gltf::Primitive)
-    -> Result<vk::PrimitiveTopology>
-    {
-        use vk::PrimitiveTopology;
-
-        use gltf::mesh::Mode;
-        
-        let topology = match primitive.mode()
-        {
-            Mode::Points => PrimitiveTopology::POINT_LIST,
-            Mode::Lines => PrimitiveTopology::LINE_LIST,
-            Mode::LineStrip => PrimitiveTopology::LINE_STRIP,
-            Mode::Triangles => PrimitiveTopology::TRIANGLE_LIST,
-            Mode::TriangleStrip => PrimitiveTopology::TRIANGLE_STRIP,
-            Mode::TriangleFan => PrimitiveTopology::TRIANGLE_FAN,
-
-            _ => {
-                // There is only one unhandled topology mode - LineLoop
-                // TODO: investigate how properly implement support LineLoop for Vulkan
-                Err(anyhow::anyhow!("Unsupported primitive topology!"))?
-            }
-
-        };
-
-        Ok(topology)
-    }
-
-    fn attribute_format(
-        data_type: DataType,
-        dimensions: Dimensions,
-    ) -> Result<vk::Format>
-    {
-        let attribute_format = match dimensions
-        {
-            Dimensions::Scalar =>
-            {
-                match data_type
-                {
-                    DataType::I8 => Format::R8_SINT,
-                    DataType::U8 => Format::R8_UINT,
-                    DataType::I16 => Format::R16_SINT,
-                    DataType::U16 => Format::R16_UINT,
-                    DataType::U32 => Format::R32_UINT,
-                    DataType::F32 => Format::R32_SFLOAT,
-                }
-            },
-            Dimensions::Vec2 =>
-            {
-                match data_type
-                {
-                    DataType::I8 => Format::R8G8_SINT,
-                    DataType::U8 => Format::R8G8_UINT,
-                    DataType::I16 => Format::R16G16_SINT,
-                    DataType::U16 => Format::R16G16_UINT,
-                    DataType::U32 => Format::R32G32_UINT,
-                    DataType::F32 => Format::R32G32_SFLOAT,
-                }
-            },
-            Dimensions::Vec3 =>
-            {
-                match data_type
-                {
-                    DataType::I8 => Format::R8G8B8_SINT,
-                    DataType::U8 => Format::R8G8B8_UINT,
-                    DataType::I16 => Format::R16G16B16_SINT,
-                    DataType::U16 => Format::R16G16B16_UINT,
-                    DataType::U32 => Format::R32G32B32_UINT,
-                    DataType::F32 => Format::R32G32B32_SFLOAT,
-                }
-            },
-            Dimensions::Vec4 =>
-            {
-                match data_type
-                {
-                    DataType::I8 => Format::R8G8B8A8_SINT,
-                    DataType::U8 => Format::R8G8B8A8_UINT,
-                    DataType::I16 => Format::R16G16B16A16_SINT,
-                    DataType::U16 => Format::R16G16B16A16_UINT,
-                    DataType::U32 => Format::R32G32B32A32_UINT,
-                    DataType::F32 => Format::R32G32B32A32_SFLOAT,
-                }
-            },
-            _ => Err(anyhow::anyhow!("Unsupported accessor type!"))?
-        };
-
-        Ok(attribute_format)
-    }
-}
\ No newline at end of file
+pub struct Indices {
+	pub index_type: vk::IndexType,
+	pub buffer: usize,
+	pub offset: usize,
+	pub count: u32,
+}
+
+// Per vertex displacements, empty when target leaves attribute unchanged
+pub struct MorphTarget {
+	pub positions: Vec<glm::Vec3>,
+	pub normals: Vec<glm::Vec3>,
+	pub tangents: Vec<glm::Vec3>,
+}
+
+pub struct Primitive {
+	topology: vk::PrimitiveTopology,
+	attributes: Vec<VertexAttribute>,
+	vertex_count: u32,
+	indices: Option<Indices>,
+	morph_targets: Vec<MorphTarget>,
+	bounds: Aabb,
+	material: usize,
+}
+
+impl Primitive {
+	pub const POSITIONS_LOCATION: u32 = 0;
+	pub const NORMALS_LOCATION: u32 = 1;
+	pub const TANGENTS_LOCATION: u32 = 2;
+	pub const TEX_COORDS_0_LOCATION: u32 = 3;
+	pub const TEX_COORDS_1_LOCATION: u32 = 4;
+	pub const JOINTS_0_LOCATION: u32 = 5;
+	pub const WEIGHTS_0_LOCATION: u32 = 6;
+
+	pub const LOCATIONS: [u32; 7] = [
+		Self::POSITIONS_LOCATION,
+		Self::NORMALS_LOCATION,
+		Self::TANGENTS_LOCATION,
+		Self::TEX_COORDS_0_LOCATION,
+		Self::TEX_COORDS_1_LOCATION,
+		Self::JOINTS_0_LOCATION,
+		Self::WEIGHTS_0_LOCATION,
+	];
+
+	// Material is an index into scene materials. Morph targets are read
+	// from buffers, vertex attributes are referenced in place
+	pub fn new(
+		primitive: &gltf::Primitive,
+		buffers: &[Vec<u8>],
+		material: usize,
+	) -> Result<Self> {
+		log::info!("Creating primitive; index: {}", primitive.index());
+
+		let topology = Self::primitive_topology(primitive.mode())?;
+
+		// positions
+		let positions = primitive
+			.get(&Semantic::Positions)
+			.ok_or_else(|| anyhow!("Primitive has no positions!"))?;
+
+		let vertex_count = positions.count() as _;
+		let bounds = Aabb::from_accessor(&positions)?;
+
+		let mut attributes = vec![Self::vertex_attribute(
+			&positions,
+			Self::POSITIONS_LOCATION,
+		)?];
+
+		let optional_attributes = [
+			(Semantic::Normals, Self::NORMALS_LOCATION),
+			(Semantic::Tangents, Self::TANGENTS_LOCATION),
+			(Semantic::TexCoords(0), Self::TEX_COORDS_0_LOCATION),
+			(Semantic::TexCoords(1), Self::TEX_COORDS_1_LOCATION),
+			(Semantic::Joints(0), Self::JOINTS_0_LOCATION),
+			(Semantic::Weights(0), Self::WEIGHTS_0_LOCATION),
+		];
+
+		for (semantic, location) in optional_attributes {
+			if let Some(accessor) = primitive.get(&semantic) {
+				attributes.push(Self::vertex_attribute(&accessor, location)?);
+			}
+		}
+
+		let indices = primitive
+			.indices()
+			.map(|indices| Self::index_data(&indices))
+			.transpose()?;
+
+		let reader =
+			primitive.reader(|buffer| Some(buffers[buffer.index()].as_slice()));
+
+		let morph_targets = reader
+			.read_morph_targets()
+			.map(|(positions, normals, tangents)| MorphTarget {
+				positions: Self::displacements(positions),
+				normals: Self::displacements(normals),
+				tangents: Self::displacements(tangents),
+			})
+			.collect::<Vec<_>>();
+
+		// Displacements are indexed by vertex, here and in shaders alike
+		for (index, target) in morph_targets.iter().enumerate() {
+			for values in [&target.positions, &target.normals, &target.tangents]
+			{
+				if !values.is_empty() && values.len() != positions.count() {
+					bail!(
+						"Morph target {index} has {} values for {} vertices!",
+						values.len(),
+						positions.count()
+					);
+				}
+			}
+		}
+
+		let primitive = Self {
+			topology,
+			attributes,
+			vertex_count,
+			indices,
+			morph_targets,
+			bounds,
+			material,
+		};
+
+		Ok(primitive)
+	}
+
+	pub fn topology(&self) -> vk::PrimitiveTopology {
+		self.topology
+	}
+
+	pub fn attribute(&self, location: u32) -> Option<&VertexAttribute> {
+		self.attributes
+			.iter()
+			.find(|attribute| attribute.location == location)
+	}
+
+	pub fn vertex_count(&self) -> u32 {
+		self.vertex_count
+	}
+
+	pub fn indices(&self) -> Option<&Indices> {
+		self.indices.as_ref()
+	}
+
+	pub fn morph_targets(&self) -> &[MorphTarget] {
+		&self.morph_targets
+	}
+
+	pub fn bounds(&self) -> &Aabb {
+		&self.bounds
+	}
+
+	pub fn material(&self) -> usize {
+		self.material
+	}
+
+	// For devices without VK_EXT_index_type_uint8 support
+	pub fn widen_u8_indices(
+		&mut self,
+		buffers: &mut Vec<Vec<u8>>,
+	) -> Result<()> {
+		let indices = match self.indices.as_mut() {
+			Some(indices) if indices.index_type == vk::IndexType::UINT8_EXT => {
+				indices
+			}
+			_ => return Ok(()),
+		};
+
+		let begin = indices.offset;
+		let end = begin + indices.count as usize;
+
+		let widened_indices = buffers[indices.buffer]
+			.get(begin..end)
+			.ok_or_else(|| anyhow!("Index accessor is out of buffer bounds"))?
+			.iter()
+			.flat_map(|&index| u16::from(index).to_le_bytes())
+			.collect::<Vec<_>>();
+
+		indices.index_type = vk::IndexType::UINT16;
+		indices.buffer = buffers.len();
+		indices.offset = 0;
+
+		buffers.push(widened_indices);
+
+		Ok(())
+	}
+
+	// Spec requires flat normals when normals are absent and MikkTSpace
+	// tangents when normal mapped primitive has no tangents. Generated data
+	// is appended to buffers. Vertices are split into triangle corners for
+	// generation and equal ones are welded back afterwards
+	pub fn generate_missing_attributes(
+		&mut self,
+		normal_tex_coord: Option<u32>,
+		buffers: &mut Vec<Vec<u8>>,
+	) -> Result<()> {
+		let indices = self.read_indices(buffers)?;
+
+		if let Some(index) =
+			indices.iter().find(|&&index| index >= self.vertex_count)
+		{
+			return Err(anyhow!(
+				"Vertex index {index} is out of {} vertices!",
+				self.vertex_count
+			));
+		}
+
+		let mut corners =
+			match tangent_space::triangle_corners(self.topology, &indices) {
+				Some(corners) => corners,
+				None => return Ok(()),
+			};
+
+		let mut split = false;
+
+		if self.attribute(Self::NORMALS_LOCATION).is_none() {
+			log::info!("Generating flat normals");
+
+			self.split_vertices(&corners, buffers)?;
+			corners = (0..self.vertex_count).collect();
+			split = true;
+
+			let positions =
+				self.read_vec3(Self::POSITIONS_LOCATION, buffers)?;
+			let normals = tangent_space::flat_normals(&positions, &corners);
+
+			self.push_attribute(
+				Self::NORMALS_LOCATION,
+				Format::R32G32B32_SFLOAT,
+				normals.iter().flat_map(|normal| normal.iter()),
+				buffers,
+			);
+		}
+
+		if let Some(tex_coord_location) =
+			self.tangent_tex_coords(normal_tex_coord)
+		{
+			log::info!("Generating MikkTSpace tangents");
+
+			// Corners sharing a vertex can disagree on tangents
+			if !split {
+				self.split_vertices(&corners, buffers)?;
+				corners = (0..self.vertex_count).collect();
+				split = true;
+			}
+
+			self.generate_tangents(tex_coord_location, &corners, buffers)?;
+		}
+
+		if split {
+			self.weld_vertices(buffers)?;
+		}
+
+		Ok(())
+	}
+
+	// Texture coordinates of normal texture when tangents are to be made
+	fn tangent_tex_coords(&self, normal_tex_coord: Option<u32>) -> Option<u32> {
+		let tex_coord_location = match normal_tex_coord? {
+			0 => Self::TEX_COORDS_0_LOCATION,
+			1 => Self::TEX_COORDS_1_LOCATION,
+			tex_coord => {
+				log::warn!(
+					"Unsupported normal texture coordinates {tex_coord}, \
+					 skipping tangent generation"
+				);
+				return None;
+			}
+		};
+
+		if self.attribute(Self::TANGENTS_LOCATION).is_some()
+			|| self.attribute(tex_coord_location).is_none()
+		{
+			return None;
+		}
+
+		Some(tex_coord_location)
+	}
+
+	fn generate_tangents(
+		&mut self,
+		tex_coord_location: u32,
+		corners: &[u32],
+		buffers: &mut Vec<Vec<u8>>,
+	) -> Result<()> {
+		let positions = self.read_vec3(Self::POSITIONS_LOCATION, buffers)?;
+		let normals = self.read_vec3(Self::NORMALS_LOCATION, buffers)?;
+		let tex_coords = self.read_vec2(tex_coord_location, buffers)?;
+
+		match tangent_space::mikktspace_tangents(
+			&positions,
+			&normals,
+			&tex_coords,
+			corners,
+		) {
+			Some(tangents) => self.push_attribute(
+				Self::TANGENTS_LOCATION,
+				Format::R32G32B32A32_SFLOAT,
+				tangents.iter().flat_map(|tangent| tangent.iter()),
+				buffers,
+			),
+			None => log::warn!("Tangent generation failed"),
+		}
+
+		Ok(())
+	}
+
+	// Vertex indices, or vertices in order for non-indexed primitive
+	fn read_indices(&self, buffers: &[Vec<u8>]) -> Result<Vec<u32>> {
+		let indices = match &self.indices {
+			Some(indices) => indices,
+			None => return Ok((0..self.vertex_count).collect()),
+		};
+
+		let size = match indices.index_type {
+			vk::IndexType::UINT8_EXT => 1,
+			vk::IndexType::UINT16 => 2,
+			_ => 4,
+		};
+
+		let begin = indices.offset;
+		let end = begin + indices.count as usize * size;
+
+		let indices = buffers[indices.buffer]
+			.get(begin..end)
+			.ok_or_else(|| anyhow!("Index accessor is out of buffer bounds"))?
+			.chunks_exact(size)
+			.map(|index| {
+				index
+					.iter()
+					.rev()
+					.fold(0, |value, &byte| value << 8 | u32::from(byte))
+			})
+			.collect();
+
+		Ok(indices)
+	}
+
+	fn element<'a>(
+		attribute: &VertexAttribute,
+		buffers: &'a [Vec<u8>],
+		vertex: usize,
+	) -> Result<&'a [u8]> {
+		let begin = attribute.offset + vertex * attribute.stride;
+
+		buffers[attribute.buffer]
+			.get(begin..begin + attribute.size)
+			.ok_or_else(|| anyhow!("Vertex accessor is out of buffer bounds"))
+	}
+
+	// Elements of an attribute present in the primitive, parsed by format
+	fn read_elements<T>(
+		&self,
+		location: u32,
+		buffers: &[Vec<u8>],
+		parse: impl Fn(Format, &[u8]) -> Option<T>,
+	) -> Result<Vec<T>> {
+		let attribute = self.attribute(location).unwrap();
+
+		(0..self.vertex_count as usize)
+			.map(|vertex| {
+				let element = Self::element(attribute, buffers, vertex)?;
+
+				parse(attribute.format, element).ok_or_else(|| {
+					anyhow!(
+						"Unsupported attribute format: {:?}",
+						attribute.format
+					)
+				})
+			})
+			.collect()
+	}
+
+	fn read_vec3(
+		&self,
+		location: u32,
+		buffers: &[Vec<u8>],
+	) -> Result<Vec<glm::Vec3>> {
+		self.read_elements(location, buffers, |format, element| match format {
+			Format::R32G32B32_SFLOAT => Some(glm::make_vec3(&floats(element))),
+			_ => None,
+		})
+	}
+
+	// Normalized integer coordinates are mapped to [0, 1]
+	fn read_vec2(
+		&self,
+		location: u32,
+		buffers: &[Vec<u8>],
+	) -> Result<Vec<glm::Vec2>> {
+		self.read_elements(location, buffers, |format, element| {
+			let tex_coord = match format {
+				Format::R32G32_SFLOAT => glm::make_vec2(&floats(element)),
+				Format::R8G8_UNORM => {
+					glm::vec2(element[0] as f32, element[1] as f32) / 255.0
+				}
+				Format::R16G16_UNORM => {
+					let [x, y] = [0, 2].map(|begin| {
+						u16::from_le_bytes([element[begin], element[begin + 1]])
+					});
+
+					glm::vec2(x as f32, y as f32) / 65535.0
+				}
+				_ => return None,
+			};
+
+			Some(tex_coord)
+		})
+	}
+
+	// Every triangle corner gets its own copy of vertex attributes and
+	// morph target displacements, primitive becomes non-indexed list
+	fn split_vertices(
+		&mut self,
+		corners: &[u32],
+		buffers: &mut Vec<Vec<u8>>,
+	) -> Result<()> {
+		for attribute in &mut self.attributes {
+			let data = corners
+				.iter()
+				.map(|&vertex| {
+					Self::element(attribute, buffers, vertex as usize)
+				})
+				.collect::<Result<Vec<_>>>()?
+				.concat();
+
+			attribute.buffer = buffers.len();
+			attribute.offset = 0;
+			attribute.stride = attribute.size;
+
+			buffers.push(data);
+		}
+
+		for target in &mut self.morph_targets {
+			for values in [
+				&mut target.positions,
+				&mut target.normals,
+				&mut target.tangents,
+			] {
+				if !values.is_empty() {
+					*values = corners
+						.iter()
+						.map(|&vertex| values[vertex as usize])
+						.collect();
+				}
+			}
+		}
+
+		self.topology = vk::PrimitiveTopology::TRIANGLE_LIST;
+		self.indices = None;
+		self.vertex_count = corners.len() as _;
+
+		Ok(())
+	}
+
+	// Vertices equal in all attributes and displacements are merged, and
+	// the primitive is indexed by 32 bit indices again
+	fn weld_vertices(&mut self, buffers: &mut Vec<Vec<u8>>) -> Result<()> {
+		let mut welded = HashMap::new();
+		let mut vertices = Vec::new();
+
+		let indices = (0..self.vertex_count as usize)
+			.map(|vertex| {
+				let mut key = Vec::new();
+
+				for attribute in &self.attributes {
+					key.extend(Self::element(attribute, buffers, vertex)?);
+				}
+
+				for target in &self.morph_targets {
+					for values in
+						[&target.positions, &target.normals, &target.tangents]
+					{
+						let value = values.get(vertex).into_iter().flatten();
+						key.extend(value.flat_map(|value| value.to_le_bytes()));
+					}
+				}
+
+				let index = *welded.entry(key).or_insert_with(|| {
+					vertices.push(vertex as u32);
+					vertices.len() as u32 - 1
+				});
+
+				Ok(index)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		self.split_vertices(&vertices, buffers)?;
+
+		self.indices = Some(Indices {
+			index_type: vk::IndexType::UINT32,
+			buffer: buffers.len(),
+			offset: 0,
+			count: indices.len() as _,
+		});
+
+		buffers.push(
+			indices
+				.iter()
+				.flat_map(|index| index.to_le_bytes())
+				.collect(),
+		);
+
+		Ok(())
+	}
+
+	fn push_attribute<'a>(
+		&mut self,
+		location: u32,
+		format: Format,
+		values: impl Iterator<Item = &'a f32>,
+		buffers: &mut Vec<Vec<u8>>,
+	) {
+		let data = values
+			.flat_map(|value| value.to_le_bytes())
+			.collect::<Vec<_>>();
+
+		let size = data.len() / self.vertex_count.max(1) as usize;
+
+		self.attributes.push(VertexAttribute {
+			location,
+			format,
+			buffer: buffers.len(),
+			offset: 0,
+			stride: size,
+			size,
+		});
+
+		buffers.push(data);
+	}
+
+	fn vertex_attribute(
+		accessor: &Accessor,
+		location: u32,
+	) -> Result<VertexAttribute> {
+		let buffer_view = Self::buffer_view(accessor)?;
+
+		let stride = buffer_view.stride().unwrap_or_else(|| accessor.size());
+
+		let format = Self::attribute_format(
+			accessor.data_type(),
+			accessor.dimensions(),
+			accessor.normalized(),
+		)?;
+
+		let attribute = VertexAttribute {
+			location,
+			format,
+			buffer: buffer_view.buffer().index(),
+			offset: buffer_view.offset() + accessor.offset(),
+			stride,
+			size: accessor.size(),
+		};
+
+		Ok(attribute)
+	}
+
+	fn displacements(
+		values: Option<gltf::accessor::Iter<[f32; 3]>>,
+	) -> Vec<glm::Vec3> {
+		values.map_or_else(Vec::new, |values| {
+			values.map(|value| glm::make_vec3(&value)).collect()
+		})
+	}
+
+	fn index_data(accessor: &Accessor) -> Result<Indices> {
+		let buffer_view = Self::buffer_view(accessor)?;
+
+		let index_type = match accessor.data_type() {
+			DataType::U8 => vk::IndexType::UINT8_EXT,
+			DataType::U16 => vk::IndexType::UINT16,
+			DataType::U32 => vk::IndexType::UINT32,
+			data_type => {
+				return Err(anyhow!("Unsupported index type: {data_type:?}"))
+			}
+		};
+
+		let indices = Indices {
+			index_type,
+			buffer: buffer_view.buffer().index(),
+			offset: buffer_view.offset() + accessor.offset(),
+			count: accessor.count() as _,
+		};
+
+		Ok(indices)
+	}
+
+	fn buffer_view<'a>(accessor: &Accessor<'a>) -> Result<View<'a>> {
+		let buffer_view = accessor
+			.view()
+			.ok_or_else(|| anyhow!("Accessor has no buffer view!"))?;
+
+		Ok(buffer_view)
+	}
+
+	fn primitive_topology(
+		mode: gltf::mesh::Mode,
+	) -> Result<vk::PrimitiveTopology> {
+		use gltf::mesh::Mode;
+		use vk::PrimitiveTopology;
+
+		let topology = match mode {
+			Mode::Points => PrimitiveTopology::POINT_LIST,
+			Mode::Lines => PrimitiveTopology::LINE_LIST,
+			Mode::LineStrip => PrimitiveTopology::LINE_STRIP,
+			Mode::Triangles => PrimitiveTopology::TRIANGLE_LIST,
+			Mode::TriangleStrip => PrimitiveTopology::TRIANGLE_STRIP,
+			Mode::TriangleFan => PrimitiveTopology::TRIANGLE_FAN,
+			Mode::LineLoop => {
+				// TODO: investigate how properly implement support LineLoop for Vulkan
+				return Err(anyhow!("Unsupported primitive topology!"));
+			}
+		};
+
+		Ok(topology)
+	}
+
+	fn attribute_format(
+		data_type: DataType,
+		dimensions: Dimensions,
+		normalized: bool,
+	) -> Result<vk::Format> {
+		let attribute_format = match (dimensions, normalized) {
+			(Dimensions::Scalar, false) => match data_type {
+				DataType::I8 => Format::R8_SINT,
+				DataType::U8 => Format::R8_UINT,
+				DataType::I16 => Format::R16_SINT,
+				DataType::U16 => Format::R16_UINT,
+				DataType::U32 => Format::R32_UINT,
+				DataType::F32 => Format::R32_SFLOAT,
+			},
+			(Dimensions::Scalar, true) => match data_type {
+				DataType::I8 => Format::R8_SNORM,
+				DataType::U8 => Format::R8_UNORM,
+				DataType::I16 => Format::R16_SNORM,
+				DataType::U16 => Format::R16_UNORM,
+				_ => return Err(anyhow!("Unsupported normalized accessor!")),
+			},
+			(Dimensions::Vec2, false) => match data_type {
+				DataType::I8 => Format::R8G8_SINT,
+				DataType::U8 => Format::R8G8_UINT,
+				DataType::I16 => Format::R16G16_SINT,
+				DataType::U16 => Format::R16G16_UINT,
+				DataType::U32 => Format::R32G32_UINT,
+				DataType::F32 => Format::R32G32_SFLOAT,
+			},
+			(Dimensions::Vec2, true) => match data_type {
+				DataType::I8 => Format::R8G8_SNORM,
+				DataType::U8 => Format::R8G8_UNORM,
+				DataType::I16 => Format::R16G16_SNORM,
+				DataType::U16 => Format::R16G16_UNORM,
+				_ => return Err(anyhow!("Unsupported normalized accessor!")),
+			},
+			(Dimensions::Vec3, false) => match data_type {
+				DataType::I8 => Format::R8G8B8_SINT,
+				DataType::U8 => Format::R8G8B8_UINT,
+				DataType::I16 => Format::R16G16B16_SINT,
+				DataType::U16 => Format::R16G16B16_UINT,
+				DataType::U32 => Format::R32G32B32_UINT,
+				DataType::F32 => Format::R32G32B32_SFLOAT,
+			},
+			(Dimensions::Vec3, true) => match data_type {
+				DataType::I8 => Format::R8G8B8_SNORM,
+				DataType::U8 => Format::R8G8B8_UNORM,
+				DataType::I16 => Format::R16G16B16_SNORM,
+				DataType::U16 => Format::R16G16B16_UNORM,
+				_ => return Err(anyhow!("Unsupported normalized accessor!")),
+			},
+			(Dimensions::Vec4, false) => match data_type {
+				DataType::I8 => Format::R8G8B8A8_SINT,
+				DataType::U8 => Format::R8G8B8A8_UINT,
+				DataType::I16 => Format::R16G16B16A16_SINT,
+				DataType::U16 => Format::R16G16B16A16_UINT,
+				DataType::U32 => Format::R32G32B32A32_UINT,
+				DataType::F32 => Format::R32G32B32A32_SFLOAT,
+			},
+			(Dimensions::Vec4, true) => match data_type {
+				DataType::I8 => Format::R8G8B8A8_SNORM,
+				DataType::U8 => Format::R8G8B8A8_UNORM,
+				DataType::I16 => Format::R16G16B16A16_SNORM,
+				DataType::U16 => Format::R16G16B16A16_UNORM,
+				_ => return Err(anyhow!("Unsupported normalized accessor!")),
+			},
+			_ => return Err(anyhow!("Unsupported accessor type!")),
+		};
+
+		Ok(attribute_format)
+	}
+}
+
+fn floats(bytes: &[u8]) -> Vec<f32> {
+	bytes
+		.chunks_exact(4)
+		.map(|float| f32::from_le_bytes(float.try_into().unwrap()))
+		.collect()
+}
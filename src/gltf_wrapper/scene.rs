@@ -1,32 +1,408 @@
-use anyhow::Result;
+use {
+	super::{
+		load_image, Aabb, Animation, Camera, ImageData, Light, Material, Mesh,
+		Node, Skin, Texture,
+	},
+	anyhow::Result,
+	std::{collections::HashMap, path::Path},
+};
 
 pub struct Scene {
-	// nodes: Vec<Node>,
+	nodes: Vec<Node>,
+	meshes: Vec<Mesh>,
+	cameras: Vec<Camera>,
+	lights: Vec<Light>,
+	skins: Vec<Skin>,
+	materials: Vec<Material>,
+	textures: Vec<Texture>,
+	images: Vec<ImageData>,
+	animations: Vec<Animation>,
 }
 
 impl Scene {
-	pub fn new(scene: &gltf::Scene) -> Result<Self> {
+	// Images are loaded from buffers or files relative to base directory.
+	// Animations are the document ones, restricted to nodes of the scene
+	pub fn new(
+		scene: &gltf::Scene,
+		animations: gltf::iter::Animations,
+		buffers: &[Vec<u8>],
+		base_dir: &Path,
+	) -> Result<Self> {
 		log::info!(
 			"Creating scene; index: {}, name: {}",
 			scene.index(),
 			scene.name().unwrap_or_default()
 		);
 
-		// let nodes = scene
-		// 	.nodes()
-		// 	.map(|ref node| Node::new(node, graphics_core))
-		// 	.collect::<Result<Vec<_>>>()?;
+		let mut builder = SceneBuilder::new(buffers, base_dir);
 
-		let scene = Self {
-						// nodes 
-				};
+		for ref node in scene.nodes() {
+			builder.add_node(node, None)?;
+		}
+
+		let node_indices = builder
+			.nodes
+			.iter()
+			.enumerate()
+			.map(|(index, node)| (node.index(), index))
+			.collect::<HashMap<_, _>>();
+
+		for skin in &mut builder.skins {
+			skin.remap_joints(&node_indices)?;
+		}
+
+		let animations = animations
+			.map(|ref animation| {
+				Animation::new(animation, buffers, &node_indices)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let mut scene = Self {
+			nodes: builder.nodes,
+			meshes: builder.meshes,
+			cameras: builder.cameras,
+			lights: builder.lights,
+			skins: builder.skins,
+			materials: builder.materials,
+			textures: builder.textures,
+			images: builder.images,
+			animations,
+		};
+
+		scene.update_world_transforms();
 
 		Ok(scene)
 	}
 
-	pub fn render(&self) {
-		// for node in self.nodes {
-		// 	node.render(command_buffer)
-		// }
+	pub fn nodes(&self) -> &[Node] {
+		&self.nodes
+	}
+
+	pub fn node(&self, index: usize) -> &Node {
+		&self.nodes[index]
+	}
+
+	pub fn meshes(&self) -> &[Mesh] {
+		&self.meshes
+	}
+
+	pub fn mesh(&self, index: usize) -> &Mesh {
+		&self.meshes[index]
+	}
+
+	pub fn camera(&self, index: usize) -> &Camera {
+		&self.cameras[index]
+	}
+
+	pub fn light(&self, index: usize) -> &Light {
+		&self.lights[index]
+	}
+
+	pub fn skins(&self) -> &[Skin] {
+		&self.skins
+	}
+
+	pub fn skin(&self, index: usize) -> &Skin {
+		&self.skins[index]
+	}
+
+	pub fn materials(&self) -> &[Material] {
+		&self.materials
+	}
+
+	pub fn material(&self, index: usize) -> &Material {
+		&self.materials[index]
+	}
+
+	pub fn textures(&self) -> &[Texture] {
+		&self.textures
+	}
+
+	pub fn image(&self, index: usize) -> &ImageData {
+		&self.images[index]
+	}
+
+	pub fn animations(&self) -> &[Animation] {
+		&self.animations
+	}
+
+	pub fn animation(&self, index: usize) -> &Animation {
+		&self.animations[index]
+	}
+
+	// Camera instances in node order
+	pub fn camera_nodes(&self) -> Vec<usize> {
+		self.nodes
+			.iter()
+			.enumerate()
+			.filter_map(|(index, node)| node.camera().map(|_| index))
+			.collect()
+	}
+
+	// Light instances in node order
+	pub fn light_nodes(&self) -> Vec<usize> {
+		self.nodes
+			.iter()
+			.enumerate()
+			.filter_map(|(index, node)| node.light().map(|_| index))
+			.collect()
+	}
+
+	// Appends generated attribute data to buffers
+	pub fn generate_missing_attributes(
+		&mut self,
+		buffers: &mut Vec<Vec<u8>>,
+	) -> Result<()> {
+		for mesh in &mut self.meshes {
+			for primitive in mesh.primitives_mut() {
+				let material = &self.materials[primitive.material()];
+				let normal_tex_coord =
+					material.normal_texture().map(|info| info.tex_coord);
+
+				primitive
+					.generate_missing_attributes(normal_tex_coord, buffers)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	pub fn widen_u8_indices(
+		&mut self,
+		buffers: &mut Vec<Vec<u8>>,
+	) -> Result<()> {
+		for mesh in &mut self.meshes {
+			for primitive in mesh.primitives_mut() {
+				primitive.widen_u8_indices(buffers)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	// World space bounds of all mesh instances
+	pub fn bounds(&self) -> Option<Aabb> {
+		self.nodes
+			.iter()
+			.filter_map(|node| {
+				let mesh = &self.meshes[node.mesh()?];
+				let bounds = mesh.bounds()?;
+
+				Some(bounds.transform(node.world_transform()))
+			})
+			.reduce(|bounds, other| bounds.union(&other))
+	}
+
+	pub fn find_animation(&self, name: &str) -> Option<usize> {
+		self.animations
+			.iter()
+			.position(|animation| animation.name() == Some(name))
+	}
+
+	// Poses nodes at animation time in seconds
+	pub fn animate(&mut self, animation: usize, time: f32) {
+		self.animations[animation].apply(time, &mut self.nodes);
+		self.update_world_transforms();
+	}
+
+	// Nodes are stored parents first, so single pass is enough
+	pub fn update_world_transforms(&mut self) {
+		for index in 0..self.nodes.len() {
+			let local_transform = self.nodes[index].local_transform().matrix();
+
+			let world_transform = match self.nodes[index].parent() {
+				Some(parent) => {
+					self.nodes[parent].world_transform() * local_transform
+				}
+				None => local_transform,
+			};
+
+			self.nodes[index].set_world_transform(world_transform);
+		}
+	}
+}
+
+struct SceneBuilder<'a> {
+	buffers: &'a [Vec<u8>],
+	base_dir: &'a Path,
+	nodes: Vec<Node>,
+	meshes: Vec<Mesh>,
+	mesh_indices: HashMap<usize, usize>,
+	cameras: Vec<Camera>,
+	camera_indices: HashMap<usize, usize>,
+	lights: Vec<Light>,
+	light_indices: HashMap<usize, usize>,
+	skins: Vec<Skin>,
+	skin_indices: HashMap<usize, usize>,
+	materials: Vec<Material>,
+	// Default material has no index
+	material_indices: HashMap<Option<usize>, usize>,
+	textures: Vec<Texture>,
+	// Same texture may be sampled both as sRGB and linear
+	texture_indices: HashMap<(usize, bool), usize>,
+	images: Vec<ImageData>,
+	image_indices: HashMap<usize, usize>,
+}
+
+impl<'a> SceneBuilder<'a> {
+	fn new(buffers: &'a [Vec<u8>], base_dir: &'a Path) -> Self {
+		Self {
+			buffers,
+			base_dir,
+			nodes: Vec::new(),
+			meshes: Vec::new(),
+			mesh_indices: HashMap::new(),
+			cameras: Vec::new(),
+			camera_indices: HashMap::new(),
+			lights: Vec::new(),
+			light_indices: HashMap::new(),
+			skins: Vec::new(),
+			skin_indices: HashMap::new(),
+			materials: Vec::new(),
+			material_indices: HashMap::new(),
+			textures: Vec::new(),
+			texture_indices: HashMap::new(),
+			images: Vec::new(),
+			image_indices: HashMap::new(),
+		}
+	}
+
+	fn add_node(
+		&mut self,
+		node: &gltf::Node,
+		parent: Option<usize>,
+	) -> Result<usize> {
+		let mesh = node
+			.mesh()
+			.map(|ref mesh| self.add_mesh(mesh))
+			.transpose()?;
+
+		let camera = node
+			.camera()
+			.map(|ref camera| self.add_camera(camera))
+			.transpose()?;
+
+		let light = node
+			.light()
+			.map(|ref light| self.add_light(light))
+			.transpose()?;
+
+		let skin = node
+			.skin()
+			.map(|ref skin| self.add_skin(skin))
+			.transpose()?;
+
+		let index = self.nodes.len();
+		self.nodes
+			.push(Node::new(node, parent, mesh, camera, light, skin)?);
+
+		for ref child in node.children() {
+			self.add_node(child, Some(index))?;
+		}
+
+		Ok(index)
+	}
+
+	fn add_mesh(&mut self, mesh: &gltf::Mesh) -> Result<usize> {
+		if let Some(&index) = self.mesh_indices.get(&mesh.index()) {
+			return Ok(index);
+		}
+
+		let index = self.meshes.len();
+		let buffers = self.buffers;
+		let mesh_data =
+			Mesh::new(mesh, buffers, |material| self.add_material(material))?;
+		self.meshes.push(mesh_data);
+		self.mesh_indices.insert(mesh.index(), index);
+
+		Ok(index)
+	}
+
+	fn add_camera(&mut self, camera: &gltf::Camera) -> Result<usize> {
+		if let Some(&index) = self.camera_indices.get(&camera.index()) {
+			return Ok(index);
+		}
+
+		let index = self.cameras.len();
+		self.cameras.push(Camera::new(camera)?);
+		self.camera_indices.insert(camera.index(), index);
+
+		Ok(index)
+	}
+
+	fn add_light(
+		&mut self,
+		light: &gltf::khr_lights_punctual::Light,
+	) -> Result<usize> {
+		if let Some(&index) = self.light_indices.get(&light.index()) {
+			return Ok(index);
+		}
+
+		let index = self.lights.len();
+		self.lights.push(Light::new(light)?);
+		self.light_indices.insert(light.index(), index);
+
+		Ok(index)
+	}
+
+	fn add_skin(&mut self, skin: &gltf::Skin) -> Result<usize> {
+		if let Some(&index) = self.skin_indices.get(&skin.index()) {
+			return Ok(index);
+		}
+
+		let index = self.skins.len();
+		self.skins.push(Skin::new(skin, self.buffers)?);
+		self.skin_indices.insert(skin.index(), index);
+
+		Ok(index)
+	}
+
+	fn add_material(&mut self, material: &gltf::Material) -> Result<usize> {
+		if let Some(&index) = self.material_indices.get(&material.index()) {
+			return Ok(index);
+		}
+
+		let material_data = Material::new(material, |texture, srgb| {
+			self.add_texture(texture, srgb)
+		})?;
+
+		let index = self.materials.len();
+		self.materials.push(material_data);
+		self.material_indices.insert(material.index(), index);
+
+		Ok(index)
+	}
+
+	fn add_texture(
+		&mut self,
+		texture: &gltf::Texture,
+		srgb: bool,
+	) -> Result<usize> {
+		let key = (texture.index(), srgb);
+
+		if let Some(&index) = self.texture_indices.get(&key) {
+			return Ok(index);
+		}
+
+		let image = self.add_image(&texture.source())?;
+
+		let index = self.textures.len();
+		self.textures.push(Texture::new(texture, image, srgb)?);
+		self.texture_indices.insert(key, index);
+
+		Ok(index)
+	}
+
+	fn add_image(&mut self, image: &gltf::Image) -> Result<usize> {
+		if let Some(&index) = self.image_indices.get(&image.index()) {
+			return Ok(index);
+		}
+
+		let index = self.images.len();
+		self.images
+			.push(load_image(image, self.buffers, self.base_dir)?);
+		self.image_indices.insert(image.index(), index);
+
+		Ok(index)
 	}
 }
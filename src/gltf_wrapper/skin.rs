@@ -0,0 +1,70 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+pub struct Skin {
+	// glTF node indices until remapped to scene ones
+	joints: Vec<usize>,
+	inverse_bind_matrices: Vec<glm::Mat4>,
+}
+
+impl Skin {
+	pub fn new(skin: &gltf::Skin, buffers: &[Vec<u8>]) -> Result<Self> {
+		log::info!(
+			"Creating skin; index: {}, name: {}",
+			skin.index(),
+			skin.name().unwrap_or_default()
+		);
+
+		let joints =
+			skin.joints().map(|joint| joint.index()).collect::<Vec<_>>();
+
+		let reader =
+			skin.reader(|buffer| Some(buffers[buffer.index()].as_slice()));
+
+		// Absent inverse bind matrices are identities
+		let inverse_bind_matrices = match reader.read_inverse_bind_matrices() {
+			Some(matrices) => matrices
+				.map(|matrix| glm::make_mat4(matrix.concat().as_slice()))
+				.collect::<Vec<_>>(),
+			None => vec![glm::Mat4::identity(); joints.len()],
+		};
+
+		if inverse_bind_matrices.len() < joints.len() {
+			return Err(anyhow!(
+				"Skin has {} joints and {} inverse bind matrices!",
+				joints.len(),
+				inverse_bind_matrices.len()
+			));
+		}
+
+		let skin = Self {
+			joints,
+			inverse_bind_matrices,
+		};
+
+		Ok(skin)
+	}
+
+	// Joint nodes as scene node indices
+	pub fn joints(&self) -> &[usize] {
+		&self.joints
+	}
+
+	pub fn inverse_bind_matrices(&self) -> &[glm::Mat4] {
+		&self.inverse_bind_matrices
+	}
+
+	// Joints must be nodes of the same scene as skinned mesh
+	pub(super) fn remap_joints(
+		&mut self,
+		node_indices: &HashMap<usize, usize>,
+	) -> Result<()> {
+		for joint in &mut self.joints {
+			*joint = *node_indices.get(joint).ok_or_else(|| {
+				anyhow!("Skin joint node {joint} is not in the scene!")
+			})?;
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,231 @@
+use {
+	anyhow::{anyhow, Result},
+	gltf::{json, Accessor, Document},
+};
+
+// Accessors with sparse values or without buffer view are resolved into
+// dense data appended to buffers, and the document is rewritten to point
+// them at it. Vertex attributes, morph targets and animation readers then
+// see plain accessors
+pub fn resolve_sparse_accessors(
+	document: Document,
+	buffers: &mut Vec<Vec<u8>>,
+) -> Result<Document> {
+	let dense_accessors = document
+		.accessors()
+		.filter(|accessor| {
+			accessor.sparse().is_some() || accessor.view().is_none()
+		})
+		.map(|accessor| Ok((accessor.index(), dense_data(&accessor, buffers)?)))
+		.collect::<Result<Vec<_>>>()?;
+
+	if dense_accessors.is_empty() {
+		return Ok(document);
+	}
+
+	let mut root = document.into_json();
+
+	for (index, data) in dense_accessors {
+		log::info!("Resolving dense accessor data; index: {index}");
+
+		// Document buffers are indexed like loaded buffers
+		root.buffers.push(json::Buffer {
+			byte_length: data.len() as _,
+			name: None,
+			uri: None,
+			extensions: None,
+			extras: Default::default(),
+		});
+
+		root.buffer_views.push(json::buffer::View {
+			buffer: json::Index::new(buffers.len() as _),
+			byte_length: data.len() as _,
+			byte_offset: None,
+			byte_stride: None,
+			name: None,
+			target: None,
+			extensions: None,
+			extras: Default::default(),
+		});
+
+		buffers.push(data);
+
+		let accessor = &mut root.accessors[index];
+		accessor.buffer_view =
+			Some(json::Index::new(root.buffer_views.len() as u32 - 1));
+		accessor.byte_offset = 0;
+		accessor.sparse = None;
+	}
+
+	Ok(Document::from_json(root)?)
+}
+
+// Tightly packed elements, base values come from buffer view when present
+// and are zeros otherwise
+fn dense_data(accessor: &Accessor, buffers: &[Vec<u8>]) -> Result<Vec<u8>> {
+	let size = accessor.size();
+	let count = accessor.count();
+
+	let mut data = match accessor.view() {
+		Some(view) => {
+			let stride = view.stride().unwrap_or(size);
+			let begin = view.offset() + accessor.offset();
+
+			(0..count)
+				.map(|element| {
+					bytes(
+						buffers,
+						view.buffer(),
+						begin + element * stride,
+						size,
+					)
+				})
+				.collect::<Result<Vec<_>>>()?
+				.concat()
+		}
+		None => vec![0; count * size],
+	};
+
+	let sparse = match accessor.sparse() {
+		Some(sparse) => sparse,
+		None => return Ok(data),
+	};
+
+	// Sparse indices and values are tightly packed
+	let indices = sparse.indices();
+	let index_size = indices.index_type().size();
+	let indices_begin = indices.view().offset() + indices.offset() as usize;
+
+	let values = sparse.values();
+	let values_begin = values.view().offset() + values.offset() as usize;
+
+	for value in 0..sparse.count() as usize {
+		let index = bytes(
+			buffers,
+			indices.view().buffer(),
+			indices_begin + value * index_size,
+			index_size,
+		)?
+		.iter()
+		.rev()
+		.fold(0, |index, &byte| index << 8 | usize::from(byte));
+
+		let element = data
+			.get_mut(index * size..(index + 1) * size)
+			.ok_or_else(|| {
+				anyhow!(
+					"Sparse index {index} is out of accessor {} bounds",
+					accessor.index()
+				)
+			})?;
+
+		element.copy_from_slice(bytes(
+			buffers,
+			values.view().buffer(),
+			values_begin + value * size,
+			size,
+		)?);
+	}
+
+	Ok(data)
+}
+
+fn bytes<'a>(
+	buffers: &'a [Vec<u8>],
+	buffer: gltf::Buffer,
+	begin: usize,
+	size: usize,
+) -> Result<&'a [u8]> {
+	buffers[buffer.index()]
+		.get(begin..begin + size)
+		.ok_or_else(|| {
+			anyhow!("Accessor is out of buffer {} bounds", buffer.index())
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Floats 1 to 4, sparse indices 2 and 0 and their values 9 and 7
+	fn buffers() -> Vec<Vec<u8>> {
+		let floats = [1.0f32, 2.0, 3.0, 4.0]
+			.iter()
+			.flat_map(|value| value.to_le_bytes());
+		let indices = [2u16, 0].iter().flat_map(|index| index.to_le_bytes());
+		let values = [9.0f32, 7.0].iter().flat_map(|value| value.to_le_bytes());
+
+		vec![floats.chain(indices).chain(values).collect()]
+	}
+
+	fn document(accessor: &str) -> Document {
+		let json = format!(
+			r#"{{
+				"asset": {{ "version": "2.0" }},
+				"buffers": [{{ "byteLength": 28 }}],
+				"bufferViews": [
+					{{ "buffer": 0, "byteLength": 16 }},
+					{{ "buffer": 0, "byteOffset": 16, "byteLength": 4 }},
+					{{ "buffer": 0, "byteOffset": 20, "byteLength": 8 }}
+				],
+				"accessors": [{{
+					{accessor},
+					"componentType": 5126,
+					"type": "SCALAR",
+					"sparse": {{
+						"count": 2,
+						"indices": {{ "bufferView": 1, "componentType": 5123 }},
+						"values": {{ "bufferView": 2 }}
+					}}
+				}}]
+			}}"#
+		);
+
+		gltf::Gltf::from_slice(json.as_bytes()).unwrap().document
+	}
+
+	fn resolved_values(document: Document) -> Vec<f32> {
+		let mut buffers = buffers();
+		let document =
+			resolve_sparse_accessors(document, &mut buffers).unwrap();
+
+		let accessor = document.accessors().next().unwrap();
+		assert!(accessor.sparse().is_none());
+
+		let view = accessor.view().unwrap();
+		let begin = view.offset() + accessor.offset();
+
+		bytes(&buffers, view.buffer(), begin, accessor.count() * 4)
+			.unwrap()
+			.chunks_exact(4)
+			.map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+			.collect()
+	}
+
+	#[test]
+	fn sparse_values_over_buffer_view() {
+		let values =
+			resolved_values(document(r#""bufferView": 0, "count": 4"#));
+
+		assert_eq!(values, [7.0, 2.0, 9.0, 4.0]);
+	}
+
+	#[test]
+	fn sparse_values_over_zeros() {
+		let values = resolved_values(document(r#""count": 4"#));
+
+		assert_eq!(values, [7.0, 0.0, 9.0, 0.0]);
+	}
+
+	#[test]
+	fn sparse_index_out_of_bounds() {
+		let mut buffers = buffers();
+		let document = document(r#""count": 2"#);
+
+		let error = resolve_sparse_accessors(document, &mut buffers)
+			.err()
+			.unwrap();
+
+		assert!(error.to_string().starts_with("Sparse index 2"), "{error}");
+	}
+}
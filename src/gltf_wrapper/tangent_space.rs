@@ -0,0 +1,315 @@
+use ash::vk;
+use bevy_mikktspace::Geometry;
+
+// Vertices of triangle corners in triangle list order, strip triangles
+// alternate order to keep winding. None for points and lines
+pub fn triangle_corners(
+	topology: vk::PrimitiveTopology,
+	indices: &[u32],
+) -> Option<Vec<u32>> {
+	let triangle_count = indices.len().saturating_sub(2);
+
+	let corners = match topology {
+		vk::PrimitiveTopology::TRIANGLE_LIST => {
+			indices[..indices.len() / 3 * 3].to_vec()
+		}
+		vk::PrimitiveTopology::TRIANGLE_STRIP => (0..triangle_count)
+			.flat_map(|i| match i % 2 {
+				0 => [indices[i], indices[i + 1], indices[i + 2]],
+				_ => [indices[i + 1], indices[i], indices[i + 2]],
+			})
+			.collect(),
+		vk::PrimitiveTopology::TRIANGLE_FAN => (0..triangle_count)
+			.flat_map(|i| [indices[0], indices[i + 1], indices[i + 2]])
+			.collect(),
+		_ => return None,
+	};
+
+	Some(corners)
+}
+
+// Normal of each corner, degenerate triangles get zero normals
+pub fn flat_normals(
+	positions: &[glm::Vec3],
+	corners: &[u32],
+) -> Vec<glm::Vec3> {
+	corners
+		.chunks_exact(3)
+		.flat_map(|triangle| {
+			let [a, b, c] =
+				[0, 1, 2].map(|corner| positions[triangle[corner] as usize]);
+
+			let normal = (b - a)
+				.cross(&(c - a))
+				.try_normalize(f32::EPSILON)
+				.unwrap_or_else(glm::Vec3::zeros);
+
+			[normal; 3]
+		})
+		.collect()
+}
+
+// Tangent of each corner with handedness in w, None when generation fails.
+// Corners sharing a vertex differ at UV seams and mirror lines, so they
+// aren't merged here
+pub fn mikktspace_tangents(
+	positions: &[glm::Vec3],
+	normals: &[glm::Vec3],
+	tex_coords: &[glm::Vec2],
+	corners: &[u32],
+) -> Option<Vec<glm::Vec4>> {
+	let mut geometry = MikkTSpaceGeometry {
+		positions,
+		normals,
+		tex_coords,
+		corners,
+		tangents: vec![glm::Vec4::zeros(); corners.len()],
+	};
+
+	bevy_mikktspace::generate_tangents(&mut geometry)
+		.then_some(geometry.tangents)
+}
+
+struct MikkTSpaceGeometry<'a> {
+	positions: &'a [glm::Vec3],
+	normals: &'a [glm::Vec3],
+	tex_coords: &'a [glm::Vec2],
+	corners: &'a [u32],
+	tangents: Vec<glm::Vec4>,
+}
+
+impl<'a> MikkTSpaceGeometry<'a> {
+	fn vertex(&self, face: usize, vert: usize) -> usize {
+		self.corners[face * 3 + vert] as usize
+	}
+}
+
+impl<'a> Geometry for MikkTSpaceGeometry<'a> {
+	fn num_faces(&self) -> usize {
+		self.corners.len() / 3
+	}
+
+	fn num_vertices_of_face(&self, _face: usize) -> usize {
+		3
+	}
+
+	fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+		self.positions[self.vertex(face, vert)].into()
+	}
+
+	fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+		self.normals[self.vertex(face, vert)].into()
+	}
+
+	// glTF texture space has V pointing down, while normal textures have
+	// green pointing up, so V is flipped for bitangents to follow green
+	fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+		let tex_coord = self.tex_coords[self.vertex(face, vert)];
+		[tex_coord.x, 1.0 - tex_coord.y]
+	}
+
+	fn set_tangent_encoded(
+		&mut self,
+		tangent: [f32; 4],
+		face: usize,
+		vert: usize,
+	) {
+		self.tangents[face * 3 + vert] = glm::make_vec4(&tangent);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EPSILON: f32 = 1e-5;
+
+	// Unit quad facing +Z with glTF texture coordinates, V grows downwards
+	fn quad() -> (Vec<glm::Vec3>, Vec<glm::Vec3>, Vec<glm::Vec2>, Vec<u32>) {
+		let positions = vec![
+			glm::vec3(0.0, 0.0, 0.0),
+			glm::vec3(1.0, 0.0, 0.0),
+			glm::vec3(1.0, 1.0, 0.0),
+			glm::vec3(0.0, 1.0, 0.0),
+		];
+
+		let normals = vec![glm::vec3(0.0, 0.0, 1.0); 4];
+
+		let tex_coords = vec![
+			glm::vec2(0.0, 1.0),
+			glm::vec2(1.0, 1.0),
+			glm::vec2(1.0, 0.0),
+			glm::vec2(0.0, 0.0),
+		];
+
+		(positions, normals, tex_coords, vec![0, 1, 2, 0, 2, 3])
+	}
+
+	fn assert_near(actual: &[f32], expected: &[f32]) {
+		assert!(
+			actual
+				.iter()
+				.zip(expected)
+				.all(|(actual, expected)| (actual - expected).abs() < EPSILON),
+			"{actual:?} != {expected:?}"
+		);
+	}
+
+	#[test]
+	fn triangle_list_corners() {
+		let corners = triangle_corners(
+			vk::PrimitiveTopology::TRIANGLE_LIST,
+			&[0, 1, 2, 2, 3, 0, 4],
+		);
+
+		assert_eq!(corners, Some(vec![0, 1, 2, 2, 3, 0]));
+	}
+
+	#[test]
+	fn triangle_strip_corners_keep_winding() {
+		let corners = triangle_corners(
+			vk::PrimitiveTopology::TRIANGLE_STRIP,
+			&[0, 1, 2, 3, 4],
+		);
+
+		assert_eq!(corners, Some(vec![0, 1, 2, 2, 1, 3, 2, 3, 4]));
+	}
+
+	#[test]
+	fn triangle_fan_corners() {
+		let corners = triangle_corners(
+			vk::PrimitiveTopology::TRIANGLE_FAN,
+			&[0, 1, 2, 3],
+		);
+
+		assert_eq!(corners, Some(vec![0, 1, 2, 0, 2, 3]));
+	}
+
+	#[test]
+	fn line_corners() {
+		let corners =
+			triangle_corners(vk::PrimitiveTopology::LINE_LIST, &[0, 1]);
+
+		assert_eq!(corners, None);
+	}
+
+	#[test]
+	fn flat_normals_follow_winding() {
+		let (positions, ..) = quad();
+
+		let normals = flat_normals(&positions, &[0, 1, 2, 0, 3, 2]);
+
+		for normal in &normals[..3] {
+			assert_near(normal.as_slice(), &[0.0, 0.0, 1.0]);
+		}
+
+		for normal in &normals[3..] {
+			assert_near(normal.as_slice(), &[0.0, 0.0, -1.0]);
+		}
+	}
+
+	#[test]
+	fn flat_normals_of_degenerate_triangle() {
+		let (positions, ..) = quad();
+
+		let normals = flat_normals(&positions, &[0, 1, 1]);
+
+		assert_eq!(normals, vec![glm::Vec3::zeros(); 3]);
+	}
+
+	#[test]
+	fn quad_tangents() {
+		let (positions, normals, tex_coords, corners) = quad();
+
+		let tangents =
+			mikktspace_tangents(&positions, &normals, &tex_coords, &corners)
+				.unwrap();
+
+		for tangent in &tangents {
+			assert_near(tangent.as_slice(), &[1.0, 0.0, 0.0, 1.0]);
+		}
+	}
+
+	#[test]
+	fn mirrored_quad_tangents() {
+		let (positions, normals, tex_coords, corners) = quad();
+
+		let tex_coords = tex_coords
+			.iter()
+			.map(|tex_coord| glm::vec2(1.0 - tex_coord.x, tex_coord.y))
+			.collect::<Vec<_>>();
+
+		let tangents =
+			mikktspace_tangents(&positions, &normals, &tex_coords, &corners)
+				.unwrap();
+
+		for tangent in &tangents {
+			assert_near(tangent.as_slice(), &[-1.0, 0.0, 0.0, -1.0]);
+		}
+	}
+
+	#[test]
+	fn skewed_triangle_tangents() {
+		let positions = vec![
+			glm::vec3(0.0, 0.0, 0.0),
+			glm::vec3(2.0, 0.0, 0.0),
+			glm::vec3(0.0, 1.0, 0.0),
+		];
+
+		let normals = vec![glm::vec3(0.0, 0.0, 1.0); 3];
+
+		let tex_coords = vec![
+			glm::vec2(0.0, 1.0),
+			glm::vec2(1.0, 0.0),
+			glm::vec2(0.0, 0.0),
+		];
+
+		let tangents =
+			mikktspace_tangents(&positions, &normals, &tex_coords, &[0, 1, 2])
+				.unwrap();
+
+		// Normalized (2, -1, 0), the direction of growing U in the plane
+		for tangent in &tangents {
+			assert_near(tangent.as_slice(), &[0.894_427, -0.447_214, 0.0, 1.0]);
+		}
+	}
+
+	// Two quads share vertices of the middle edge, U is mirrored on the
+	// right one, so those vertices get different tangents on each side
+	#[test]
+	fn mirror_line_tangents() {
+		let positions = vec![
+			glm::vec3(0.0, 0.0, 0.0),
+			glm::vec3(1.0, 0.0, 0.0),
+			glm::vec3(2.0, 0.0, 0.0),
+			glm::vec3(0.0, 1.0, 0.0),
+			glm::vec3(1.0, 1.0, 0.0),
+			glm::vec3(2.0, 1.0, 0.0),
+		];
+
+		let normals = vec![glm::vec3(0.0, 0.0, 1.0); 6];
+
+		let tex_coords = positions
+			.iter()
+			.map(|position: &glm::Vec3| {
+				glm::vec2(1.0 - (position.x - 1.0).abs(), 1.0 - position.y)
+			})
+			.collect::<Vec<_>>();
+
+		let corners = [0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4];
+
+		let tangents =
+			mikktspace_tangents(&positions, &normals, &tex_coords, &corners)
+				.unwrap();
+
+		assert_eq!(tangents.len(), corners.len());
+
+		for tangent in &tangents[..6] {
+			assert_near(tangent.as_slice(), &[1.0, 0.0, 0.0, 1.0]);
+		}
+
+		for tangent in &tangents[6..] {
+			assert_near(tangent.as_slice(), &[-1.0, 0.0, 0.0, -1.0]);
+		}
+	}
+}
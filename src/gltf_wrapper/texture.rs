@@ -0,0 +1,112 @@
+use anyhow::Result;
+use ash::vk;
+use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+pub struct Texture {
+	index: usize,
+	image: usize,
+	srgb: bool,
+	mag_filter: vk::Filter,
+	min_filter: vk::Filter,
+	mipmap_mode: Option<vk::SamplerMipmapMode>,
+	address_mode_u: vk::SamplerAddressMode,
+	address_mode_v: vk::SamplerAddressMode,
+}
+
+impl Texture {
+	// Image is an index into scene images, color textures are sampled as
+	// sRGB encoded
+	pub fn new(
+		texture: &gltf::Texture,
+		image: usize,
+		srgb: bool,
+	) -> Result<Self> {
+		log::info!(
+			"Creating texture; index: {}, name: {}, sRGB: {srgb}",
+			texture.index(),
+			texture.name().unwrap_or_default()
+		);
+
+		let sampler = texture.sampler();
+
+		// Filters are up to implementation when not specified
+		let mag_filter = match sampler.mag_filter() {
+			Some(MagFilter::Nearest) => vk::Filter::NEAREST,
+			Some(MagFilter::Linear) | None => vk::Filter::LINEAR,
+		};
+
+		// Mipmapping is off for plain Nearest and Linear minification
+		let (min_filter, mipmap_mode) = match sampler.min_filter() {
+			Some(MinFilter::Nearest) => (vk::Filter::NEAREST, None),
+			Some(MinFilter::Linear) => (vk::Filter::LINEAR, None),
+			Some(MinFilter::NearestMipmapNearest) => {
+				(vk::Filter::NEAREST, Some(vk::SamplerMipmapMode::NEAREST))
+			}
+			Some(MinFilter::LinearMipmapNearest) => {
+				(vk::Filter::LINEAR, Some(vk::SamplerMipmapMode::NEAREST))
+			}
+			Some(MinFilter::NearestMipmapLinear) => {
+				(vk::Filter::NEAREST, Some(vk::SamplerMipmapMode::LINEAR))
+			}
+			Some(MinFilter::LinearMipmapLinear) | None => {
+				(vk::Filter::LINEAR, Some(vk::SamplerMipmapMode::LINEAR))
+			}
+		};
+
+		let texture = Self {
+			index: texture.index(),
+			image,
+			srgb,
+			mag_filter,
+			min_filter,
+			mipmap_mode,
+			address_mode_u: Self::address_mode(sampler.wrap_s()),
+			address_mode_v: Self::address_mode(sampler.wrap_t()),
+		};
+
+		Ok(texture)
+	}
+
+	pub fn index(&self) -> usize {
+		self.index
+	}
+
+	pub fn image(&self) -> usize {
+		self.image
+	}
+
+	pub fn srgb(&self) -> bool {
+		self.srgb
+	}
+
+	pub fn mag_filter(&self) -> vk::Filter {
+		self.mag_filter
+	}
+
+	pub fn min_filter(&self) -> vk::Filter {
+		self.min_filter
+	}
+
+	// None when only the first mip level is sampled
+	pub fn mipmap_mode(&self) -> Option<vk::SamplerMipmapMode> {
+		self.mipmap_mode
+	}
+
+	pub fn address_mode_u(&self) -> vk::SamplerAddressMode {
+		self.address_mode_u
+	}
+
+	pub fn address_mode_v(&self) -> vk::SamplerAddressMode {
+		self.address_mode_v
+	}
+
+	fn address_mode(wrapping_mode: WrappingMode) -> vk::SamplerAddressMode {
+		match wrapping_mode {
+			WrappingMode::ClampToEdge => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+			WrappingMode::MirroredRepeat => {
+				vk::SamplerAddressMode::MIRRORED_REPEAT
+			}
+			WrappingMode::Repeat => vk::SamplerAddressMode::REPEAT,
+		}
+	}
+}
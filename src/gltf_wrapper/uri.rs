@@ -0,0 +1,83 @@
+use {
+	anyhow::{anyhow, Context, Result},
+	std::path::Path,
+};
+
+const DATA_URI_SCHEME: &str = "data:";
+const BASE64_SUFFIX: &str = ";base64";
+
+pub struct UriData {
+	pub mime_type: Option<String>,
+	pub bytes: Vec<u8>,
+}
+
+pub fn read(uri: &str, base_dir: &Path) -> Result<UriData> {
+	match uri.strip_prefix(DATA_URI_SCHEME) {
+		Some(data_uri) => read_data_uri(data_uri),
+		None => {
+			let relative_path = String::from_utf8(percent_decode(uri)?)?;
+			let path = base_dir.join(relative_path);
+
+			let bytes = std::fs::read(&path).with_context(|| {
+				format!("Unable to read file: {}", path.display())
+			})?;
+
+			let data = UriData {
+				mime_type: None,
+				bytes,
+			};
+
+			Ok(data)
+		}
+	}
+}
+
+// data:[<mime type>][;base64],<data>
+fn read_data_uri(data_uri: &str) -> Result<UriData> {
+	let (header, payload) = data_uri
+		.split_once(',')
+		.ok_or_else(|| anyhow!("Malformed data URI: no data separator"))?;
+
+	let (mime_type, bytes) = match header.strip_suffix(BASE64_SUFFIX) {
+		Some(mime_type) => {
+			let bytes = base64::decode(payload)
+				.context("Malformed data URI: invalid base64 data")?;
+
+			(mime_type, bytes)
+		}
+		None => (header, percent_decode(payload)?),
+	};
+
+	let mime_type = (!mime_type.is_empty()).then(|| mime_type.to_owned());
+
+	let data = UriData { mime_type, bytes };
+
+	Ok(data)
+}
+
+fn percent_decode(string: &str) -> Result<Vec<u8>> {
+	let mut bytes = string.bytes();
+	let mut decoded = Vec::with_capacity(string.len());
+
+	while let Some(byte) = bytes.next() {
+		if byte == b'%' {
+			let hex = [
+				bytes.next().unwrap_or_default(),
+				bytes.next().unwrap_or_default(),
+			];
+
+			let byte = std::str::from_utf8(&hex)
+				.ok()
+				.and_then(|hex| u8::from_str_radix(hex, 16).ok())
+				.ok_or_else(|| {
+					anyhow!("Malformed URI: invalid percent encoding")
+				})?;
+
+			decoded.push(byte);
+		} else {
+			decoded.push(byte);
+		}
+	}
+
+	Ok(decoded)
+}
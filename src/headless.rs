@@ -0,0 +1,292 @@
+use super::renderer;
+use super::renderer::AnimationPlayer;
+use super::renderer::Environment;
+use super::renderer::RenderTargets;
+use super::renderer::SceneRenderer;
+use super::renderer::SceneView;
+use super::renderer::ShadowQuality;
+use super::vulkan_wrapper::Allocator;
+use super::vulkan_wrapper::Buffer;
+use super::vulkan_wrapper::CommandPool;
+use super::vulkan_wrapper::Fence;
+use super::vulkan_wrapper::Image;
+use super::vulkan_wrapper::ImageView;
+use super::vulkan_wrapper::PipelineCache;
+use super::vulkan_wrapper::Queue;
+use crate::slice_from_ref;
+use anyhow::{anyhow, Context, Result};
+use ash::vk;
+use std::path::PathBuf;
+
+// Matches image crate RGBA8 layout, so read back data is encoded as is.
+// Writes are sRGB encoded, blending happens in linear space
+const COLOR_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+const COLOR_FORMAT_SIZE: usize = 4;
+// Golden image test looks for it to skip on machines without lavapipe
+const NO_CPU_DEVICE: &str = "No CPU Vulkan device found";
+
+pub struct RenderConfig {
+	pub input_file: PathBuf,
+	pub output_file: PathBuf,
+	pub scene_index: usize,
+	pub camera_index: Option<usize>,
+	pub environment_file: Option<PathBuf>,
+	pub skybox: bool,
+	pub shadow_quality: ShadowQuality,
+	pub animation: Option<String>,
+	// Seconds into the animation the scene is posed at
+	pub animation_time: f32,
+	pub width: u32,
+	pub height: u32,
+	// Software rasterizer gives output independent of installed GPUs
+	pub cpu_device: bool,
+}
+
+// Renders single frame offscreen and saves it, no window system required
+pub fn render(config: RenderConfig) -> Result<()> {
+	let (mut scene, mut buffers) =
+		renderer::load_scene(&config.input_file, config.scene_index)?;
+
+	// Without Vulkan loader there is no CPU device either
+	let instance = renderer::create_instance(&[]).map_err(|err| {
+		if config.cpu_device {
+			err.context(NO_CPU_DEVICE)
+		} else {
+			err
+		}
+	})?;
+
+	#[cfg(debug_assertions)]
+	let _debug_messenger =
+		super::vulkan_wrapper::debug_messanger::DebugMessenger::new(
+			&instance,
+			Some(renderer::vulkan_debug_callback),
+		)?;
+
+	let (physical_device, graphics_queue_family_index) =
+		renderer::find_physical_device(&instance, |physical_device, _| {
+			!config.cpu_device
+				|| physical_device.properties.device_type
+					== vk::PhysicalDeviceType::CPU
+		})
+		.ok_or_else(|| {
+			if config.cpu_device {
+				anyhow!(NO_CPU_DEVICE)
+			} else {
+				anyhow!("The suitable physical device is not found")
+			}
+		})?;
+
+	let device = renderer::create_device(
+		&instance,
+		physical_device,
+		graphics_queue_family_index,
+		&[],
+		&mut scene,
+		&mut buffers,
+	)?;
+
+	let queue = Queue::new(&device, graphics_queue_family_index);
+	let allocator = Allocator::new(&instance, &device, physical_device, false)?;
+
+	let command_pool_create_info = vk::CommandPoolCreateInfo::builder()
+		.queue_family_index(graphics_queue_family_index);
+	let command_pool = CommandPool::new(&device, &command_pool_create_info)?;
+
+	let pipeline_cache_path = renderer::pipeline_cache_path();
+	let pipeline_cache =
+		PipelineCache::new(&device, pipeline_cache_path.as_deref())?;
+
+	let environment = Environment::new(
+		&device,
+		&allocator,
+		&command_pool,
+		&queue,
+		&pipeline_cache,
+		config.environment_file.as_deref(),
+		config.skybox,
+	)?;
+
+	let scene_renderer = SceneRenderer::new(
+		&device,
+		&allocator,
+		&command_pool,
+		&queue,
+		&pipeline_cache,
+		&scene,
+		&buffers,
+		COLOR_FORMAT,
+		vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+		environment,
+		config.shadow_quality,
+	)?;
+
+	let extent = vk::Extent2D {
+		width: config.width,
+		height: config.height,
+	};
+
+	let image_extent = vk::Extent3D {
+		width: extent.width,
+		height: extent.height,
+		depth: 1,
+	};
+
+	// color image
+	let color_image_info = vk::ImageCreateInfo::builder()
+		.image_type(vk::ImageType::TYPE_2D)
+		.extent(image_extent)
+		.mip_levels(1)
+		.array_layers(1)
+		.format(COLOR_FORMAT)
+		.tiling(vk::ImageTiling::OPTIMAL)
+		.initial_layout(vk::ImageLayout::UNDEFINED)
+		.usage(
+			vk::ImageUsageFlags::COLOR_ATTACHMENT
+				| vk::ImageUsageFlags::TRANSFER_SRC,
+		)
+		.samples(vk::SampleCountFlags::TYPE_1)
+		.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+	let color_image =
+		Image::new(&device, &allocator, &color_image_info, "color")?;
+
+	let color_subresource_range = vk::ImageSubresourceRange::builder()
+		.aspect_mask(vk::ImageAspectFlags::COLOR)
+		.level_count(1)
+		.layer_count(1)
+		.build();
+
+	let color_image_view_info = vk::ImageViewCreateInfo::builder()
+		.image(color_image.handle())
+		.view_type(vk::ImageViewType::TYPE_2D)
+		.format(COLOR_FORMAT)
+		.subresource_range(color_subresource_range);
+
+	let color_image_view = ImageView::new(&device, &color_image_view_info)?;
+
+	let render_targets = RenderTargets::new(
+		&device,
+		&allocator,
+		scene_renderer.render_pass(),
+		scene_renderer.depth_format(),
+		slice_from_ref(&color_image_view.handle()),
+		extent,
+	)?;
+
+	// read back buffer
+	let read_back_size =
+		extent.width as usize * extent.height as usize * COLOR_FORMAT_SIZE;
+
+	let read_back_buffer_info = vk::BufferCreateInfo::builder()
+		.size(read_back_size as _)
+		.usage(vk::BufferUsageFlags::TRANSFER_DST)
+		.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+	let read_back_buffer = Buffer::new(
+		&device,
+		&allocator,
+		&read_back_buffer_info,
+		gpu_allocator::MemoryLocation::GpuToCpu,
+		"read back buffer",
+	)?;
+
+	let mut frame_uniforms = scene_renderer.create_frame_uniforms()?;
+
+	let mut animation_player =
+		AnimationPlayer::new(&scene, config.animation.as_deref())?;
+	animation_player.set_time(config.animation_time);
+	animation_player.update(0.0, &mut scene);
+
+	let scene_view = SceneView::new(&scene, config.camera_index)?;
+	let (view, projection, viewport_area) =
+		scene_view.camera_matrices(&scene, extent);
+
+	frame_uniforms.update(&view, &projection, &scene)?;
+
+	// record
+	let command_buffer = command_pool
+		.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?;
+
+	let begin_info = vk::CommandBufferBeginInfo::builder()
+		.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+	command_buffer.begin(&begin_info)?;
+
+	scene_renderer.record(
+		&command_buffer,
+		render_targets.framebuffer(0),
+		viewport_area,
+		&frame_uniforms,
+		&scene,
+	);
+
+	// Render pass leaves color image in TRANSFER_SRC_OPTIMAL layout
+	let render_done_barrier = vk::MemoryBarrier::builder()
+		.src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+		.dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+		.build();
+
+	command_buffer.pipeline_barrier(
+		vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+		vk::PipelineStageFlags::TRANSFER,
+		slice_from_ref(&render_done_barrier),
+		&[],
+		&[],
+	);
+
+	let copy_region = vk::BufferImageCopy::builder()
+		.image_subresource(
+			vk::ImageSubresourceLayers::builder()
+				.aspect_mask(vk::ImageAspectFlags::COLOR)
+				.layer_count(1)
+				.build(),
+		)
+		.image_extent(image_extent)
+		.build();
+
+	command_buffer.copy_image_to_buffer(
+		color_image.handle(),
+		vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+		&read_back_buffer,
+		slice_from_ref(&copy_region),
+	);
+
+	let copy_done_barrier = vk::MemoryBarrier::builder()
+		.src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+		.dst_access_mask(vk::AccessFlags::HOST_READ)
+		.build();
+
+	command_buffer.pipeline_barrier(
+		vk::PipelineStageFlags::TRANSFER,
+		vk::PipelineStageFlags::HOST,
+		slice_from_ref(&copy_done_barrier),
+		&[],
+		&[],
+	);
+
+	command_buffer.end()?;
+
+	let render_done_fence = Fence::new(&device, false)?;
+	queue.submit(&command_buffer, &[], &[], &[], &render_done_fence)?;
+	render_done_fence.wait_max_timeout()?;
+
+	read_back_buffer.invalidate()?;
+
+	image::save_buffer(
+		&config.output_file,
+		read_back_buffer.mapped_slice()?,
+		extent.width,
+		extent.height,
+		image::ColorType::Rgba8,
+	)
+	.with_context(|| {
+		format!("Unable to save image: {}", config.output_file.display())
+	})?;
+
+	log::info!("Image saved: {}", config.output_file.display());
+
+	pipeline_cache.save_or_warn();
+
+	Ok(())
+}
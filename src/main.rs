@@ -1,11 +1,15 @@
+mod camera;
 mod gltf_wrapper;
+mod headless;
+mod renderer;
 mod viewer;
 mod vulkan_wrapper;
 mod wsi;
 
 use anyhow::{anyhow, Result};
-use clap::{Arg, Command};
+use clap::{Arg, ArgMatches, Command};
 use core::slice::from_ref as slice_from_ref;
+use renderer::ShadowQuality;
 use std::{
 	path::PathBuf,
 	thread::{self},
@@ -13,13 +17,21 @@ use std::{
 use viewer::LaunchConfig;
 use winit::{
 	dpi::LogicalSize,
-	event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+	event::{
+		ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode,
+		WindowEvent,
+	},
 	event_loop::{ControlFlow, EventLoop},
 	window::WindowBuilder,
 };
 use wsi::PresentTarget;
 
 const DEFAULT_SCENE_INDEX: usize = 0;
+const DEFAULT_SHADOW_QUALITY: &str = "low";
+const DEFAULT_RENDER_SIZE: &str = "1024x768";
+const PIXELS_PER_SCROLL_LINE: f32 = 100.0;
+const ANIMATION_SCRUB_STEP: f32 = 0.1; // seconds per key press
+const ANIMATION_SPEED_STEP: f32 = 2.0;
 
 #[derive(Debug)]
 pub enum CustomEvent {
@@ -31,32 +43,77 @@ fn main() -> Result<()> {
 	let pkg_name = env!("CARGO_PKG_NAME");
 
 	let args = Command::new(pkg_name)
-		.arg(Arg::new("FILE").index(1).help("glTF file path"))
-		.arg(
-			Arg::new("INDEX")
-				.long("index")
-				.takes_value(true)
-				.help("glTF scene index"),
-		)
+		.args(scene_args())
 		.arg(
 			Arg::new("RENDERDOC")
 				.long("renderdoc")
 				.takes_value(false)
 				.help("enables RenderDoc"),
 		)
+		.subcommand(
+			Command::new("render")
+				.about("Renders glTF scene offscreen into an image file")
+				.args(scene_args())
+				.arg(
+					Arg::new("OUTPUT")
+						.short('o')
+						.long("output")
+						.takes_value(true)
+						.required(true)
+						.help("output image path, format follows extension"),
+				)
+				.arg(
+					Arg::new("SIZE")
+						.long("size")
+						.takes_value(true)
+						.default_value(DEFAULT_RENDER_SIZE)
+						.help("output image size as WIDTHxHEIGHT"),
+				)
+				.arg(
+					Arg::new("TIME")
+						.long("time")
+						.takes_value(true)
+						.default_value("0")
+						.help("animation time in seconds"),
+				)
+				.arg(
+					Arg::new("CPU_DEVICE")
+						.long("cpu-device")
+						.takes_value(false)
+						.help("renders on CPU device only, e.g. lavapipe"),
+				),
+		)
 		.get_matches();
 
-	let input_file = args
-		.value_of("FILE")
-		.ok_or_else(|| anyhow!("No input file"))?;
+	if let Some(args) = args.subcommand_matches("render") {
+		let (width, height) = parse_size(args.value_of("SIZE").unwrap())?;
+		let animation_time = args.value_of("TIME").unwrap().parse::<f32>()?;
 
-	let input_file = PathBuf::from(input_file);
+		let config = headless::RenderConfig {
+			input_file: input_file(args)?,
+			output_file: PathBuf::from(args.value_of("OUTPUT").unwrap()),
+			scene_index: scene_index(args)?,
+			camera_index: camera_index(args)?,
+			environment_file: environment_file(args),
+			skybox: args.is_present("SKYBOX"),
+			shadow_quality: shadow_quality(args)?,
+			animation: animation(args),
+			animation_time,
+			width,
+			height,
+			cpu_device: args.is_present("CPU_DEVICE"),
+		};
 
-	let scene_index = if let Some(scene_index) = args.value_of("INDEX") {
-		scene_index.parse::<usize>()? // todo
-	} else {
-		DEFAULT_SCENE_INDEX
-	};
+		return headless::render(config);
+	}
+
+	let input_file = input_file(&args)?;
+	let scene_index = scene_index(&args)?;
+	let camera_index = camera_index(&args)?;
+	let environment_file = environment_file(&args);
+	let skybox = args.is_present("SKYBOX");
+	let shadow_quality = shadow_quality(&args)?;
+	let animation = animation(&args);
 
 	let renderdoc = args.is_present("RENDERDOC");
 
@@ -67,7 +124,7 @@ fn main() -> Result<()> {
 	let window = WindowBuilder::new()
 		.with_title(pkg_name)
 		.with_inner_size(logical_window_size)
-		.with_resizable(false)
+		.with_resizable(true)
 		.build(&event_loop)?;
 
 	let present_target = PresentTarget::new(window);
@@ -83,6 +140,11 @@ fn main() -> Result<()> {
 		let config = LaunchConfig {
 			input_file,
 			scene_index,
+			camera_index,
+			environment_file,
+			skybox,
+			shadow_quality,
+			animation,
 			renderdoc,
 		};
 
@@ -107,26 +169,131 @@ fn main() -> Result<()> {
 			viewer_thread.take().unwrap().join().unwrap();
 		};
 
+		// Viewer thread is gone after an error, events to it are dropped then
+		let send = |event| {
+			let _ = tx.send(event);
+		};
+
 		match event {
 			Event::WindowEvent { event, .. } => match event {
 				WindowEvent::CloseRequested => {
 					log::info!("Shut down");
-					tx.send(viewer::Event::Stop).unwrap();
+					send(viewer::Event::Stop);
 					exit();
 				}
+				WindowEvent::Resized(_) => {
+					send(viewer::Event::Resize);
+				}
 				WindowEvent::KeyboardInput { input, .. } => {
 					if let Some(virtual_keycode) = input.virtual_keycode {
 						match virtual_keycode {
 							VirtualKeyCode::Key2 => match input.state {
-								ElementState::Pressed => tx
-									.send(viewer::Event::RenderDocFrameCapture)
-									.unwrap(),
+								ElementState::Pressed => {
+									send(viewer::Event::RenderDocFrameCapture);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::R => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::ResetCamera);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::F => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::FrameAll);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::C => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::NextCamera);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::Space => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::ToggleAnimation);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::N => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::NextAnimation);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::S => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::NextShadowQuality);
+								}
+								_ => (),
+							},
+							VirtualKeyCode::Left => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::ScrubAnimation {
+										seconds: -ANIMATION_SCRUB_STEP,
+									});
+								}
+								_ => (),
+							},
+							VirtualKeyCode::Right => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::ScrubAnimation {
+										seconds: ANIMATION_SCRUB_STEP,
+									});
+								}
+								_ => (),
+							},
+							VirtualKeyCode::Up => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::ScaleAnimationSpeed {
+										factor: ANIMATION_SPEED_STEP,
+									});
+								}
+								_ => (),
+							},
+							VirtualKeyCode::Down => match input.state {
+								ElementState::Pressed => {
+									send(viewer::Event::ScaleAnimationSpeed {
+										factor: 1.0 / ANIMATION_SPEED_STEP,
+									});
+								}
 								_ => (),
 							},
 							_ => (),
 						}
 					}
 				}
+				WindowEvent::CursorMoved { position, .. } => {
+					send(viewer::Event::CursorMoved {
+						x: position.x as _,
+						y: position.y as _,
+					});
+				}
+				WindowEvent::MouseInput { state, button, .. } => {
+					let button = match button {
+						MouseButton::Left => camera::MouseButton::Left,
+						MouseButton::Middle => camera::MouseButton::Middle,
+						MouseButton::Right => camera::MouseButton::Right,
+						_ => return,
+					};
+
+					send(viewer::Event::MouseButton {
+						button,
+						pressed: state == ElementState::Pressed,
+					});
+				}
+				WindowEvent::MouseWheel { delta, .. } => {
+					let steps = match delta {
+						MouseScrollDelta::LineDelta(_, y) => y,
+						MouseScrollDelta::PixelDelta(position) => {
+							position.y as f32 / PIXELS_PER_SCROLL_LINE
+						}
+					};
+
+					send(viewer::Event::MouseWheel { steps });
+				}
 				_ => (),
 			},
 			Event::UserEvent(event) => match event {
@@ -139,3 +306,90 @@ fn main() -> Result<()> {
 		};
 	});
 }
+
+fn scene_args<'a>() -> [Arg<'a>; 7] {
+	[
+		Arg::new("FILE").index(1).help("glTF file path"),
+		Arg::new("INDEX")
+			.long("index")
+			.takes_value(true)
+			.help("glTF scene index"),
+		Arg::new("CAMERA")
+			.long("camera")
+			.takes_value(true)
+			.help("camera node index in traversal order, defaults to first"),
+		Arg::new("ENVIRONMENT")
+			.long("environment")
+			.takes_value(true)
+			.help("equirectangular HDR environment image for lighting"),
+		Arg::new("SKYBOX")
+			.long("skybox")
+			.takes_value(false)
+			.requires("ENVIRONMENT")
+			.help("draws environment as background"),
+		Arg::new("SHADOW_QUALITY")
+			.long("shadow-quality")
+			.takes_value(true)
+			.possible_values(ShadowQuality::VARIANTS)
+			.default_value(DEFAULT_SHADOW_QUALITY)
+			.help("shadow map resolution and filtering of scene lights"),
+		Arg::new("ANIMATION")
+			.long("animation")
+			.takes_value(true)
+			.help("glTF animation index or name, defaults to the first one"),
+	]
+}
+
+fn input_file(args: &ArgMatches) -> Result<PathBuf> {
+	let input_file = args
+		.value_of("FILE")
+		.ok_or_else(|| anyhow!("No input file"))?;
+
+	Ok(PathBuf::from(input_file))
+}
+
+fn environment_file(args: &ArgMatches) -> Option<PathBuf> {
+	args.value_of("ENVIRONMENT").map(PathBuf::from)
+}
+
+fn animation(args: &ArgMatches) -> Option<String> {
+	args.value_of("ANIMATION").map(str::to_owned)
+}
+
+fn shadow_quality(args: &ArgMatches) -> Result<ShadowQuality> {
+	args.value_of("SHADOW_QUALITY").unwrap().parse()
+}
+
+fn scene_index(args: &ArgMatches) -> Result<usize> {
+	let scene_index = if let Some(scene_index) = args.value_of("INDEX") {
+		scene_index.parse::<usize>()? // todo
+	} else {
+		DEFAULT_SCENE_INDEX
+	};
+
+	Ok(scene_index)
+}
+
+fn camera_index(args: &ArgMatches) -> Result<Option<usize>> {
+	let camera_index = args
+		.value_of("CAMERA")
+		.map(str::parse::<usize>)
+		.transpose()?;
+
+	Ok(camera_index)
+}
+
+fn parse_size(size: &str) -> Result<(u32, u32)> {
+	let (width, height) = size.split_once('x').ok_or_else(|| {
+		anyhow!("Invalid size, expected WIDTHxHEIGHT: {size}")
+	})?;
+
+	let width = width.parse::<u32>()?;
+	let height = height.parse::<u32>()?;
+
+	if width == 0 || height == 0 {
+		return Err(anyhow!("Image size must be non-zero: {size}"));
+	}
+
+	Ok((width, height))
+}
@@ -0,0 +1,106 @@
+use crate::gltf_wrapper::Scene;
+use anyhow::{anyhow, Result};
+
+const MIN_SPEED: f32 = 1.0 / 16.0;
+const MAX_SPEED: f32 = 16.0;
+
+// Plays one of scene animations in a loop
+pub struct AnimationPlayer {
+	animation_count: usize,
+	active_animation: Option<usize>,
+	time: f32,
+	speed: f32,
+	playing: bool,
+}
+
+impl AnimationPlayer {
+	// Animation is selected by index or name, the first one plays when none
+	// is requested
+	pub fn new(scene: &Scene, animation: Option<&str>) -> Result<Self> {
+		let animation_count = scene.animations().len();
+
+		for (index, animation) in scene.animations().iter().enumerate() {
+			log::info!(
+				"Scene animation {index}; name: {}, duration: {}s",
+				animation.name().unwrap_or_default(),
+				animation.duration()
+			);
+		}
+
+		let active_animation = match animation {
+			Some(animation) => {
+				let index = animation
+					.parse::<usize>()
+					.ok()
+					.filter(|&index| index < animation_count)
+					.or_else(|| scene.find_animation(animation))
+					.ok_or_else(|| {
+						anyhow!("Scene has no animation: {animation}")
+					})?;
+
+				Some(index)
+			}
+			None => (animation_count > 0).then_some(0),
+		};
+
+		let animation_player = Self {
+			animation_count,
+			active_animation,
+			time: 0.0,
+			speed: 1.0,
+			playing: true,
+		};
+
+		Ok(animation_player)
+	}
+
+	pub fn set_time(&mut self, time: f32) {
+		self.time = time.max(0.0);
+	}
+
+	pub fn toggle_playback(&mut self) {
+		self.playing = !self.playing;
+
+		log::info!("Animation playing: {}", self.playing);
+	}
+
+	pub fn scrub(&mut self, seconds: f32) {
+		self.time = (self.time + seconds).max(0.0);
+	}
+
+	pub fn scale_speed(&mut self, factor: f32) {
+		self.speed = (self.speed * factor).clamp(MIN_SPEED, MAX_SPEED);
+
+		log::info!("Animation speed: {}", self.speed);
+	}
+
+	pub fn next_animation(&mut self) {
+		self.active_animation = self
+			.active_animation
+			.map(|index| (index + 1) % self.animation_count);
+
+		self.time = 0.0;
+
+		log::info!("Active scene animation: {:?}", self.active_animation);
+	}
+
+	// Advances timeline by elapsed seconds when playing and poses the scene
+	pub fn update(&mut self, elapsed: f32, scene: &mut Scene) {
+		let index = match self.active_animation {
+			Some(index) => index,
+			None => return,
+		};
+
+		if self.playing {
+			self.time += elapsed * self.speed;
+		}
+
+		let duration = scene.animation(index).duration();
+
+		if duration > 0.0 {
+			self.time %= duration;
+		}
+
+		scene.animate(index, self.time);
+	}
+}
@@ -0,0 +1,789 @@
+use super::gen_shader_path;
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{
+	Allocator, Buffer, CommandBuffer, CommandPool, ComputePipeline,
+	DescriptorPool, DescriptorSetLayout, Device, Fence, Image, ImageView,
+	PipelineCache, PipelineLayout, Queue, Sampler, ShaderModule,
+};
+use anyhow::{Context, Result};
+use ash::vk;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+const SHADER_ENTRY_POINT: &std::ffi::CStr = cstr::cstr!("main");
+const SOURCE_FORMAT: vk::Format = vk::Format::R32G32B32A32_SFLOAT;
+const CUBE_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+const CUBE_FACE_COUNT: u32 = 6;
+const WORKGROUP_SIZE: u32 = 8;
+
+// Radiance of uniform environment used when no HDR file is given
+const DEFAULT_RADIANCE: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+
+const MIN_ENVIRONMENT_SIZE: u32 = 16;
+const MAX_ENVIRONMENT_SIZE: u32 = 1024;
+const IRRADIANCE_SIZE: u32 = 32;
+// Irradiance integration samples environment level of about this size
+const IRRADIANCE_SOURCE_SIZE: u32 = 64;
+const PREFILTERED_SIZE: u32 = 128;
+// Roughness is mapped linearly over prefiltered levels
+const PREFILTERED_MIP_LEVELS: u32 = 5;
+const BRDF_LUT_SIZE: u32 = 256;
+
+// Compute descriptor sets, one for each dispatch
+const FILTER_SET_COUNT: u32 = 3 + PREFILTERED_MIP_LEVELS;
+// Irradiance, prefiltered, BRDF LUT and environment for skybox
+const LIGHTING_BINDING_COUNT: u32 = 4;
+
+// Mirrors Parameters push constants in filtering compute shaders
+#[repr(C)]
+struct FilterPushConstants {
+	roughness: f32,
+	source_lod: f32,
+}
+
+// Storage image written by compute dispatch and sampled image it reads from
+struct FilterPass {
+	pipeline: usize,
+	descriptor_set: vk::DescriptorSet,
+	extent: vk::Extent3D,
+	layers: u32,
+	push_constants: FilterPushConstants,
+}
+
+// Image based lighting maps precomputed from equirectangular environment,
+// bound as descriptor set 2 of the material shader
+pub struct Environment<'a> {
+	descriptor_set: vk::DescriptorSet,
+	descriptor_set_layout: DescriptorSetLayout<'a>,
+	_descriptor_pool: DescriptorPool<'a>,
+	_samplers: Vec<Sampler<'a>>,
+	_image_views: Vec<ImageView<'a>>,
+	_images: Vec<Image<'a>>,
+	skybox: bool,
+}
+
+impl<'a> Environment<'a> {
+	pub fn new(
+		device: &'a Device,
+		allocator: &'a Allocator,
+		command_pool: &CommandPool,
+		queue: &Queue,
+		pipeline_cache: &PipelineCache,
+		hdr_file: Option<&Path>,
+		skybox: bool,
+	) -> Result<Self> {
+		let (width, height, pixels) = match hdr_file {
+			Some(hdr_file) => load_hdr(hdr_file).with_context(|| {
+				format!("Unable to load environment: {}", hdr_file.display())
+			})?,
+			None => (1, 1, DEFAULT_RADIANCE.to_vec()),
+		};
+
+		// Cube faces roughly match equirectangular texel density
+		let environment_size = (height / 2)
+			.next_power_of_two()
+			.clamp(MIN_ENVIRONMENT_SIZE, MAX_ENVIRONMENT_SIZE);
+
+		let source_image = create_image(
+			device,
+			allocator,
+			SOURCE_FORMAT,
+			width,
+			height,
+			1,
+			1,
+			vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST,
+			"environment source",
+		)?;
+
+		let environment_image = create_image(
+			device,
+			allocator,
+			CUBE_FORMAT,
+			environment_size,
+			environment_size,
+			32 - environment_size.leading_zeros(),
+			CUBE_FACE_COUNT,
+			vk::ImageUsageFlags::STORAGE
+				| vk::ImageUsageFlags::SAMPLED
+				| vk::ImageUsageFlags::TRANSFER_SRC
+				| vk::ImageUsageFlags::TRANSFER_DST,
+			"environment",
+		)?;
+
+		let irradiance_image = create_image(
+			device,
+			allocator,
+			CUBE_FORMAT,
+			IRRADIANCE_SIZE,
+			IRRADIANCE_SIZE,
+			1,
+			CUBE_FACE_COUNT,
+			vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+			"irradiance",
+		)?;
+
+		let prefiltered_image = create_image(
+			device,
+			allocator,
+			CUBE_FORMAT,
+			PREFILTERED_SIZE,
+			PREFILTERED_SIZE,
+			PREFILTERED_MIP_LEVELS,
+			CUBE_FACE_COUNT,
+			vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+			"prefiltered environment",
+		)?;
+
+		let brdf_lut_image = create_image(
+			device,
+			allocator,
+			CUBE_FORMAT,
+			BRDF_LUT_SIZE,
+			BRDF_LUT_SIZE,
+			1,
+			1,
+			vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+			"BRDF LUT",
+		)?;
+
+		// views
+		let source_view = create_image_view(
+			device,
+			&source_image,
+			vk::ImageViewType::TYPE_2D,
+			0,
+			1,
+		)?;
+
+		let environment_view = create_image_view(
+			device,
+			&environment_image,
+			vk::ImageViewType::CUBE,
+			0,
+			environment_image.mip_levels(),
+		)?;
+
+		let environment_storage_view = create_image_view(
+			device,
+			&environment_image,
+			vk::ImageViewType::TYPE_2D_ARRAY,
+			0,
+			1,
+		)?;
+
+		let irradiance_view = create_image_view(
+			device,
+			&irradiance_image,
+			vk::ImageViewType::CUBE,
+			0,
+			1,
+		)?;
+
+		let irradiance_storage_view = create_image_view(
+			device,
+			&irradiance_image,
+			vk::ImageViewType::TYPE_2D_ARRAY,
+			0,
+			1,
+		)?;
+
+		let prefiltered_view = create_image_view(
+			device,
+			&prefiltered_image,
+			vk::ImageViewType::CUBE,
+			0,
+			PREFILTERED_MIP_LEVELS,
+		)?;
+
+		let prefiltered_storage_views = (0..PREFILTERED_MIP_LEVELS)
+			.map(|level| {
+				create_image_view(
+					device,
+					&prefiltered_image,
+					vk::ImageViewType::TYPE_2D_ARRAY,
+					level,
+					1,
+				)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let brdf_lut_view = create_image_view(
+			device,
+			&brdf_lut_image,
+			vk::ImageViewType::TYPE_2D,
+			0,
+			1,
+		)?;
+
+		// samplers, 32 bit float formats may lack linear filtering support
+		let source_filter = match device.find_supported_format(
+			slice_from_ref(&SOURCE_FORMAT),
+			vk::ImageTiling::OPTIMAL,
+			vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR,
+		) {
+			Some(_) => vk::Filter::LINEAR,
+			None => vk::Filter::NEAREST,
+		};
+
+		let source_sampler_info = vk::SamplerCreateInfo::builder()
+			.mag_filter(source_filter)
+			.min_filter(source_filter)
+			.address_mode_u(vk::SamplerAddressMode::REPEAT)
+			.address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+			.build();
+
+		let source_sampler = Sampler::new(device, &source_sampler_info)?;
+
+		let sampler_info = vk::SamplerCreateInfo::builder()
+			.mag_filter(vk::Filter::LINEAR)
+			.min_filter(vk::Filter::LINEAR)
+			.mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+			.address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+			.address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+			.address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+			.max_lod(vk::LOD_CLAMP_NONE)
+			.build();
+
+		let sampler = Sampler::new(device, &sampler_info)?;
+
+		// descriptor set layouts
+		let filter_bindings = [
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(0)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.stage_flags(vk::ShaderStageFlags::COMPUTE)
+				.build(),
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(1)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+				.stage_flags(vk::ShaderStageFlags::COMPUTE)
+				.build(),
+		];
+
+		let filter_descriptor_set_layout =
+			DescriptorSetLayout::new(device, &filter_bindings)?;
+
+		let lighting_bindings = (0..LIGHTING_BINDING_COUNT)
+			.map(|binding| {
+				vk::DescriptorSetLayoutBinding::builder()
+					.binding(binding)
+					.descriptor_count(1)
+					.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+					.stage_flags(vk::ShaderStageFlags::FRAGMENT)
+					.build()
+			})
+			.collect::<Vec<_>>();
+
+		let descriptor_set_layout =
+			DescriptorSetLayout::new(device, &lighting_bindings)?;
+
+		// descriptor pool
+		let pool_sizes = [
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.descriptor_count(FILTER_SET_COUNT + LIGHTING_BINDING_COUNT)
+				.build(),
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::STORAGE_IMAGE)
+				.descriptor_count(FILTER_SET_COUNT)
+				.build(),
+		];
+
+		let descriptor_pool_create_info =
+			vk::DescriptorPoolCreateInfo::builder()
+				.pool_sizes(&pool_sizes)
+				.max_sets(FILTER_SET_COUNT + 1)
+				.build();
+
+		let descriptor_pool =
+			DescriptorPool::new(device, &descriptor_pool_create_info)?;
+
+		let image_info = |sampler: &Sampler, image_view: &ImageView, layout| {
+			vk::DescriptorImageInfo::builder()
+				.sampler(sampler.handle())
+				.image_view(image_view.handle())
+				.image_layout(layout)
+				.build()
+		};
+
+		// Sampled image and storage image of each dispatch in recording order
+		let mut filter_images = vec![
+			(&source_sampler, &source_view, &environment_storage_view),
+			(&sampler, &environment_view, &irradiance_storage_view),
+		];
+
+		for storage_view in &prefiltered_storage_views {
+			filter_images.push((&sampler, &environment_view, storage_view));
+		}
+
+		// BRDF LUT doesn't read the environment, but the binding is kept valid
+		filter_images.push((&sampler, &environment_view, &brdf_lut_view));
+
+		let filter_descriptor_set_layouts =
+			vec![filter_descriptor_set_layout.handle(); filter_images.len()];
+
+		let filter_descriptor_sets = descriptor_pool
+			.allocate_descriptor_sets(&filter_descriptor_set_layouts)?;
+
+		for (&descriptor_set, (sampler, sampled_view, storage_view)) in
+			filter_descriptor_sets.iter().zip(&filter_images)
+		{
+			let sampled_info = image_info(
+				sampler,
+				sampled_view,
+				vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+			);
+
+			let storage_info =
+				image_info(sampler, storage_view, vk::ImageLayout::GENERAL);
+
+			let descriptor_write_sets = [
+				vk::WriteDescriptorSet::builder()
+					.dst_set(descriptor_set)
+					.dst_binding(0)
+					.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+					.image_info(slice_from_ref(&sampled_info))
+					.build(),
+				vk::WriteDescriptorSet::builder()
+					.dst_set(descriptor_set)
+					.dst_binding(1)
+					.descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+					.image_info(slice_from_ref(&storage_info))
+					.build(),
+			];
+
+			device.update_descriptor_sets(&descriptor_write_sets, &[]);
+		}
+
+		let descriptor_set = descriptor_pool.allocate_descriptor_sets(
+			slice_from_ref(&descriptor_set_layout.handle()),
+		)?[0];
+
+		let lighting_image_infos = [
+			&irradiance_view,
+			&prefiltered_view,
+			&brdf_lut_view,
+			&environment_view,
+		]
+		.map(|image_view| {
+			image_info(
+				&sampler,
+				image_view,
+				vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+			)
+		});
+
+		let descriptor_write_sets = lighting_image_infos
+			.iter()
+			.enumerate()
+			.map(|(binding, image_info)| {
+				vk::WriteDescriptorSet::builder()
+					.dst_set(descriptor_set)
+					.dst_binding(binding as _)
+					.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+					.image_info(slice_from_ref(image_info))
+					.build()
+			})
+			.collect::<Vec<_>>();
+
+		device.update_descriptor_sets(&descriptor_write_sets, &[]);
+
+		// compute pipelines
+		let push_constant_ranges = [vk::PushConstantRange::builder()
+			.stage_flags(vk::ShaderStageFlags::COMPUTE)
+			.size(std::mem::size_of::<FilterPushConstants>() as _)
+			.build()];
+
+		let pipeline_set_layouts = [filter_descriptor_set_layout.handle()];
+
+		let pipeline_layout_create_info =
+			vk::PipelineLayoutCreateInfo::builder()
+				.set_layouts(&pipeline_set_layouts)
+				.push_constant_ranges(&push_constant_ranges);
+
+		let pipeline_layout =
+			PipelineLayout::new(device, &pipeline_layout_create_info)?;
+
+		let pipelines = [
+			"equirect_to_cube.comp",
+			"irradiance.comp",
+			"prefilter.comp",
+			"brdf_lut.comp",
+		]
+		.iter()
+		.map(|shader| {
+			let shader_module =
+				ShaderModule::new(device, &gen_shader_path(shader))?;
+
+			let stage = vk::PipelineShaderStageCreateInfo::builder()
+				.module(shader_module.handle())
+				.name(SHADER_ENTRY_POINT)
+				.stage(vk::ShaderStageFlags::COMPUTE)
+				.build();
+
+			let create_info = vk::ComputePipelineCreateInfo::builder()
+				.stage(stage)
+				.layout(pipeline_layout.handle())
+				.build();
+
+			ComputePipeline::new(device, pipeline_cache, &create_info)
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+		// Irradiance integrates many samples, so it reads a low resolution
+		// level of the environment
+		let irradiance_source_lod = (environment_size / IRRADIANCE_SOURCE_SIZE)
+			.max(1)
+			.trailing_zeros() as f32;
+
+		let mut filter_passes = vec![
+			FilterPass {
+				pipeline: 0,
+				descriptor_set: filter_descriptor_sets[0],
+				extent: environment_image.extent(),
+				layers: CUBE_FACE_COUNT,
+				push_constants: FilterPushConstants {
+					roughness: 0.0,
+					source_lod: 0.0,
+				},
+			},
+			FilterPass {
+				pipeline: 1,
+				descriptor_set: filter_descriptor_sets[1],
+				extent: irradiance_image.extent(),
+				layers: CUBE_FACE_COUNT,
+				push_constants: FilterPushConstants {
+					roughness: 0.0,
+					source_lod: irradiance_source_lod,
+				},
+			},
+		];
+
+		for level in 0..PREFILTERED_MIP_LEVELS {
+			filter_passes.push(FilterPass {
+				pipeline: 2,
+				descriptor_set: filter_descriptor_sets[2 + level as usize],
+				extent: prefiltered_image.level_extent(level),
+				layers: CUBE_FACE_COUNT,
+				push_constants: FilterPushConstants {
+					roughness: level as f32
+						/ (PREFILTERED_MIP_LEVELS - 1) as f32,
+					source_lod: 0.0,
+				},
+			});
+		}
+
+		filter_passes.push(FilterPass {
+			pipeline: 3,
+			descriptor_set: *filter_descriptor_sets.last().unwrap(),
+			extent: brdf_lut_image.extent(),
+			layers: 1,
+			push_constants: FilterPushConstants {
+				roughness: 0.0,
+				source_lod: 0.0,
+			},
+		});
+
+		// staging
+		let buffer_create_info = vk::BufferCreateInfo::builder()
+			.size(std::mem::size_of_val(pixels.as_slice()) as _)
+			.usage(vk::BufferUsageFlags::TRANSFER_SRC)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let mut staging_buffer = Buffer::new(
+			device,
+			allocator,
+			&buffer_create_info,
+			gpu_allocator::MemoryLocation::CpuToGpu,
+			"environment staging buffer",
+		)?;
+
+		staging_buffer
+			.mapped_slice_mut()?
+			.copy_from_slice(as_bytes(&pixels));
+
+		staging_buffer.flush()?;
+
+		// record
+		let command_buffer = command_pool
+			.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?;
+
+		let begin_info = vk::CommandBufferBeginInfo::builder()
+			.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+		command_buffer.begin(&begin_info)?;
+
+		source_image.record_upload(
+			&command_buffer,
+			&staging_buffer,
+			&[0],
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+		);
+
+		let storage_barriers = [
+			&environment_image,
+			&irradiance_image,
+			&prefiltered_image,
+			&brdf_lut_image,
+		]
+		.map(|image| {
+			image.level_barrier(
+				0,
+				image.mip_levels(),
+				(vk::ImageLayout::UNDEFINED, vk::AccessFlags::empty()),
+				(vk::ImageLayout::GENERAL, vk::AccessFlags::SHADER_WRITE),
+			)
+		});
+
+		command_buffer.pipeline_barrier(
+			vk::PipelineStageFlags::TOP_OF_PIPE,
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+			&[],
+			&[],
+			&storage_barriers,
+		);
+
+		let (environment_pass, filter_passes) =
+			filter_passes.split_first().unwrap();
+
+		record_filter_pass(
+			&command_buffer,
+			&pipelines,
+			&pipeline_layout,
+			environment_pass,
+		);
+
+		// Environment levels are blitted from the first one, which filters
+		// then read from
+		let transfer_barrier = environment_image.level_barrier(
+			0,
+			environment_image.mip_levels(),
+			(vk::ImageLayout::GENERAL, vk::AccessFlags::SHADER_WRITE),
+			(
+				vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+				vk::AccessFlags::TRANSFER_WRITE,
+			),
+		);
+
+		command_buffer.pipeline_barrier(
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+			vk::PipelineStageFlags::TRANSFER,
+			&[],
+			&[],
+			slice_from_ref(&transfer_barrier),
+		);
+
+		environment_image.record_mip_generation(
+			&command_buffer,
+			1,
+			vk::PipelineStageFlags::COMPUTE_SHADER
+				| vk::PipelineStageFlags::FRAGMENT_SHADER,
+		);
+
+		for filter_pass in filter_passes {
+			record_filter_pass(
+				&command_buffer,
+				&pipelines,
+				&pipeline_layout,
+				filter_pass,
+			);
+		}
+
+		let read_barriers =
+			[&irradiance_image, &prefiltered_image, &brdf_lut_image].map(
+				|image| {
+					image.level_barrier(
+						0,
+						image.mip_levels(),
+						(
+							vk::ImageLayout::GENERAL,
+							vk::AccessFlags::SHADER_WRITE,
+						),
+						(
+							vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+							vk::AccessFlags::SHADER_READ,
+						),
+					)
+				},
+			);
+
+		command_buffer.pipeline_barrier(
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+			vk::PipelineStageFlags::FRAGMENT_SHADER,
+			&[],
+			&[],
+			&read_barriers,
+		);
+
+		command_buffer.end()?;
+
+		let fence = Fence::new(device, false)?;
+		queue.submit(&command_buffer, &[], &[], &[], &fence)?;
+		fence.wait_max_timeout()?;
+
+		log::info!(
+			"Environment prepared; size: {width}x{height}, cube size: \
+			 {environment_size}"
+		);
+
+		let mut image_views = vec![
+			source_view,
+			environment_view,
+			environment_storage_view,
+			irradiance_view,
+			irradiance_storage_view,
+			prefiltered_view,
+			brdf_lut_view,
+		];
+
+		image_views.extend(prefiltered_storage_views);
+
+		let environment = Self {
+			descriptor_set,
+			descriptor_set_layout,
+			_descriptor_pool: descriptor_pool,
+			_samplers: vec![source_sampler, sampler],
+			_image_views: image_views,
+			_images: vec![
+				source_image,
+				environment_image,
+				irradiance_image,
+				prefiltered_image,
+				brdf_lut_image,
+			],
+			skybox,
+		};
+
+		Ok(environment)
+	}
+
+	pub fn descriptor_set_layout(&self) -> &DescriptorSetLayout<'a> {
+		&self.descriptor_set_layout
+	}
+
+	pub fn descriptor_set(&self) -> vk::DescriptorSet {
+		self.descriptor_set
+	}
+
+	pub fn skybox(&self) -> bool {
+		self.skybox
+	}
+}
+
+fn record_filter_pass(
+	command_buffer: &CommandBuffer,
+	pipelines: &[ComputePipeline],
+	pipeline_layout: &PipelineLayout,
+	filter_pass: &FilterPass,
+) {
+	let group_count = |size: u32| size.div_ceil(WORKGROUP_SIZE);
+
+	command_buffer.bind_pipeline(&pipelines[filter_pass.pipeline]);
+	command_buffer.bind_descriptor_sets(
+		vk::PipelineBindPoint::COMPUTE,
+		pipeline_layout.handle(),
+		0,
+		slice_from_ref(&filter_pass.descriptor_set),
+		&[],
+	);
+	command_buffer.push_constants(
+		pipeline_layout.handle(),
+		vk::ShaderStageFlags::COMPUTE,
+		0,
+		&filter_pass.push_constants,
+	);
+	command_buffer.dispatch(
+		group_count(filter_pass.extent.width),
+		group_count(filter_pass.extent.height),
+		filter_pass.layers,
+	);
+}
+
+// Decodes Radiance HDR file into RGBA pixels
+fn load_hdr(path: &Path) -> Result<(u32, u32, Vec<f32>)> {
+	let reader = BufReader::new(File::open(path)?);
+	let decoder = image::codecs::hdr::HdrDecoder::new(reader)?;
+	let metadata = decoder.metadata();
+
+	let pixels = decoder
+		.read_image_hdr()?
+		.into_iter()
+		.flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 1.0])
+		.collect();
+
+	Ok((metadata.width, metadata.height, pixels))
+}
+
+fn as_bytes(pixels: &[f32]) -> &[u8] {
+	unsafe {
+		std::slice::from_raw_parts(
+			pixels.as_ptr() as *const u8,
+			std::mem::size_of_val(pixels),
+		)
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_image<'a>(
+	device: &'a Device,
+	allocator: &'a Allocator,
+	format: vk::Format,
+	width: u32,
+	height: u32,
+	mip_levels: u32,
+	array_layers: u32,
+	usage: vk::ImageUsageFlags,
+	name: &str,
+) -> Result<Image<'a>> {
+	let flags = if array_layers == CUBE_FACE_COUNT {
+		vk::ImageCreateFlags::CUBE_COMPATIBLE
+	} else {
+		vk::ImageCreateFlags::empty()
+	};
+
+	let image_create_info = vk::ImageCreateInfo::builder()
+		.flags(flags)
+		.image_type(vk::ImageType::TYPE_2D)
+		.extent(vk::Extent3D {
+			width,
+			height,
+			depth: 1,
+		})
+		.mip_levels(mip_levels)
+		.array_layers(array_layers)
+		.format(format)
+		.tiling(vk::ImageTiling::OPTIMAL)
+		.initial_layout(vk::ImageLayout::UNDEFINED)
+		.usage(usage)
+		.samples(vk::SampleCountFlags::TYPE_1)
+		.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+	Image::new(device, allocator, &image_create_info, name)
+}
+
+fn create_image_view<'a>(
+	device: &'a Device,
+	image: &Image,
+	view_type: vk::ImageViewType,
+	base_level: u32,
+	level_count: u32,
+) -> Result<ImageView<'a>> {
+	let subresource_range = vk::ImageSubresourceRange::builder()
+		.aspect_mask(vk::ImageAspectFlags::COLOR)
+		.base_mip_level(base_level)
+		.level_count(level_count)
+		.layer_count(image.array_layers())
+		.build();
+
+	let image_view_info = vk::ImageViewCreateInfo::builder()
+		.image(image.handle())
+		.view_type(view_type)
+		.format(image.format())
+		.subresource_range(subresource_range);
+
+	ImageView::new(device, &image_view_info)
+}
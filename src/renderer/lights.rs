@@ -0,0 +1,188 @@
+use crate::gltf_wrapper::{Aabb, LightKind, Scene};
+
+// Lights scenes without their own are lit with, follows camera direction
+const HEADLIGHT_INTENSITY: f32 = 3.0;
+
+const DIRECTIONAL_LIGHT_TYPE: u32 = 0;
+const POINT_LIGHT_TYPE: u32 = 1;
+const SPOT_LIGHT_TYPE: u32 = 2;
+
+// Spot shadow near plane relative to far one, keeps depth precision usable
+const SPOT_SHADOW_NEAR_RATIO: f32 = 0.001;
+const MIN_SHADOW_RADIUS: f32 = 0.01;
+
+// Mirrors Light structure in geometry.frag.hlsl, std430 layout
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LightData {
+	position: glm::Vec3,
+	// Zero for infinite range
+	range: f32,
+	direction: glm::Vec3,
+	light_type: u32,
+	// Color premultiplied by intensity
+	color: glm::Vec3,
+	// Spot cone falloff is saturate(cos_angle * scale + offset)
+	spot_scale: f32,
+	spot_offset: f32,
+	// Shadow map layer, negative when light casts no shadow
+	shadow_index: i32,
+	_padding: [f32; 2],
+	shadow_matrix: glm::Mat4,
+}
+
+// Point lights would need cube maps, so they don't cast shadows
+fn casts_shadow(kind: LightKind) -> bool {
+	!matches!(kind, LightKind::Point)
+}
+
+pub fn shadow_caster_count(scene: &Scene) -> usize {
+	scene
+		.light_nodes()
+		.into_iter()
+		.filter(|&index| {
+			let light = scene.node(index).light().unwrap();
+			casts_shadow(scene.light(light).kind())
+		})
+		.count()
+}
+
+// World space lights of scene light nodes, or single headlight when there
+// are none. Shadow casters get view projection matrices of their maps in
+// shadow index order
+pub fn scene_lights(
+	scene: &Scene,
+	view: &glm::Mat4,
+	shadows: bool,
+) -> (Vec<LightData>, Vec<glm::Mat4>) {
+	let bounds = scene.bounds();
+	let mut shadow_matrices = Vec::new();
+
+	let lights = scene
+		.light_nodes()
+		.into_iter()
+		.map(|index| {
+			let node = scene.node(index);
+			let light = scene.light(node.light().unwrap());
+			let transform = node.world_transform();
+
+			let position = transform.column(3).xyz();
+			let direction = (transform * glm::vec4(0.0, 0.0, -1.0, 0.0))
+				.xyz()
+				.normalize();
+			let color = light.color() * light.intensity();
+
+			let (light_type, spot_scale, spot_offset) = match light.kind() {
+				LightKind::Directional => (DIRECTIONAL_LIGHT_TYPE, 0.0, 1.0),
+				LightKind::Point => (POINT_LIGHT_TYPE, 0.0, 1.0),
+				LightKind::Spot {
+					inner_cone_angle,
+					outer_cone_angle,
+				} => {
+					let cos_inner = inner_cone_angle.cos();
+					let cos_outer = outer_cone_angle.cos();
+					let scale = 1.0 / (cos_inner - cos_outer).max(0.001);
+
+					(SPOT_LIGHT_TYPE, scale, -cos_outer * scale)
+				}
+			};
+
+			let shadow_matrix = match bounds {
+				Some(bounds) if shadows && casts_shadow(light.kind()) => Some(
+					shadow_matrix(light.kind(), &position, &direction, &bounds),
+				),
+				_ => None,
+			};
+
+			let shadow_index = match shadow_matrix {
+				Some(shadow_matrix) => {
+					shadow_matrices.push(shadow_matrix);
+					shadow_matrices.len() as i32 - 1
+				}
+				None => -1,
+			};
+
+			LightData {
+				position,
+				range: light.range().unwrap_or(0.0),
+				direction,
+				light_type,
+				color,
+				spot_scale,
+				spot_offset,
+				shadow_index,
+				_padding: [0.0; 2],
+				shadow_matrix: shadow_matrix
+					.unwrap_or_else(glm::Mat4::identity),
+			}
+		})
+		.collect::<Vec<_>>();
+
+	if !lights.is_empty() {
+		return (lights, shadow_matrices);
+	}
+
+	let camera_transform = glm::inverse(view);
+	let direction = (camera_transform * glm::vec4(0.0, 0.0, -1.0, 0.0))
+		.xyz()
+		.normalize();
+
+	let headlight = LightData {
+		position: glm::Vec3::zeros(),
+		range: 0.0,
+		direction,
+		light_type: DIRECTIONAL_LIGHT_TYPE,
+		color: glm::vec3(1.0, 1.0, 1.0) * HEADLIGHT_INTENSITY,
+		spot_scale: 0.0,
+		spot_offset: 1.0,
+		shadow_index: -1,
+		_padding: [0.0; 2],
+		shadow_matrix: glm::Mat4::identity(),
+	};
+
+	(vec![headlight], shadow_matrices)
+}
+
+// Directional maps cover whole scene bounds orthographically, spot maps
+// cover the outer cone up to the farthest bounds point
+fn shadow_matrix(
+	kind: LightKind,
+	position: &glm::Vec3,
+	direction: &glm::Vec3,
+	bounds: &Aabb,
+) -> glm::Mat4 {
+	let center = bounds.center();
+	let radius = bounds.radius().max(MIN_SHADOW_RADIUS);
+
+	let up = if direction.y.abs() > 0.99 {
+		glm::vec3(1.0, 0.0, 0.0)
+	} else {
+		glm::vec3(0.0, 1.0, 0.0)
+	};
+
+	match kind {
+		LightKind::Spot {
+			outer_cone_angle, ..
+		} => {
+			let view = glm::look_at(position, &(position + direction), &up);
+			let far = glm::distance(position, &center) + radius;
+			let near = far * SPOT_SHADOW_NEAR_RATIO;
+			let fov = (2.0 * outer_cone_angle).min(std::f32::consts::PI * 0.99);
+
+			glm::perspective_rh_zo(1.0, fov, near, far) * view
+		}
+		_ => {
+			let eye = center - direction * radius;
+			let view = glm::look_at(&eye, &center, &up);
+
+			glm::ortho_rh_zo(
+				-radius,
+				radius,
+				-radius,
+				radius,
+				0.0,
+				2.0 * radius,
+			) * view
+		}
+	}
+}
@@ -0,0 +1,226 @@
+use super::textures::SceneTextures;
+use crate::gltf_wrapper::{AlphaMode, Material, TextureInfo};
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{
+	Allocator, Buffer, DescriptorPool, DescriptorSetLayout, Device,
+};
+use anyhow::Result;
+use ash::vk;
+
+// Binding 0 is material uniform buffer, followed by textures in order of
+// MaterialUBO texture coordinate sets
+const TEXTURE_BINDING_COUNT: usize = 5;
+
+const OPAQUE_ALPHA_MODE: u32 = 0;
+const MASK_ALPHA_MODE: u32 = 1;
+const BLEND_ALPHA_MODE: u32 = 2;
+
+// Mirrors Material constant buffer in geometry.frag.hlsl
+#[repr(C)]
+struct MaterialUBO {
+	base_color_factor: glm::Vec4,
+	emissive_factor: glm::Vec3,
+	normal_scale: f32,
+	metallic_factor: f32,
+	roughness_factor: f32,
+	occlusion_strength: f32,
+	// Texture coordinate set, negative when texture is absent
+	base_color_tex_coord: i32,
+	metallic_roughness_tex_coord: i32,
+	normal_tex_coord: i32,
+	occlusion_tex_coord: i32,
+	emissive_tex_coord: i32,
+	alpha_mode: u32,
+	alpha_cutoff: f32,
+}
+
+impl MaterialUBO {
+	fn new(material: &Material) -> Self {
+		let tex_coord = |info: Option<&TextureInfo>| {
+			info.map_or(-1, |info| info.tex_coord as i32)
+		};
+
+		Self {
+			base_color_factor: *material.base_color_factor(),
+			emissive_factor: *material.emissive_factor(),
+			normal_scale: material.normal_scale(),
+			metallic_factor: material.metallic_factor(),
+			roughness_factor: material.roughness_factor(),
+			occlusion_strength: material.occlusion_strength(),
+			base_color_tex_coord: tex_coord(material.base_color_texture()),
+			metallic_roughness_tex_coord: tex_coord(
+				material.metallic_roughness_texture(),
+			),
+			normal_tex_coord: tex_coord(material.normal_texture()),
+			occlusion_tex_coord: tex_coord(material.occlusion_texture()),
+			emissive_tex_coord: tex_coord(material.emissive_texture()),
+			alpha_mode: match material.alpha_mode() {
+				AlphaMode::Opaque => OPAQUE_ALPHA_MODE,
+				AlphaMode::Mask => MASK_ALPHA_MODE,
+				AlphaMode::Blend => BLEND_ALPHA_MODE,
+			},
+			alpha_cutoff: material.alpha_cutoff(),
+		}
+	}
+}
+
+fn material_textures(material: &Material) -> [Option<&TextureInfo>; 5] {
+	[
+		material.base_color_texture(),
+		material.metallic_roughness_texture(),
+		material.normal_texture(),
+		material.occlusion_texture(),
+		material.emissive_texture(),
+	]
+}
+
+// Per material uniform buffers and descriptor sets, indexed as scene
+// materials
+pub struct MaterialDescriptors<'a> {
+	descriptor_sets: Vec<vk::DescriptorSet>,
+	_descriptor_pool: DescriptorPool<'a>,
+	_uniform_buffers: Vec<Buffer<'a>>,
+	descriptor_set_layout: DescriptorSetLayout<'a>,
+}
+
+impl<'a> MaterialDescriptors<'a> {
+	pub fn new(
+		device: &'a Device,
+		allocator: &'a Allocator,
+		materials: &[Material],
+		textures: &SceneTextures,
+	) -> Result<Self> {
+		let uniform_buffer_binding = vk::DescriptorSetLayoutBinding::builder()
+			.binding(0)
+			.descriptor_count(1)
+			.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+			.stage_flags(vk::ShaderStageFlags::FRAGMENT)
+			.build();
+
+		let texture_bindings = (1..=TEXTURE_BINDING_COUNT).map(|binding| {
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(binding as _)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.stage_flags(vk::ShaderStageFlags::FRAGMENT)
+				.build()
+		});
+
+		let bindings = std::iter::once(uniform_buffer_binding)
+			.chain(texture_bindings)
+			.collect::<Vec<_>>();
+
+		let descriptor_set_layout =
+			DescriptorSetLayout::new(device, &bindings)?;
+
+		// Scenes without meshes have no materials, but pool can't be empty
+		let set_count = materials.len().max(1);
+
+		let pool_sizes = [
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::UNIFORM_BUFFER)
+				.descriptor_count(set_count as _)
+				.build(),
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.descriptor_count((set_count * TEXTURE_BINDING_COUNT) as _)
+				.build(),
+		];
+
+		let descriptor_pool_create_info =
+			vk::DescriptorPoolCreateInfo::builder()
+				.pool_sizes(&pool_sizes)
+				.max_sets(set_count as _)
+				.build();
+
+		let descriptor_pool =
+			DescriptorPool::new(device, &descriptor_pool_create_info)?;
+
+		let uniform_buffer_size = std::mem::size_of::<MaterialUBO>();
+
+		let uniform_buffers = materials
+			.iter()
+			.map(|material| {
+				let buffer_create_info = vk::BufferCreateInfo::builder()
+					.size(uniform_buffer_size as _)
+					.usage(vk::BufferUsageFlags::UNIFORM_BUFFER)
+					.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+				let mut buffer = Buffer::new(
+					device,
+					allocator,
+					&buffer_create_info,
+					gpu_allocator::MemoryLocation::CpuToGpu,
+					"material buffer",
+				)?;
+
+				buffer.copy_into_n_flush(&MaterialUBO::new(material))?;
+
+				Ok(buffer)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let descriptor_set_layouts =
+			vec![descriptor_set_layout.handle(); materials.len()];
+
+		let descriptor_sets = if materials.is_empty() {
+			Vec::new()
+		} else {
+			descriptor_pool.allocate_descriptor_sets(&descriptor_set_layouts)?
+		};
+
+		for ((&descriptor_set, buffer), material) in
+			descriptor_sets.iter().zip(&uniform_buffers).zip(materials)
+		{
+			let descriptor_buffer_info = vk::DescriptorBufferInfo::builder()
+				.buffer(buffer.handle())
+				.range(uniform_buffer_size as _)
+				.build();
+
+			let descriptor_write_set = vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(0)
+				.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+				.buffer_info(slice_from_ref(&descriptor_buffer_info))
+				.build();
+
+			let image_infos = material_textures(material)
+				.map(|info| textures.image_info(info.map(|info| info.texture)));
+
+			let texture_write_sets =
+				image_infos.iter().enumerate().map(|(index, image_info)| {
+					vk::WriteDescriptorSet::builder()
+						.dst_set(descriptor_set)
+						.dst_binding(index as u32 + 1)
+						.descriptor_type(
+							vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+						)
+						.image_info(slice_from_ref(image_info))
+						.build()
+				});
+
+			let descriptor_write_sets = std::iter::once(descriptor_write_set)
+				.chain(texture_write_sets)
+				.collect::<Vec<_>>();
+
+			device.update_descriptor_sets(&descriptor_write_sets, &[]);
+		}
+
+		let material_descriptors = Self {
+			descriptor_sets,
+			_descriptor_pool: descriptor_pool,
+			_uniform_buffers: uniform_buffers,
+			descriptor_set_layout,
+		};
+
+		Ok(material_descriptors)
+	}
+
+	pub fn descriptor_set_layout(&self) -> &DescriptorSetLayout<'a> {
+		&self.descriptor_set_layout
+	}
+
+	pub fn descriptor_set(&self, material: usize) -> vk::DescriptorSet {
+		self.descriptor_sets[material]
+	}
+}
@@ -0,0 +1,87 @@
+use {
+	crate::gltf_wrapper::{Animation, Scene},
+	std::collections::HashSet,
+};
+
+// Root joint of each skin is the first one without a joint parent, it
+// places the skinned mesh as a whole
+pub fn skin_roots(scene: &Scene) -> Vec<usize> {
+	scene
+		.skins()
+		.iter()
+		.map(|skin| {
+			let joints = skin.joints();
+
+			joints
+				.iter()
+				.position(|&joint| {
+					scene
+						.node(joint)
+						.parent()
+						.map_or(true, |parent| !joints.contains(&parent))
+				})
+				.unwrap_or_default()
+		})
+		.collect()
+}
+
+// Node whose world transform places the mesh of the given one, and the
+// transform applied before it. Skinned meshes go through root joint
+fn placement(
+	scene: &Scene,
+	index: usize,
+	skin_roots: &[usize],
+) -> (usize, glm::Mat4) {
+	let skin = match scene.node(index).skin() {
+		Some(skin) => skin,
+		None => return (index, glm::Mat4::identity()),
+	};
+
+	let root = skin_roots[skin];
+	let skin = scene.skin(skin);
+
+	match skin.joints().get(root) {
+		Some(&joint) => (joint, skin.inverse_bind_matrices()[root]),
+		None => (index, glm::Mat4::identity()),
+	}
+}
+
+// Negative determinant flips winding of front faces
+pub fn mirrored(scene: &Scene, index: usize, skin_roots: &[usize]) -> bool {
+	let (node, bind) = placement(scene, index, skin_roots);
+	glm::determinant(&(scene.node(node).world_transform() * bind)) < 0.0
+}
+
+// Meshes of nodes which can get negative determinant, either at rest or
+// by animated scale of any ancestor. Only those need mirrored pipelines
+pub fn mirrorable_meshes(scene: &Scene, skin_roots: &[usize]) -> Vec<bool> {
+	let animated = scene
+		.animations()
+		.iter()
+		.flat_map(Animation::mirrored_nodes)
+		.collect::<HashSet<_>>();
+
+	// Nodes are stored parents first
+	let mut mirrorable_nodes = Vec::with_capacity(scene.nodes().len());
+
+	for (index, node) in scene.nodes().iter().enumerate() {
+		let local = glm::determinant(&node.local_transform().matrix()) < 0.0;
+		let parent = node
+			.parent()
+			.map_or(false, |parent| mirrorable_nodes[parent]);
+
+		mirrorable_nodes.push(local || parent || animated.contains(&index));
+	}
+
+	let mut meshes = vec![false; scene.meshes().len()];
+
+	for (index, node) in scene.nodes().iter().enumerate() {
+		if let Some(mesh) = node.mesh() {
+			let (node, bind) = placement(scene, index, skin_roots);
+			meshes[mesh] |=
+				mirrorable_nodes[node] || glm::determinant(&bind) < 0.0;
+		}
+	}
+
+	meshes
+}
@@ -0,0 +1,81 @@
+mod animation_player;
+mod environment;
+mod lights;
+mod materials;
+mod mirroring;
+mod morph_targets;
+mod pipelines;
+mod primitive_draw;
+mod render_targets;
+mod scene_renderer;
+mod scene_view;
+mod setup;
+mod shadows;
+mod skinning;
+mod textures;
+
+pub use animation_player::AnimationPlayer;
+pub use environment::Environment;
+pub use render_targets::RenderTargets;
+pub use scene_renderer::FrameUniforms;
+pub use scene_renderer::SceneRenderer;
+pub use scene_view::SceneView;
+pub use setup::create_device;
+pub use setup::create_instance;
+pub use setup::find_physical_device;
+pub use setup::load_scene;
+pub use setup::pipeline_cache_path;
+pub use shadows::ShadowQuality;
+
+use ash::vk;
+use std::path::PathBuf;
+
+pub fn gen_shader_path(name: &str) -> PathBuf {
+	PathBuf::from("gen")
+		.join(if cfg!(debug_assertions) {
+			"debug"
+		} else {
+			"release"
+		})
+		.join("shaders")
+		.join(name)
+}
+
+#[cfg(debug_assertions)]
+pub unsafe extern "system" fn vulkan_debug_callback(
+	severity_flags: vk::DebugUtilsMessageSeverityFlagsEXT,
+	message_type_flags: vk::DebugUtilsMessageTypeFlagsEXT,
+	callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
+	_: *mut std::os::raw::c_void,
+) -> vk::Bool32 {
+	use log::{error, info, trace, warn};
+
+	use vk::DebugUtilsMessageSeverityFlagsEXT;
+	use vk::DebugUtilsMessageTypeFlagsEXT;
+
+	let callback_data = *callback_data;
+
+	if let Ok(message) =
+		std::ffi::CStr::from_ptr(callback_data.p_message).to_str()
+	{
+		let message_type = match message_type_flags {
+			DebugUtilsMessageTypeFlagsEXT::GENERAL => "[General]",
+			DebugUtilsMessageTypeFlagsEXT::PERFORMANCE => "[Performance]",
+			DebugUtilsMessageTypeFlagsEXT::VALIDATION => "[Validation]",
+			_ => "[Unknown]",
+		};
+
+		let message = format!("{message_type} {message}");
+
+		match severity_flags {
+			DebugUtilsMessageSeverityFlagsEXT::WARNING => warn!("{message}"),
+			DebugUtilsMessageSeverityFlagsEXT::ERROR => error!("{message}"),
+			DebugUtilsMessageSeverityFlagsEXT::INFO => info!("{message}"),
+			_ => trace!("{message}"),
+		};
+	} else {
+		error!("Vulkan debug callback: unable to get message data!");
+	}
+
+	vk::FALSE
+}
@@ -0,0 +1,110 @@
+use crate::gltf_wrapper::{Node, Primitive, Scene};
+
+// Deltas of primitive targets in scene delta buffer, no targets when count
+// is zero
+#[derive(Clone, Copy, Default)]
+pub struct MorphTargetRange {
+	pub delta_offset: u32,
+	pub target_count: u32,
+	pub vertex_count: u32,
+}
+
+// Deltas are laid out target by target, each vertex has position, normal
+// and tangent delta in a row. Ranges are indexed as scene meshes and their
+// primitives
+pub fn morph_deltas(
+	scene: &Scene,
+) -> (Vec<glm::Vec4>, Vec<Vec<MorphTargetRange>>) {
+	let mut deltas = Vec::new();
+
+	let ranges = scene
+		.meshes()
+		.iter()
+		.map(|mesh| {
+			mesh.primitives()
+				.iter()
+				.map(|primitive| push_primitive_deltas(primitive, &mut deltas))
+				.collect()
+		})
+		.collect();
+
+	(deltas, ranges)
+}
+
+fn push_primitive_deltas(
+	primitive: &Primitive,
+	deltas: &mut Vec<glm::Vec4>,
+) -> MorphTargetRange {
+	let targets = primitive.morph_targets();
+	let vertex_count = primitive.vertex_count();
+
+	if targets.is_empty() {
+		return MorphTargetRange::default();
+	}
+
+	let range = MorphTargetRange {
+		delta_offset: deltas.len() as _,
+		target_count: targets.len() as _,
+		vertex_count,
+	};
+
+	// Attributes a target doesn't displace get zero deltas
+	let delta = |values: &[glm::Vec3], vertex: usize| {
+		values
+			.get(vertex)
+			.map_or_else(glm::Vec4::zeros, |value| value.push(0.0))
+	};
+
+	for target in targets {
+		for vertex in 0..vertex_count as usize {
+			deltas.push(delta(&target.positions, vertex));
+			deltas.push(delta(&target.normals, vertex));
+			deltas.push(delta(&target.tangents, vertex));
+		}
+	}
+
+	range
+}
+
+fn node_target_count(scene: &Scene, node: &Node) -> usize {
+	node.mesh()
+		.map_or(0, |mesh| scene.mesh(mesh).morph_target_count())
+}
+
+// Start of node weights in morph weights, None for nodes without targets
+pub fn weight_offsets(scene: &Scene) -> Vec<Option<usize>> {
+	scene
+		.nodes()
+		.iter()
+		.scan(0, |offset, node| {
+			let target_count = node_target_count(scene, node);
+			let node_offset = (target_count > 0).then_some(*offset);
+			*offset += target_count;
+
+			Some(node_offset)
+		})
+		.collect()
+}
+
+pub fn weight_count(scene: &Scene) -> usize {
+	scene
+		.nodes()
+		.iter()
+		.map(|node| node_target_count(scene, node))
+		.sum()
+}
+
+// Node weights in node order, each padded or cut to its target count
+pub fn morph_weights(scene: &Scene) -> Vec<f32> {
+	scene
+		.nodes()
+		.iter()
+		.flat_map(|node| {
+			node.weights()
+				.iter()
+				.copied()
+				.chain(std::iter::repeat(0.0))
+				.take(node_target_count(scene, node))
+		})
+		.collect()
+}
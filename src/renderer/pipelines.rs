@@ -0,0 +1,169 @@
+use crate::gltf_wrapper::Primitive;
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{Device, GraphicsPipeline, PipelineCache};
+use anyhow::Result;
+use ash::vk;
+use std::collections::HashMap;
+
+// Pipeline state varying between primitive draws
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+	pub topology: vk::PrimitiveTopology,
+	// Format and stride of each attribute in Primitive::LOCATIONS order
+	pub vertex_layout: Vec<(vk::Format, u32)>,
+	pub cull_mode: vk::CullModeFlags,
+	pub front_face: vk::FrontFace,
+	pub blend: bool,
+}
+
+// Graphics pipelines created from a shared base on first use, draws with
+// equal keys share one pipeline
+pub struct PipelineVariants<'a> {
+	device: &'a Device<'a>,
+	pipeline_cache: &'a PipelineCache<'a>,
+	pipelines: Vec<GraphicsPipeline<'a>>,
+	indices: HashMap<PipelineKey, usize>,
+}
+
+impl<'a> PipelineVariants<'a> {
+	pub fn new(device: &'a Device, pipeline_cache: &'a PipelineCache) -> Self {
+		Self {
+			device,
+			pipeline_cache,
+			pipelines: Vec::new(),
+			indices: HashMap::new(),
+		}
+	}
+
+	// Base provides shaders, layout and render pass, the rest of state comes
+	// from the key
+	pub fn get_or_create(
+		&mut self,
+		key: &PipelineKey,
+		base_pipeline_create_info: &vk::GraphicsPipelineCreateInfo,
+	) -> Result<usize> {
+		if let Some(&index) = self.indices.get(key) {
+			return Ok(index);
+		}
+
+		let pipeline = create_pipeline(
+			self.device,
+			self.pipeline_cache,
+			key,
+			base_pipeline_create_info,
+		)?;
+
+		self.pipelines.push(pipeline);
+		self.indices.insert(key.clone(), self.pipelines.len() - 1);
+
+		log::info!("Pipeline variant count: {}", self.pipelines.len());
+
+		Ok(self.pipelines.len() - 1)
+	}
+
+	pub fn pipeline(&self, index: usize) -> &GraphicsPipeline<'a> {
+		&self.pipelines[index]
+	}
+}
+
+fn create_pipeline<'a>(
+	device: &'a Device,
+	pipeline_cache: &PipelineCache,
+	key: &PipelineKey,
+	base_pipeline_create_info: &vk::GraphicsPipelineCreateInfo,
+) -> Result<GraphicsPipeline<'a>> {
+	// Every attribute has its own binding
+	let binding_descriptions = key
+		.vertex_layout
+		.iter()
+		.enumerate()
+		.map(|(binding, &(_, stride))| {
+			vk::VertexInputBindingDescription::builder()
+				.binding(binding as _)
+				.stride(stride)
+				.input_rate(vk::VertexInputRate::VERTEX)
+				.build()
+		})
+		.collect::<Vec<_>>();
+
+	let attribute_descriptions = key
+		.vertex_layout
+		.iter()
+		.zip(Primitive::LOCATIONS)
+		.enumerate()
+		.map(|(binding, (&(format, _), location))| {
+			vk::VertexInputAttributeDescription::builder()
+				.binding(binding as _)
+				.location(location)
+				.format(format)
+				.offset(0)
+				.build()
+		})
+		.collect::<Vec<_>>();
+
+	let vertex_input_state_info =
+		vk::PipelineVertexInputStateCreateInfo::builder()
+			.vertex_binding_descriptions(&binding_descriptions)
+			.vertex_attribute_descriptions(&attribute_descriptions);
+
+	let input_assembly_state_info =
+		vk::PipelineInputAssemblyStateCreateInfo::builder()
+			.topology(key.topology);
+
+	let rasterization_state_info = vk::PipelineRasterizationStateCreateInfo {
+		cull_mode: key.cull_mode,
+		front_face: key.front_face,
+		..unsafe { *base_pipeline_create_info.p_rasterization_state }
+	};
+
+	let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+		p_vertex_input_state: &*vertex_input_state_info,
+		p_input_assembly_state: &*input_assembly_state_info,
+		p_rasterization_state: &rasterization_state_info,
+		..*base_pipeline_create_info
+	};
+
+	if !key.blend {
+		return GraphicsPipeline::new(
+			device,
+			pipeline_cache,
+			&pipeline_create_info,
+		);
+	}
+
+	// Blended primitives are composited over opaque ones, they are tested
+	// against depth but don't write it
+	let blend_attachment = vk::PipelineColorBlendAttachmentState::builder()
+		.color_write_mask(
+			vk::ColorComponentFlags::R
+				| vk::ColorComponentFlags::G
+				| vk::ColorComponentFlags::B
+				| vk::ColorComponentFlags::A,
+		)
+		.blend_enable(true)
+		.src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+		.dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+		.color_blend_op(vk::BlendOp::ADD)
+		.src_alpha_blend_factor(vk::BlendFactor::ONE)
+		.dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+		.alpha_blend_op(vk::BlendOp::ADD)
+		.build();
+
+	let blend_state_info = vk::PipelineColorBlendStateCreateInfo::builder()
+		.attachments(slice_from_ref(&blend_attachment))
+		.build();
+
+	let blend_depth_stencil_state_info =
+		vk::PipelineDepthStencilStateCreateInfo {
+			depth_write_enable: vk::FALSE,
+			..unsafe { *base_pipeline_create_info.p_depth_stencil_state }
+		};
+
+	let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+		p_color_blend_state: &blend_state_info,
+		p_depth_stencil_state: &blend_depth_stencil_state_info,
+		..pipeline_create_info
+	};
+
+	GraphicsPipeline::new(device, pipeline_cache, &pipeline_create_info)
+}
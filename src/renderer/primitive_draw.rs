@@ -0,0 +1,356 @@
+use super::morph_targets::MorphTargetRange;
+use super::pipelines::{PipelineKey, PipelineVariants};
+use crate::gltf_wrapper::{AlphaMode, Material, Primitive};
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{
+	Allocator, Buffer, CommandBuffer, CommandPool, Device, Fence, Queue,
+};
+use anyhow::Result;
+use ash::vk;
+
+const DEFAULT_VERTEX_ATTRIBUTE_FORMAT: vk::Format =
+	vk::Format::R32G32B32_SFLOAT;
+// Joint indices are integers, shader input type has to match
+const DEFAULT_JOINTS_ATTRIBUTE_FORMAT: vk::Format =
+	vk::Format::R32G32B32A32_UINT;
+const DEFAULT_VERTEX_ATTRIBUTE_SIZE: usize = 4 * std::mem::size_of::<f32>();
+
+// Vulkan buffers can't be empty, so empty data has no buffer and null
+// handle. Only primitives without vertices refer to it, those aren't drawn
+pub struct VertexDataBuffers<'a> {
+	_buffers: Vec<Option<Buffer<'a>>>,
+	handles: Vec<vk::Buffer>,
+}
+
+impl<'a> VertexDataBuffers<'a> {
+	pub fn new(buffers: Vec<Option<Buffer<'a>>>) -> Self {
+		let handles = buffers
+			.iter()
+			.map(|buffer| {
+				buffer.as_ref().map_or(vk::Buffer::null(), Buffer::handle)
+			})
+			.collect();
+
+		Self {
+			_buffers: buffers,
+			handles,
+		}
+	}
+
+	pub fn handles(&self) -> &[vk::Buffer] {
+		self.handles.as_slice()
+	}
+}
+
+// Pipelines are indices into scene renderer pipeline variants
+pub struct PrimitiveDraw {
+	pipeline: usize,
+	mirrored_pipeline: Option<usize>,
+	shadow_pipeline: usize,
+	vertex_buffers: Vec<vk::Buffer>,
+	vertex_buffer_offsets: Vec<vk::DeviceSize>,
+	index_buffer: Option<(vk::Buffer, vk::DeviceSize, vk::IndexType)>,
+	count: u32,
+	material_descriptor_set: vk::DescriptorSet,
+	morph_targets: MorphTargetRange,
+	alpha_mode: AlphaMode,
+	// Local bounds center, blended primitives are sorted by its depth
+	center: glm::Vec3,
+}
+
+impl PrimitiveDraw {
+	// Mirrored variant is made only for meshes of nodes which can get
+	// negative determinant, see mirrorable_meshes
+	#[allow(clippy::too_many_arguments)]
+	pub fn new<'a>(
+		primitive: &Primitive,
+		material: &Material,
+		material_descriptor_set: vk::DescriptorSet,
+		pipelines: &mut PipelineVariants<'a>,
+		base_pipeline_create_info: &vk::GraphicsPipelineCreateInfo,
+		shadow_pipelines: &mut PipelineVariants<'a>,
+		shadow_pipeline_create_info: &vk::GraphicsPipelineCreateInfo,
+		vertex_buffers: &VertexDataBuffers,
+		default_vertex_buffer: &Buffer,
+		morph_targets: MorphTargetRange,
+		mirrorable: bool,
+	) -> Result<Self> {
+		let mut vertex_layout = Vec::new();
+		let mut vertex_buffer_handles = Vec::new();
+		let mut vertex_buffer_offsets = Vec::new();
+
+		let default_buffer = default_vertex_buffer.handle();
+
+		for location in Primitive::LOCATIONS {
+			let default_format = if location == Primitive::JOINTS_0_LOCATION {
+				DEFAULT_JOINTS_ATTRIBUTE_FORMAT
+			} else {
+				DEFAULT_VERTEX_ATTRIBUTE_FORMAT
+			};
+
+			// Missing attributes are sourced from zeroed buffer with zero stride
+			let (format, stride, buffer, offset) =
+				match primitive.attribute(location) {
+					Some(attribute) => (
+						attribute.format,
+						attribute.stride,
+						vertex_buffers.handles()[attribute.buffer],
+						attribute.offset,
+					),
+					None => (default_format, 0, default_buffer, 0),
+				};
+
+			vertex_layout.push((format, stride as u32));
+			vertex_buffer_handles.push(buffer);
+			vertex_buffer_offsets.push(offset as vk::DeviceSize);
+		}
+
+		// Back faces of double sided materials are lit with flipped normals
+		let cull_mode = if material.double_sided() {
+			vk::CullModeFlags::NONE
+		} else {
+			vk::CullModeFlags::BACK
+		};
+
+		let alpha_mode = material.alpha_mode();
+
+		let key = PipelineKey {
+			topology: primitive.topology(),
+			vertex_layout,
+			cull_mode,
+			front_face: vk::FrontFace::COUNTER_CLOCKWISE,
+			blend: alpha_mode == AlphaMode::Blend,
+		};
+
+		let pipeline =
+			pipelines.get_or_create(&key, base_pipeline_create_info)?;
+
+		let mirrored_pipeline = if mirrorable {
+			let mirrored_key = PipelineKey {
+				front_face: vk::FrontFace::CLOCKWISE,
+				..key.clone()
+			};
+
+			Some(
+				pipelines
+					.get_or_create(&mirrored_key, base_pipeline_create_info)?,
+			)
+		} else {
+			None
+		};
+
+		// Depth only variant shares vertex input, shader skips shading inputs.
+		// Both faces are drawn without blending, so the rest of key doesn't
+		// matter
+		let shadow_key = PipelineKey {
+			cull_mode: vk::CullModeFlags::NONE,
+			blend: false,
+			..key
+		};
+
+		let shadow_pipeline = shadow_pipelines
+			.get_or_create(&shadow_key, shadow_pipeline_create_info)?;
+
+		let index_buffer = primitive.indices().map(|indices| {
+			(
+				vertex_buffers.handles()[indices.buffer],
+				indices.offset as vk::DeviceSize,
+				indices.index_type,
+			)
+		});
+
+		let count = match primitive.indices() {
+			Some(indices) => indices.count,
+			None => primitive.vertex_count(),
+		};
+
+		let primitive_draw = Self {
+			pipeline,
+			mirrored_pipeline,
+			shadow_pipeline,
+			vertex_buffers: vertex_buffer_handles,
+			vertex_buffer_offsets,
+			index_buffer,
+			count,
+			material_descriptor_set,
+			morph_targets,
+			alpha_mode,
+			center: primitive.bounds().center(),
+		};
+
+		Ok(primitive_draw)
+	}
+
+	pub fn morph_targets(&self) -> MorphTargetRange {
+		self.morph_targets
+	}
+
+	pub fn alpha_mode(&self) -> AlphaMode {
+		self.alpha_mode
+	}
+
+	pub fn center(&self) -> &glm::Vec3 {
+		&self.center
+	}
+
+	// Mirrored nodes have clockwise front faces
+	pub fn record(
+		&self,
+		command_buffer: &CommandBuffer,
+		pipelines: &PipelineVariants,
+		pipeline_layout: vk::PipelineLayout,
+		mirrored: bool,
+	) {
+		let material_descriptor_set = 1;
+
+		let pipeline = match self.mirrored_pipeline {
+			Some(mirrored_pipeline) if mirrored => mirrored_pipeline,
+			_ => self.pipeline,
+		};
+
+		command_buffer.bind_pipeline(pipelines.pipeline(pipeline));
+		command_buffer.bind_descriptor_sets(
+			vk::PipelineBindPoint::GRAPHICS,
+			pipeline_layout,
+			material_descriptor_set,
+			slice_from_ref(&self.material_descriptor_set),
+			&[],
+		);
+		self.record_geometry(command_buffer);
+	}
+
+	// Expects shadow pass push constants
+	pub fn record_shadow(
+		&self,
+		command_buffer: &CommandBuffer,
+		shadow_pipelines: &PipelineVariants,
+	) {
+		command_buffer
+			.bind_pipeline(shadow_pipelines.pipeline(self.shadow_pipeline));
+		self.record_geometry(command_buffer);
+	}
+
+	fn record_geometry(&self, command_buffer: &CommandBuffer) {
+		if self.count == 0 {
+			return;
+		}
+
+		command_buffer.bind_vertex_buffers(
+			&self.vertex_buffers,
+			&self.vertex_buffer_offsets,
+		);
+
+		match self.index_buffer {
+			Some((buffer, offset, index_type)) => {
+				command_buffer.bind_index_buffer(buffer, offset, index_type);
+				command_buffer.draw_indexed(self.count, 1, 0, 0, 0);
+			}
+			None => command_buffer.draw(self.count, 1, 0, 0),
+		}
+	}
+}
+
+pub fn create_default_vertex_buffer<'a>(
+	device: &'a Device,
+	allocator: &'a Allocator,
+) -> Result<Buffer<'a>> {
+	let buffer_create_info = vk::BufferCreateInfo::builder()
+		.size(DEFAULT_VERTEX_ATTRIBUTE_SIZE as _)
+		.usage(vk::BufferUsageFlags::VERTEX_BUFFER)
+		.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+	let mut buffer = Buffer::new(
+		device,
+		allocator,
+		&buffer_create_info,
+		gpu_allocator::MemoryLocation::CpuToGpu,
+		"default vertex buffer",
+	)?;
+
+	buffer.mapped_slice_mut()?.fill(0);
+	buffer.flush()?;
+
+	Ok(buffer)
+}
+
+pub fn create_vertex_buffers<'a>(
+	buffers: &[Vec<u8>],
+	command_pool: &'a CommandPool,
+	allocator: &'a Allocator,
+	queue: &Queue,
+) -> Result<VertexDataBuffers<'a>> {
+	let transfer_command_buffer = command_pool
+		.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?;
+
+	let device = command_pool.device();
+
+	// buffers
+	let staging_buffers = buffers
+		.iter()
+		.map(|buffer| {
+			if buffer.is_empty() {
+				return Ok(None);
+			}
+
+			let buffer_create_info = vk::BufferCreateInfo::builder()
+				.size(buffer.len() as _)
+				.usage(vk::BufferUsageFlags::TRANSFER_SRC)
+				.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+			let mut staging_buffer = Buffer::new(
+				device,
+				allocator,
+				&buffer_create_info,
+				gpu_allocator::MemoryLocation::CpuToGpu,
+				"staging buffer",
+			)?;
+
+			staging_buffer.mapped_slice_mut()?.copy_from_slice(buffer);
+			staging_buffer.flush()?;
+
+			Ok(Some(staging_buffer))
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	let begin_info = vk::CommandBufferBeginInfo::builder();
+	transfer_command_buffer.begin(&begin_info)?;
+	let vertex_buffers = staging_buffers
+		.iter()
+		.map(|stagin_buffer| {
+			let stagin_buffer = match stagin_buffer {
+				Some(stagin_buffer) => stagin_buffer,
+				None => return Ok(None),
+			};
+
+			let buffer_create_info = vk::BufferCreateInfo::builder()
+				.size(stagin_buffer.size() as _)
+				.usage(
+					vk::BufferUsageFlags::INDEX_BUFFER
+						| vk::BufferUsageFlags::VERTEX_BUFFER
+						| vk::BufferUsageFlags::TRANSFER_DST,
+				)
+				.sharing_mode(vk::SharingMode::EXCLUSIVE)
+				.build();
+
+			let vertex_buffer = Buffer::new(
+				device,
+				allocator,
+				&buffer_create_info,
+				gpu_allocator::MemoryLocation::CpuToGpu,
+				"vertex buffer",
+			)?;
+
+			transfer_command_buffer.copy_buffer(stagin_buffer, &vertex_buffer);
+
+			Ok(Some(vertex_buffer))
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	transfer_command_buffer.end()?;
+	let transfer_fence = Fence::new(device, false)?;
+	queue.submit(&transfer_command_buffer, &[], &[], &[], &transfer_fence)?;
+	transfer_fence.wait_max_timeout()?;
+
+	let vertex_buffers = VertexDataBuffers::new(vertex_buffers);
+	Ok(vertex_buffers)
+}
@@ -0,0 +1,90 @@
+use crate::vulkan_wrapper::{
+	Allocator, Device, FrameBuffer, Image, ImageView, RenderPass,
+};
+use anyhow::Result;
+use ash::vk;
+
+pub struct RenderTargets<'a> {
+	framebuffers: Vec<FrameBuffer<'a>>,
+	_depth_image_view: ImageView<'a>,
+	_depth_image: Image<'a>,
+}
+
+impl<'a> RenderTargets<'a> {
+	// Creates framebuffer per color attachment sharing single depth image
+	pub fn new(
+		device: &'a Device,
+		allocator: &'a Allocator,
+		render_pass: &RenderPass,
+		depth_format: vk::Format,
+		color_image_views: &[vk::ImageView],
+		extent: vk::Extent2D,
+	) -> Result<Self> {
+		let depth_format_tiling = vk::ImageTiling::OPTIMAL;
+
+		let depth_extent = vk::Extent3D::builder()
+			.width(extent.width)
+			.height(extent.height)
+			.depth(1)
+			.build();
+
+		let depth_image_info = vk::ImageCreateInfo::builder()
+			.image_type(vk::ImageType::TYPE_2D)
+			.extent(depth_extent)
+			.mip_levels(1)
+			.array_layers(1)
+			.format(depth_format)
+			.tiling(depth_format_tiling)
+			.initial_layout(vk::ImageLayout::UNDEFINED)
+			.usage(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT)
+			.samples(vk::SampleCountFlags::TYPE_1)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let depth_image =
+			Image::new(device, allocator, &depth_image_info, "depth")?;
+
+		let depth_image_subresource_range =
+			vk::ImageSubresourceRange::builder()
+				.level_count(1)
+				.layer_count(1)
+				.aspect_mask(vk::ImageAspectFlags::DEPTH)
+				.build();
+
+		let depth_image_view_info = vk::ImageViewCreateInfo::builder()
+			.image(depth_image.handle())
+			.view_type(vk::ImageViewType::TYPE_2D)
+			.format(depth_format)
+			.subresource_range(depth_image_subresource_range);
+
+		let depth_image_view = ImageView::new(device, &depth_image_view_info)?;
+
+		let framebuffers = color_image_views
+			.iter()
+			.map(|&image_view| {
+				let attachments = [image_view, depth_image_view.handle()];
+
+				let frame_buffer_create_info =
+					vk::FramebufferCreateInfo::builder()
+						.render_pass(render_pass.handle())
+						.attachments(&attachments)
+						.width(extent.width)
+						.height(extent.height)
+						.layers(1);
+
+				FrameBuffer::new(device, &frame_buffer_create_info)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let render_targets = Self {
+			framebuffers,
+			_depth_image_view: depth_image_view,
+			_depth_image: depth_image,
+		};
+
+		Ok(render_targets)
+	}
+
+	pub fn framebuffer(&self, index: usize) -> &FrameBuffer<'a> {
+		&self.framebuffers[index]
+	}
+}
@@ -0,0 +1,1168 @@
+use super::environment::Environment;
+use super::gen_shader_path;
+use super::lights::{scene_lights, shadow_caster_count, LightData};
+use super::materials::MaterialDescriptors;
+use super::mirroring::{mirrorable_meshes, mirrored, skin_roots};
+use super::morph_targets::{
+	morph_deltas, morph_weights, weight_count, weight_offsets,
+};
+use super::pipelines::PipelineVariants;
+use super::primitive_draw::{
+	create_default_vertex_buffer, create_vertex_buffers, PrimitiveDraw,
+	VertexDataBuffers,
+};
+use super::shadows::{ShadowMaps, ShadowQuality};
+use super::skinning::{joint_count, joint_matrices, joint_offsets};
+use super::textures::SceneTextures;
+use crate::gltf_wrapper::{AlphaMode, Node, Scene};
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{
+	Allocator, Buffer, CommandBuffer, CommandPool, DescriptorPool,
+	DescriptorSetLayout, Device, FrameBuffer, GraphicsPipeline, PipelineCache,
+	PipelineLayout, Queue, RenderPass, ShaderModule,
+};
+use anyhow::{anyhow, Result};
+use ash::vk;
+
+const UNIFORM_BUFFER_VK_DESCRIPTOR_COUNT: usize = 64;
+const STORAGE_BUFFER_VK_DESCRIPTOR_COUNT: usize = 64;
+const SAMPLER_VK_DESCRIPTOR_COUNT: usize = 64;
+const DESCRIPTOR_SET_COUNT: usize = 512;
+const SHADOW_DEPTH_BIAS_CONSTANT: f32 = 1.25;
+const SHADOW_DEPTH_BIAS_SLOPE: f32 = 1.75;
+const SHADER_ENTRY_POINT: &std::ffi::CStr = cstr::cstr!("main");
+// Linear, color attachments are sRGB encoded. Gives 0.1 grey on screen
+const CLEAR_COLOR: [f32; 4] = [0.01, 0.01, 0.01, 1.0];
+
+// Mirrors ViewProjection constant buffer in geometry shaders
+#[repr(C)]
+struct ViewProjectionUBO {
+	mt: glm::TMat4<f32>,
+	inverse_mt: glm::TMat4<f32>,
+	camera_position: glm::Vec3,
+	light_count: u32,
+	shadow_filter_radius: u32,
+	_padding: [u32; 3],
+}
+
+// Normal matrix is derived in the shader, keeping push constants within
+// guaranteed 128 bytes
+#[repr(C)]
+struct ModelPushConstants {
+	model: glm::TMat4<f32>,
+	// Negative for nodes without skin
+	joint_offset: i32,
+	weight_offset: u32,
+	delta_offset: u32,
+	target_count: u32,
+	vertex_count: u32,
+}
+
+// Model transform composed with light view projection
+#[repr(C)]
+struct ShadowPushConstants {
+	mvp: glm::TMat4<f32>,
+	joint_offset: i32,
+	weight_offset: u32,
+	delta_offset: u32,
+	target_count: u32,
+	vertex_count: u32,
+}
+
+pub struct FrameUniforms<'a> {
+	view_projection_buffer: Buffer<'a>,
+	light_buffer: Buffer<'a>,
+	light_capacity: usize,
+	joint_buffer: Buffer<'a>,
+	weight_buffer: Buffer<'a>,
+	shadow_quality: ShadowQuality,
+	// Light view projections of rendered shadow maps in layer order
+	shadow_matrices: Vec<glm::Mat4>,
+	// Blended primitives are sorted by their depth in view space
+	view: glm::Mat4,
+	descriptor_set: vk::DescriptorSet,
+}
+
+impl<'a> FrameUniforms<'a> {
+	pub fn update(
+		&mut self,
+		view: &glm::Mat4,
+		projection: &glm::Mat4,
+		scene: &Scene,
+	) -> Result<()> {
+		let (mut lights, shadow_matrices) =
+			scene_lights(scene, view, self.shadow_quality.enabled());
+
+		if lights.len() > self.light_capacity {
+			log::warn!(
+				"Light count {} exceeds capacity {}",
+				lights.len(),
+				self.light_capacity
+			);
+
+			lights.truncate(self.light_capacity);
+		}
+
+		self.light_buffer.copy_slice_into(&lights)?;
+		self.light_buffer.flush()?;
+		self.shadow_matrices = shadow_matrices;
+		self.view = *view;
+
+		self.joint_buffer.copy_slice_into(&joint_matrices(scene))?;
+		self.joint_buffer.flush()?;
+
+		self.weight_buffer.copy_slice_into(&morph_weights(scene))?;
+		self.weight_buffer.flush()?;
+
+		let camera_transform = glm::inverse(view);
+		let view_projection = projection * view;
+
+		let view_projection = ViewProjectionUBO {
+			mt: view_projection,
+			inverse_mt: glm::inverse(&view_projection),
+			camera_position: camera_transform.column(3).xyz(),
+			light_count: lights.len() as _,
+			shadow_filter_radius: self.shadow_quality.filter_radius(),
+			_padding: [0; 3],
+		};
+
+		self.view_projection_buffer
+			.copy_into_n_flush(&view_projection)
+	}
+}
+
+// Draws scene meshes into a color and depth attachment pair
+pub struct SceneRenderer<'a> {
+	mesh_draws: Vec<Vec<PrimitiveDraw>>,
+	pipelines: PipelineVariants<'a>,
+	shadow_pipelines: PipelineVariants<'a>,
+	pipeline_layout: PipelineLayout<'a>,
+	descriptor_pool: DescriptorPool<'a>,
+	descriptor_set_layout: DescriptorSetLayout<'a>,
+	_material_descriptors: MaterialDescriptors<'a>,
+	_textures: SceneTextures<'a>,
+	skybox_pipeline: Option<GraphicsPipeline<'a>>,
+	environment: Environment<'a>,
+	shadow_pipeline_layout: PipelineLayout<'a>,
+	shadow_maps: ShadowMaps<'a>,
+	render_pass: RenderPass<'a>,
+	depth_format: vk::Format,
+	_vertex_buffers: VertexDataBuffers<'a>,
+	_default_vertex_buffer: Buffer<'a>,
+	morph_delta_buffer: Buffer<'a>,
+	light_capacity: usize,
+	joint_offsets: Vec<usize>,
+	skin_roots: Vec<usize>,
+	joint_capacity: usize,
+	weight_offsets: Vec<Option<usize>>,
+	weight_capacity: usize,
+	device: &'a Device<'a>,
+	allocator: &'a Allocator<'a>,
+}
+
+impl<'a> SceneRenderer<'a> {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		device: &'a Device,
+		allocator: &'a Allocator,
+		command_pool: &'a CommandPool,
+		queue: &Queue,
+		pipeline_cache: &'a PipelineCache,
+		scene: &Scene,
+		buffers: &[Vec<u8>],
+		color_format: vk::Format,
+		color_final_layout: vk::ImageLayout,
+		environment: Environment<'a>,
+		shadow_quality: ShadowQuality,
+	) -> Result<Self> {
+		let vertex_buffers =
+			create_vertex_buffers(buffers, command_pool, allocator, queue)?;
+
+		let default_vertex_buffer =
+			create_default_vertex_buffer(device, allocator)?;
+
+		let (morph_deltas, morph_target_ranges) = morph_deltas(scene);
+
+		// Storage buffer can't be empty, so there is one delta at least
+		let morph_delta_buffer_size =
+			std::mem::size_of::<glm::Vec4>() * morph_deltas.len().max(1);
+
+		let buffer_create_info = vk::BufferCreateInfo::builder()
+			.size(morph_delta_buffer_size as _)
+			.usage(vk::BufferUsageFlags::STORAGE_BUFFER)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let mut morph_delta_buffer = Buffer::new(
+			device,
+			allocator,
+			&buffer_create_info,
+			gpu_allocator::MemoryLocation::CpuToGpu,
+			"morph delta buffer",
+		)?;
+
+		morph_delta_buffer.copy_slice_into(&morph_deltas)?;
+		morph_delta_buffer.flush()?;
+
+		// vertex shader
+		let vertex_shader_module =
+			ShaderModule::new(device, &gen_shader_path("geometry.vert"))?;
+
+		// fragment shader
+		let frag_shader_module =
+			ShaderModule::new(device, &gen_shader_path("geometry.frag"))?;
+
+		let vertex_shader_state_create_info =
+			vk::PipelineShaderStageCreateInfo::builder()
+				.module(vertex_shader_module.handle())
+				.name(SHADER_ENTRY_POINT)
+				.stage(vk::ShaderStageFlags::VERTEX)
+				.build();
+
+		let frag_shader_state_create_info =
+			vk::PipelineShaderStageCreateInfo::builder()
+				.module(frag_shader_module.handle())
+				.name(SHADER_ENTRY_POINT)
+				.stage(vk::ShaderStageFlags::FRAGMENT)
+				.build();
+
+		let shader_stage_create_infos = [
+			vertex_shader_state_create_info,
+			frag_shader_state_create_info,
+		];
+
+		// viewport state, viewport and scissor are set dynamically to survive
+		// render targets recreation
+		let viewport_state_info =
+			vk::PipelineViewportStateCreateInfo::builder()
+				.viewport_count(1)
+				.scissor_count(1);
+
+		let dynamic_states =
+			[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+
+		let dynamic_state_info = vk::PipelineDynamicStateCreateInfo::builder()
+			.dynamic_states(&dynamic_states);
+
+		// multisample_state
+		let multisample_state_info =
+			vk::PipelineMultisampleStateCreateInfo::builder()
+				.rasterization_samples(vk::SampleCountFlags::TYPE_1);
+
+		// rasterization_info
+		let rasterization_info =
+			vk::PipelineRasterizationStateCreateInfo::builder()
+				.polygon_mode(vk::PolygonMode::FILL)
+				.cull_mode(vk::CullModeFlags::BACK)
+				.front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+				.line_width(1.0);
+
+		// color_blend_state
+		let color_blend_attachments =
+			[vk::PipelineColorBlendAttachmentState::builder()
+				.color_write_mask(
+					vk::ColorComponentFlags::R
+						| vk::ColorComponentFlags::G
+						| vk::ColorComponentFlags::B
+						| vk::ColorComponentFlags::A,
+				)
+				.blend_enable(false)
+				.build()];
+
+		let color_blend_state =
+			vk::PipelineColorBlendStateCreateInfo::builder()
+				.attachments(&color_blend_attachments);
+
+		// depth
+		let depth_format_candidates = [
+			vk::Format::D24_UNORM_S8_UINT,
+			vk::Format::D32_SFLOAT,
+			vk::Format::D32_SFLOAT_S8_UINT,
+		];
+
+		let depth_format_tiling = vk::ImageTiling::OPTIMAL;
+		let depth_format_features =
+			vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT;
+
+		let depth_format = device
+			.find_supported_format(
+				&depth_format_candidates,
+				depth_format_tiling,
+				depth_format_features,
+			)
+			.ok_or_else(|| anyhow!("Suitable depth format not found"))?;
+
+		// render pass
+		let attachment_descriptions = [
+			vk::AttachmentDescription::builder()
+				.format(color_format)
+				.samples(vk::SampleCountFlags::TYPE_1)
+				.load_op(vk::AttachmentLoadOp::CLEAR)
+				.store_op(vk::AttachmentStoreOp::STORE)
+				.stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+				.stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+				.initial_layout(vk::ImageLayout::UNDEFINED)
+				.final_layout(color_final_layout)
+				.build(),
+			vk::AttachmentDescription::builder()
+				.format(depth_format)
+				.samples(vk::SampleCountFlags::TYPE_1)
+				.load_op(vk::AttachmentLoadOp::CLEAR)
+				.store_op(vk::AttachmentStoreOp::DONT_CARE)
+				.stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+				.stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+				.initial_layout(vk::ImageLayout::UNDEFINED)
+				.final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+				.build(),
+		];
+
+		let attachment_references = [vk::AttachmentReference::builder()
+			.attachment(0)
+			.layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+			.build()];
+
+		let depth_attachment_reference = vk::AttachmentReference::builder()
+			.attachment(1)
+			.layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+			.build();
+
+		let subpass_descriptions = [vk::SubpassDescription::builder()
+			.pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+			.color_attachments(&attachment_references)
+			.depth_stencil_attachment(&depth_attachment_reference)
+			.build()];
+
+		let subpass_dependency = [vk::SubpassDependency::builder()
+			.src_subpass(vk::SUBPASS_EXTERNAL)
+			.dst_subpass(0)
+			.src_stage_mask(
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+					| vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+			)
+			.src_access_mask(vk::AccessFlags::empty())
+			.dst_stage_mask(
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+					| vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+			)
+			.dst_access_mask(
+				vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+					| vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+			)
+			.build()];
+
+		let render_pass_create_info = vk::RenderPassCreateInfo::builder()
+			.attachments(&attachment_descriptions)
+			.subpasses(&subpass_descriptions)
+			.dependencies(&subpass_dependency)
+			.build();
+
+		let render_pass = RenderPass::new(device, &render_pass_create_info)?;
+
+		// layout
+		let bindings = [
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(0)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+				.stage_flags(
+					vk::ShaderStageFlags::VERTEX
+						| vk::ShaderStageFlags::FRAGMENT,
+				)
+				.build(),
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(1)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.stage_flags(vk::ShaderStageFlags::FRAGMENT)
+				.build(),
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(2)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.stage_flags(vk::ShaderStageFlags::FRAGMENT)
+				.build(),
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(3)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.stage_flags(vk::ShaderStageFlags::VERTEX)
+				.build(),
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(4)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.stage_flags(vk::ShaderStageFlags::VERTEX)
+				.build(),
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(5)
+				.descriptor_count(1)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.stage_flags(vk::ShaderStageFlags::VERTEX)
+				.build(),
+		];
+
+		let descriptor_set_layout =
+			DescriptorSetLayout::new(device, &bindings)?;
+
+		let textures =
+			SceneTextures::new(device, allocator, command_pool, queue, scene)?;
+
+		let material_descriptors = MaterialDescriptors::new(
+			device,
+			allocator,
+			scene.materials(),
+			&textures,
+		)?;
+
+		// set 0 is per frame, set 1 is per material, set 2 is environment
+		let descriptor_set_layouts = [
+			descriptor_set_layout.handle(),
+			material_descriptors.descriptor_set_layout().handle(),
+			environment.descriptor_set_layout().handle(),
+		];
+
+		let push_constant_ranges = [vk::PushConstantRange::builder()
+			.stage_flags(vk::ShaderStageFlags::VERTEX)
+			.size(std::mem::size_of::<ModelPushConstants>() as _)
+			.build()];
+
+		let pipeline_layout_create_info =
+			vk::PipelineLayoutCreateInfo::builder()
+				.set_layouts(&descriptor_set_layouts)
+				.push_constant_ranges(&push_constant_ranges);
+
+		let pipeline_layout =
+			PipelineLayout::new(device, &pipeline_layout_create_info)?;
+
+		// descriptor pool
+		let pool_sizes = [
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::UNIFORM_BUFFER)
+				.descriptor_count(UNIFORM_BUFFER_VK_DESCRIPTOR_COUNT as _)
+				.build(),
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::STORAGE_BUFFER)
+				.descriptor_count(STORAGE_BUFFER_VK_DESCRIPTOR_COUNT as _)
+				.build(),
+			vk::DescriptorPoolSize::builder()
+				.ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.descriptor_count(SAMPLER_VK_DESCRIPTOR_COUNT as _)
+				.build(),
+		];
+
+		let descriptor_pool_create_info =
+			vk::DescriptorPoolCreateInfo::builder()
+				.pool_sizes(&pool_sizes)
+				.max_sets(DESCRIPTOR_SET_COUNT as _)
+				.build();
+
+		let descriptor_pool =
+			DescriptorPool::new(device, &descriptor_pool_create_info)?;
+
+		// depth stencil state
+		let depth_stencil_state_info =
+			vk::PipelineDepthStencilStateCreateInfo::builder()
+				.depth_test_enable(true)
+				.depth_write_enable(true)
+				.depth_compare_op(vk::CompareOp::LESS);
+
+		let graphics_pipeline_create_info =
+			vk::GraphicsPipelineCreateInfo::builder()
+				.stages(&shader_stage_create_infos)
+				.rasterization_state(&rasterization_info)
+				.color_blend_state(&color_blend_state)
+				.multisample_state(&multisample_state_info)
+				.viewport_state(&viewport_state_info)
+				.dynamic_state(&dynamic_state_info)
+				.render_pass(render_pass.handle())
+				.layout(pipeline_layout.handle())
+				.depth_stencil_state(&depth_stencil_state_info)
+				.build();
+
+		// shadows
+		let shadow_maps = ShadowMaps::new(
+			device,
+			allocator,
+			command_pool,
+			queue,
+			shadow_caster_count(scene),
+			shadow_quality,
+		)?;
+
+		let shadow_push_constant_ranges = [vk::PushConstantRange::builder()
+			.stage_flags(vk::ShaderStageFlags::VERTEX)
+			.size(std::mem::size_of::<ShadowPushConstants>() as _)
+			.build()];
+
+		// Per frame set provides joint matrices
+		let shadow_pipeline_layout_create_info =
+			vk::PipelineLayoutCreateInfo::builder()
+				.set_layouts(&descriptor_set_layouts[..1])
+				.push_constant_ranges(&shadow_push_constant_ranges);
+
+		let shadow_pipeline_layout =
+			PipelineLayout::new(device, &shadow_pipeline_layout_create_info)?;
+
+		let shadow_vertex_shader_module =
+			ShaderModule::new(device, &gen_shader_path("shadow.vert"))?;
+
+		let shadow_shader_stage_create_infos =
+			[vk::PipelineShaderStageCreateInfo::builder()
+				.module(shadow_vertex_shader_module.handle())
+				.name(SHADER_ENTRY_POINT)
+				.stage(vk::ShaderStageFlags::VERTEX)
+				.build()];
+
+		// Both faces are drawn, slope scaled bias keeps lit surfaces from
+		// shadowing themselves
+		let shadow_rasterization_info =
+			vk::PipelineRasterizationStateCreateInfo::builder()
+				.polygon_mode(vk::PolygonMode::FILL)
+				.cull_mode(vk::CullModeFlags::NONE)
+				.front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+				.depth_bias_enable(true)
+				.depth_bias_constant_factor(SHADOW_DEPTH_BIAS_CONSTANT)
+				.depth_bias_slope_factor(SHADOW_DEPTH_BIAS_SLOPE)
+				.line_width(1.0);
+
+		let shadow_depth_stencil_state_info =
+			vk::PipelineDepthStencilStateCreateInfo::builder()
+				.depth_test_enable(true)
+				.depth_write_enable(true)
+				.depth_compare_op(vk::CompareOp::LESS_OR_EQUAL);
+
+		let shadow_pipeline_create_info =
+			vk::GraphicsPipelineCreateInfo::builder()
+				.stages(&shadow_shader_stage_create_infos)
+				.rasterization_state(&shadow_rasterization_info)
+				.multisample_state(&multisample_state_info)
+				.viewport_state(&viewport_state_info)
+				.dynamic_state(&dynamic_state_info)
+				.render_pass(shadow_maps.render_pass().handle())
+				.layout(shadow_pipeline_layout.handle())
+				.depth_stencil_state(&shadow_depth_stencil_state_info)
+				.build();
+
+		let mut pipelines = PipelineVariants::new(device, pipeline_cache);
+		let mut shadow_pipelines =
+			PipelineVariants::new(device, pipeline_cache);
+
+		let skin_roots = skin_roots(scene);
+		let mirrorable_meshes = mirrorable_meshes(scene, &skin_roots);
+
+		let mesh_draws = scene
+			.meshes()
+			.iter()
+			.zip(&morph_target_ranges)
+			.zip(mirrorable_meshes)
+			.map(|((mesh, morph_target_ranges), mirrorable)| {
+				mesh.primitives()
+					.iter()
+					.zip(morph_target_ranges)
+					.map(|(primitive, &morph_targets)| {
+						let material = primitive.material();
+
+						PrimitiveDraw::new(
+							primitive,
+							scene.material(material),
+							material_descriptors.descriptor_set(material),
+							&mut pipelines,
+							&graphics_pipeline_create_info,
+							&mut shadow_pipelines,
+							&shadow_pipeline_create_info,
+							&vertex_buffers,
+							&default_vertex_buffer,
+							morph_targets,
+							mirrorable,
+						)
+					})
+					.collect::<Result<Vec<_>>>()
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let skybox_pipeline = if environment.skybox() {
+			Some(create_skybox_pipeline(
+				device,
+				pipeline_cache,
+				&graphics_pipeline_create_info,
+			)?)
+		} else {
+			None
+		};
+
+		let scene_renderer = Self {
+			mesh_draws,
+			pipelines,
+			shadow_pipelines,
+			pipeline_layout,
+			descriptor_pool,
+			descriptor_set_layout,
+			_material_descriptors: material_descriptors,
+			_textures: textures,
+			skybox_pipeline,
+			environment,
+			shadow_pipeline_layout,
+			shadow_maps,
+			render_pass,
+			depth_format,
+			_vertex_buffers: vertex_buffers,
+			_default_vertex_buffer: default_vertex_buffer,
+			morph_delta_buffer,
+			// Light nodes don't change, so the headlight needs one slot at most
+			light_capacity: scene.light_nodes().len().max(1),
+			joint_offsets: joint_offsets(scene),
+			skin_roots,
+			joint_capacity: joint_count(scene).max(1),
+			weight_offsets: weight_offsets(scene),
+			weight_capacity: weight_count(scene).max(1),
+			device,
+			allocator,
+		};
+
+		Ok(scene_renderer)
+	}
+
+	pub fn render_pass(&self) -> &RenderPass<'a> {
+		&self.render_pass
+	}
+
+	pub fn depth_format(&self) -> vk::Format {
+		self.depth_format
+	}
+
+	pub fn shadow_quality(&self) -> ShadowQuality {
+		self.shadow_maps.quality()
+	}
+
+	// Recreates shadow maps, frame uniforms have to be rebound to them
+	// afterwards. Device is expected to be idle. Shadow pipelines are kept,
+	// the new render pass is compatible with the old one
+	pub fn set_shadow_quality(
+		&mut self,
+		command_pool: &CommandPool,
+		queue: &Queue,
+		scene: &Scene,
+		shadow_quality: ShadowQuality,
+	) -> Result<()> {
+		self.shadow_maps = ShadowMaps::new(
+			self.device,
+			self.allocator,
+			command_pool,
+			queue,
+			shadow_caster_count(scene),
+			shadow_quality,
+		)?;
+
+		Ok(())
+	}
+
+	// Points frame descriptor set at current shadow maps
+	pub fn rebind_shadow_maps(&self, frame_uniforms: &mut FrameUniforms) {
+		let shadow_maps_info = self.shadow_maps.image_info();
+
+		let descriptor_write_set = vk::WriteDescriptorSet::builder()
+			.dst_set(frame_uniforms.descriptor_set)
+			.dst_binding(2)
+			.dst_array_element(0)
+			.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+			.image_info(slice_from_ref(&shadow_maps_info))
+			.build();
+
+		self.device
+			.update_descriptor_sets(slice_from_ref(&descriptor_write_set), &[]);
+
+		frame_uniforms.shadow_quality = self.shadow_maps.quality();
+	}
+
+	pub fn create_frame_uniforms(&self) -> Result<FrameUniforms<'a>> {
+		let view_projection_buffer_size =
+			std::mem::size_of::<ViewProjectionUBO>();
+
+		let buffer_create_info = vk::BufferCreateInfo::builder()
+			.size(view_projection_buffer_size as _)
+			.usage(vk::BufferUsageFlags::UNIFORM_BUFFER)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let view_projection_buffer = Buffer::new(
+			self.device,
+			self.allocator,
+			&buffer_create_info,
+			gpu_allocator::MemoryLocation::CpuToGpu,
+			"view projection buffer",
+		)?;
+
+		let light_buffer_size =
+			std::mem::size_of::<LightData>() * self.light_capacity;
+
+		let buffer_create_info = vk::BufferCreateInfo::builder()
+			.size(light_buffer_size as _)
+			.usage(vk::BufferUsageFlags::STORAGE_BUFFER)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let light_buffer = Buffer::new(
+			self.device,
+			self.allocator,
+			&buffer_create_info,
+			gpu_allocator::MemoryLocation::CpuToGpu,
+			"light buffer",
+		)?;
+
+		let joint_buffer_size =
+			std::mem::size_of::<glm::Mat4>() * self.joint_capacity;
+
+		let buffer_create_info = vk::BufferCreateInfo::builder()
+			.size(joint_buffer_size as _)
+			.usage(vk::BufferUsageFlags::STORAGE_BUFFER)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let joint_buffer = Buffer::new(
+			self.device,
+			self.allocator,
+			&buffer_create_info,
+			gpu_allocator::MemoryLocation::CpuToGpu,
+			"joint buffer",
+		)?;
+
+		let weight_buffer_size =
+			std::mem::size_of::<f32>() * self.weight_capacity;
+
+		let buffer_create_info = vk::BufferCreateInfo::builder()
+			.size(weight_buffer_size as _)
+			.usage(vk::BufferUsageFlags::STORAGE_BUFFER)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let weight_buffer = Buffer::new(
+			self.device,
+			self.allocator,
+			&buffer_create_info,
+			gpu_allocator::MemoryLocation::CpuToGpu,
+			"morph weight buffer",
+		)?;
+
+		let descriptor_set_layouts = [self.descriptor_set_layout.handle()];
+		let descriptor_set = self
+			.descriptor_pool
+			.allocate_descriptor_sets(&descriptor_set_layouts)?[0];
+
+		let descriptor_buffer_info = vk::DescriptorBufferInfo::builder()
+			.buffer(view_projection_buffer.handle())
+			.range(view_projection_buffer_size as _)
+			.build();
+
+		let light_buffer_info = vk::DescriptorBufferInfo::builder()
+			.buffer(light_buffer.handle())
+			.range(light_buffer_size as _)
+			.build();
+
+		let joint_buffer_info = vk::DescriptorBufferInfo::builder()
+			.buffer(joint_buffer.handle())
+			.range(joint_buffer_size as _)
+			.build();
+
+		// Deltas are shared by all frames
+		let morph_delta_buffer_info = vk::DescriptorBufferInfo::builder()
+			.buffer(self.morph_delta_buffer.handle())
+			.range(vk::WHOLE_SIZE)
+			.build();
+
+		let weight_buffer_info = vk::DescriptorBufferInfo::builder()
+			.buffer(weight_buffer.handle())
+			.range(weight_buffer_size as _)
+			.build();
+
+		let shadow_maps_info = self.shadow_maps.image_info();
+
+		let descriptor_write_sets = [
+			vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(0)
+				.dst_array_element(0)
+				.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+				.buffer_info(slice_from_ref(&descriptor_buffer_info))
+				.build(),
+			vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(1)
+				.dst_array_element(0)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.buffer_info(slice_from_ref(&light_buffer_info))
+				.build(),
+			vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(2)
+				.dst_array_element(0)
+				.descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+				.image_info(slice_from_ref(&shadow_maps_info))
+				.build(),
+			vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(3)
+				.dst_array_element(0)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.buffer_info(slice_from_ref(&joint_buffer_info))
+				.build(),
+			vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(4)
+				.dst_array_element(0)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.buffer_info(slice_from_ref(&morph_delta_buffer_info))
+				.build(),
+			vk::WriteDescriptorSet::builder()
+				.dst_set(descriptor_set)
+				.dst_binding(5)
+				.dst_array_element(0)
+				.descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+				.buffer_info(slice_from_ref(&weight_buffer_info))
+				.build(),
+		];
+
+		self.device
+			.update_descriptor_sets(&descriptor_write_sets, &[]);
+
+		let frame_uniforms = FrameUniforms {
+			view_projection_buffer,
+			light_buffer,
+			light_capacity: self.light_capacity,
+			joint_buffer,
+			weight_buffer,
+			shadow_quality: self.shadow_maps.quality(),
+			shadow_matrices: Vec::new(),
+			view: glm::Mat4::identity(),
+			descriptor_set,
+		};
+
+		Ok(frame_uniforms)
+	}
+
+	// Records shadow passes followed by whole render pass, viewport area is
+	// drawn over cleared framebuffer
+	pub fn record(
+		&self,
+		command_buffer: &CommandBuffer,
+		frame_buffer: &FrameBuffer,
+		viewport_area: vk::Rect2D,
+		frame_uniforms: &FrameUniforms,
+		scene: &Scene,
+	) {
+		self.record_shadow_passes(command_buffer, frame_uniforms, scene);
+
+		let render_area = ash::vk::Rect2D::builder()
+			.extent(frame_buffer.extent())
+			.build();
+
+		let clear_color_value = vk::ClearColorValue {
+			float32: CLEAR_COLOR,
+		};
+
+		let clear_color_value = vk::ClearValue {
+			color: clear_color_value,
+		};
+
+		let clear_depth_stencil_value = vk::ClearDepthStencilValue {
+			depth: 1.0,
+			stencil: 0,
+		};
+
+		let clear_depth_stencil_value = vk::ClearValue {
+			depth_stencil: clear_depth_stencil_value,
+		};
+
+		let clear_values = [clear_color_value, clear_depth_stencil_value];
+
+		let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
+			.clear_values(&clear_values)
+			.render_pass(self.render_pass.handle())
+			.framebuffer(frame_buffer.handle())
+			.render_area(render_area);
+
+		command_buffer.begin_render_pass(
+			&render_pass_begin_info,
+			vk::SubpassContents::INLINE,
+		);
+
+		let viewport_offset = viewport_area.offset;
+		let viewport_extent = viewport_area.extent;
+
+		// Flipped viewport to keep Y axis up
+		let viewport = vk::Viewport::builder()
+			.x(viewport_offset.x as _)
+			.y((viewport_offset.y + viewport_extent.height as i32) as _)
+			.width(viewport_extent.width as _)
+			.height(-(viewport_extent.height as f32))
+			.max_depth(1.0)
+			.build();
+
+		command_buffer.set_viewport(slice_from_ref(&viewport));
+		command_buffer.set_scissor(slice_from_ref(&viewport_area));
+
+		let first_descriptor_set = 0;
+		command_buffer.bind_descriptor_sets(
+			vk::PipelineBindPoint::GRAPHICS,
+			self.pipeline_layout.handle(),
+			first_descriptor_set as _,
+			slice_from_ref(&frame_uniforms.descriptor_set),
+			&[],
+		);
+
+		let environment_descriptor_set = 2;
+		command_buffer.bind_descriptor_sets(
+			vk::PipelineBindPoint::GRAPHICS,
+			self.pipeline_layout.handle(),
+			environment_descriptor_set,
+			slice_from_ref(&self.environment.descriptor_set()),
+			&[],
+		);
+
+		let mut blend_draws = Vec::new();
+
+		for (index, node) in scene.nodes().iter().enumerate() {
+			let mesh = match node.mesh() {
+				Some(mesh) => mesh,
+				None => continue,
+			};
+
+			for primitive_draw in &self.mesh_draws[mesh] {
+				if primitive_draw.alpha_mode() != AlphaMode::Blend {
+					self.record_primitive_draw(
+						command_buffer,
+						scene,
+						index,
+						node,
+						primitive_draw,
+					);
+
+					continue;
+				}
+
+				// Skinned primitives are sorted by their node as well
+				let center =
+					node.world_transform() * primitive_draw.center().push(1.0);
+				let depth = (frame_uniforms.view * center).z;
+
+				blend_draws.push((depth, index, node, primitive_draw));
+			}
+		}
+
+		// Skybox fills only pixels left at cleared depth
+		if let Some(skybox_pipeline) = &self.skybox_pipeline {
+			command_buffer.bind_pipeline(skybox_pipeline);
+			command_buffer.draw(3, 1, 0, 0);
+		}
+
+		// Blended primitives go over everything else, back to front. View
+		// looks down negative z, so the farthest ones come first
+		blend_draws.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+
+		for (_, index, node, primitive_draw) in blend_draws {
+			self.record_primitive_draw(
+				command_buffer,
+				scene,
+				index,
+				node,
+				primitive_draw,
+			);
+		}
+
+		command_buffer.end_render_pass();
+	}
+
+	// Morph target ranges differ between primitives of a mesh, so model
+	// push constants are set for each of them
+	fn record_primitive_draw(
+		&self,
+		command_buffer: &CommandBuffer,
+		scene: &Scene,
+		index: usize,
+		node: &Node,
+		primitive_draw: &PrimitiveDraw,
+	) {
+		let push_constants =
+			self.model_push_constants(index, node, primitive_draw);
+
+		command_buffer.push_constants(
+			self.pipeline_layout.handle(),
+			vk::ShaderStageFlags::VERTEX,
+			0,
+			&push_constants,
+		);
+
+		// Model of skinned nodes is identity, so determinant comes from
+		// their placement
+		primitive_draw.record(
+			command_buffer,
+			&self.pipelines,
+			self.pipeline_layout.handle(),
+			mirrored(scene, index, &self.skin_roots),
+		);
+	}
+
+	// Skinned nodes are placed by their joints alone
+	fn model_push_constants(
+		&self,
+		index: usize,
+		node: &Node,
+		primitive_draw: &PrimitiveDraw,
+	) -> ModelPushConstants {
+		let (model, joint_offset) = match node.skin() {
+			Some(skin) => {
+				(glm::Mat4::identity(), self.joint_offsets[skin] as _)
+			}
+			None => (*node.world_transform(), -1),
+		};
+
+		// Primitives without targets of morphed meshes are left undisplaced
+		let morph_targets = primitive_draw.morph_targets();
+		let weight_offset = self.weight_offsets[index].unwrap_or_default();
+
+		ModelPushConstants {
+			model,
+			joint_offset,
+			weight_offset: weight_offset as _,
+			delta_offset: morph_targets.delta_offset,
+			target_count: morph_targets.target_count,
+			vertex_count: morph_targets.vertex_count,
+		}
+	}
+
+	fn record_shadow_passes(
+		&self,
+		command_buffer: &CommandBuffer,
+		frame_uniforms: &FrameUniforms,
+		scene: &Scene,
+	) {
+		let clear_depth_stencil_value = vk::ClearValue {
+			depth_stencil: vk::ClearDepthStencilValue {
+				depth: 1.0,
+				stencil: 0,
+			},
+		};
+
+		command_buffer.bind_descriptor_sets(
+			vk::PipelineBindPoint::GRAPHICS,
+			self.shadow_pipeline_layout.handle(),
+			0,
+			slice_from_ref(&frame_uniforms.descriptor_set),
+			&[],
+		);
+
+		for (layer, shadow_matrix) in
+			frame_uniforms.shadow_matrices.iter().enumerate()
+		{
+			let frame_buffer = self.shadow_maps.framebuffer(layer);
+
+			let render_area =
+				vk::Rect2D::builder().extent(frame_buffer.extent()).build();
+
+			let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
+				.clear_values(slice_from_ref(&clear_depth_stencil_value))
+				.render_pass(self.shadow_maps.render_pass().handle())
+				.framebuffer(frame_buffer.handle())
+				.render_area(render_area);
+
+			command_buffer.begin_render_pass(
+				&render_pass_begin_info,
+				vk::SubpassContents::INLINE,
+			);
+
+			// Shadow matrices follow Vulkan clip space, so no flip here
+			let viewport = vk::Viewport::builder()
+				.width(render_area.extent.width as _)
+				.height(render_area.extent.height as _)
+				.max_depth(1.0)
+				.build();
+
+			command_buffer.set_viewport(slice_from_ref(&viewport));
+			command_buffer.set_scissor(slice_from_ref(&render_area));
+
+			for (index, node) in scene.nodes().iter().enumerate() {
+				let mesh = match node.mesh() {
+					Some(mesh) => mesh,
+					None => continue,
+				};
+
+				for primitive_draw in &self.mesh_draws[mesh] {
+					let model =
+						self.model_push_constants(index, node, primitive_draw);
+
+					let push_constants = ShadowPushConstants {
+						mvp: shadow_matrix * model.model,
+						joint_offset: model.joint_offset,
+						weight_offset: model.weight_offset,
+						delta_offset: model.delta_offset,
+						target_count: model.target_count,
+						vertex_count: model.vertex_count,
+					};
+
+					command_buffer.push_constants(
+						self.shadow_pipeline_layout.handle(),
+						vk::ShaderStageFlags::VERTEX,
+						0,
+						&push_constants,
+					);
+
+					primitive_draw
+						.record_shadow(command_buffer, &self.shadow_pipelines);
+				}
+			}
+
+			command_buffer.end_render_pass();
+		}
+	}
+}
+
+// Fullscreen triangle at far depth, shares layout and render state of mesh
+// pipelines
+fn create_skybox_pipeline<'a>(
+	device: &'a Device,
+	pipeline_cache: &PipelineCache,
+	base_pipeline_create_info: &vk::GraphicsPipelineCreateInfo,
+) -> Result<GraphicsPipeline<'a>> {
+	let vertex_shader_module =
+		ShaderModule::new(device, &gen_shader_path("skybox.vert"))?;
+
+	let frag_shader_module =
+		ShaderModule::new(device, &gen_shader_path("skybox.frag"))?;
+
+	let shader_stage_create_infos = [
+		vk::PipelineShaderStageCreateInfo::builder()
+			.module(vertex_shader_module.handle())
+			.name(SHADER_ENTRY_POINT)
+			.stage(vk::ShaderStageFlags::VERTEX)
+			.build(),
+		vk::PipelineShaderStageCreateInfo::builder()
+			.module(frag_shader_module.handle())
+			.name(SHADER_ENTRY_POINT)
+			.stage(vk::ShaderStageFlags::FRAGMENT)
+			.build(),
+	];
+
+	let vertex_input_state_info =
+		vk::PipelineVertexInputStateCreateInfo::builder();
+
+	let input_assembly_state_info =
+		vk::PipelineInputAssemblyStateCreateInfo::builder()
+			.topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+
+	let rasterization_state_info = vk::PipelineRasterizationStateCreateInfo {
+		cull_mode: vk::CullModeFlags::NONE,
+		..unsafe { *base_pipeline_create_info.p_rasterization_state }
+	};
+
+	let depth_stencil_state_info =
+		vk::PipelineDepthStencilStateCreateInfo::builder()
+			.depth_test_enable(true)
+			.depth_write_enable(false)
+			.depth_compare_op(vk::CompareOp::LESS_OR_EQUAL);
+
+	let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+		stage_count: shader_stage_create_infos.len() as _,
+		p_stages: shader_stage_create_infos.as_ptr(),
+		p_vertex_input_state: &*vertex_input_state_info,
+		p_input_assembly_state: &*input_assembly_state_info,
+		p_rasterization_state: &rasterization_state_info,
+		p_depth_stencil_state: &*depth_stencil_state_info,
+		..*base_pipeline_create_info
+	};
+
+	GraphicsPipeline::new(device, pipeline_cache, &pipeline_create_info)
+}
@@ -0,0 +1,132 @@
+use crate::camera::{CameraController, OrbitCamera};
+use crate::gltf_wrapper::{Aabb, Scene};
+use anyhow::{anyhow, Result};
+use ash::vk;
+
+// Selects between scene cameras and the orbit camera
+pub struct SceneView {
+	bounds: Aabb,
+	camera_nodes: Vec<usize>,
+	active_camera: Option<usize>,
+	camera_controller: CameraController,
+}
+
+impl SceneView {
+	// Starts from the first scene camera when none is requested
+	pub fn new(scene: &Scene, camera_index: Option<usize>) -> Result<Self> {
+		let bounds = scene.bounds().unwrap_or_else(|| {
+			Aabb::new(glm::vec3(-1.0, -1.0, -1.0), glm::vec3(1.0, 1.0, 1.0))
+		});
+
+		let camera = OrbitCamera::new(&bounds);
+		let camera_controller = CameraController::new(camera);
+
+		let camera_nodes = scene.camera_nodes();
+
+		for (index, &node) in camera_nodes.iter().enumerate() {
+			let node = scene.node(node);
+			let camera = scene.camera(node.camera().unwrap());
+
+			log::info!(
+				"Scene camera {index}; node: {}, camera: {}",
+				node.name().unwrap_or_default(),
+				camera.name().unwrap_or_default()
+			);
+		}
+
+		// None selects the orbit camera
+		let active_camera = match camera_index {
+			Some(index) if index >= camera_nodes.len() => {
+				return Err(anyhow!("Scene has no camera with index: {index}"));
+			}
+			Some(index) => Some(index),
+			None => (!camera_nodes.is_empty()).then_some(0),
+		};
+
+		let scene_view = Self {
+			bounds,
+			camera_nodes,
+			active_camera,
+			camera_controller,
+		};
+
+		Ok(scene_view)
+	}
+
+	pub fn camera_controller_mut(&mut self) -> &mut CameraController {
+		&mut self.camera_controller
+	}
+
+	pub fn frame_all(&mut self) {
+		self.camera_controller.frame(&self.bounds);
+	}
+
+	pub fn next_camera(&mut self) {
+		self.active_camera = match self.active_camera {
+			Some(index) if index + 1 < self.camera_nodes.len() => {
+				Some(index + 1)
+			}
+			Some(_) => None,
+			None => (!self.camera_nodes.is_empty()).then_some(0),
+		};
+
+		log::info!("Active scene camera: {:?}", self.active_camera);
+	}
+
+	// View and projection matrices and viewport area honouring camera
+	// aspect ratio
+	pub fn camera_matrices(
+		&self,
+		scene: &Scene,
+		extent: vk::Extent2D,
+	) -> (glm::Mat4, glm::Mat4, vk::Rect2D) {
+		let width = extent.width as f32;
+		let height = extent.height as f32;
+
+		let (view, projection, aspect_ratio) = match self.active_camera {
+			Some(index) => {
+				let node = scene.node(self.camera_nodes[index]);
+				let camera = scene.camera(node.camera().unwrap());
+				let aspect_ratio =
+					camera.aspect_ratio().unwrap_or(width / height);
+
+				let view = glm::inverse(node.world_transform());
+				let projection = camera.projection_matrix(aspect_ratio);
+
+				(view, projection, aspect_ratio)
+			}
+			None => {
+				let camera = self.camera_controller.camera();
+				let view = camera.view();
+				let projection = camera.projection(width, height);
+
+				(view, projection, width / height)
+			}
+		};
+
+		(view, projection, letterbox(extent, aspect_ratio))
+	}
+}
+
+// Largest centered area of given aspect ratio
+fn letterbox(extent: vk::Extent2D, aspect_ratio: f32) -> vk::Rect2D {
+	let width = extent.width as f32;
+	let height = extent.height as f32;
+
+	let (width, height) = if width / height > aspect_ratio {
+		(height * aspect_ratio, height)
+	} else {
+		(width, width / aspect_ratio)
+	};
+
+	let width = (width.round() as u32).clamp(1, extent.width);
+	let height = (height.round() as u32).clamp(1, extent.height);
+
+	vk::Rect2D {
+		offset: vk::Offset2D {
+			x: ((extent.width - width) / 2) as _,
+			y: ((extent.height - height) / 2) as _,
+		},
+		extent: vk::Extent2D { width, height },
+	}
+}
@@ -0,0 +1,169 @@
+use crate::gltf_wrapper::{self, Scene};
+use crate::vulkan_wrapper::{
+	self, Device, ExtensionName, Instance, PhysicalDevice,
+};
+use anyhow::{anyhow, Context, Result};
+use ash::vk;
+use gltf::Gltf;
+use std::path::{Path, PathBuf};
+
+const PIPELINE_CACHE_FILE: &str = "oko/pipeline_cache.bin";
+
+pub fn load_scene(
+	input_file: &Path,
+	scene_index: usize,
+) -> Result<(Scene, Vec<Vec<u8>>)> {
+	let gltf = Gltf::open(input_file).with_context(|| {
+		format!("Unable to load glTF file: {}", input_file.display())
+	})?;
+
+	let base_dir = input_file.parent().unwrap_or_else(|| Path::new(""));
+	let mut buffers = gltf_wrapper::load_buffers(&gltf, base_dir)?;
+	let document =
+		gltf_wrapper::resolve_sparse_accessors(gltf.document, &mut buffers)?;
+
+	let scene = document.scenes().nth(scene_index).ok_or_else(|| {
+		anyhow!("glTF file has no requested index: {scene_index}")
+	})?;
+
+	let mut scene =
+		Scene::new(&scene, document.animations(), &buffers, base_dir)?;
+	scene.generate_missing_attributes(&mut buffers)?;
+
+	Ok((scene, buffers))
+}
+
+pub fn create_instance(extensions: &[ExtensionName]) -> Result<Instance> {
+	let instance_extensions = [
+		#[cfg(debug_assertions)]
+		ash::extensions::ext::DebugUtils::name().as_ptr(),
+	];
+
+	let instance_extensions = [&instance_extensions[..], extensions].concat();
+
+	let required_instance_version = vulkan_wrapper::make_api_version(1, 1, 0);
+
+	Instance::new(&instance_extensions, required_instance_version)
+}
+
+// Pipeline cache file under platform user cache directory, None when the
+// directory can't be determined
+pub fn pipeline_cache_path() -> Option<PathBuf> {
+	let env_dir = |name| {
+		std::env::var_os(name)
+			.map(PathBuf::from)
+			.filter(|dir| dir.is_absolute())
+	};
+
+	let cache_dir = if cfg!(windows) {
+		env_dir("LOCALAPPDATA")
+	} else if cfg!(target_os = "macos") {
+		env_dir("HOME").map(|home| home.join("Library/Caches"))
+	} else {
+		env_dir("XDG_CACHE_HOME")
+			.or_else(|| env_dir("HOME").map(|home| home.join(".cache")))
+	};
+
+	cache_dir.map(|dir| dir.join(PIPELINE_CACHE_FILE))
+}
+
+// Finds device with graphics queue family accepted by the filter
+pub fn find_physical_device(
+	instance: &Instance,
+	queue_family_filter: impl Fn(&PhysicalDevice, u32) -> bool,
+) -> Option<(&PhysicalDevice, u32)> {
+	instance
+		.physical_devices()
+		.iter()
+		.find_map(|physical_device| {
+			let queue_families_properties =
+				&physical_device.queue_families_properties;
+
+			let graphics_queue_index = queue_families_properties
+				.iter()
+				.enumerate()
+				.find_map(|(queue_index, &queue_family_properties)| {
+					let queue_index = queue_index as _;
+
+					let graphic_support = queue_family_properties
+						.queue_flags
+						.contains(vk::QueueFlags::GRAPHICS);
+
+					if graphic_support
+						&& queue_family_filter(physical_device, queue_index)
+					{
+						Some(queue_index)
+					} else {
+						None
+					}
+				});
+
+			graphics_queue_index
+				.map(|queue_index| (physical_device, queue_index))
+		})
+}
+
+// Enables optional features the scene benefits from, the scene data is
+// adjusted when they are missing
+pub fn create_device<'a>(
+	instance: &'a Instance,
+	physical_device: &'a PhysicalDevice,
+	queue_family_index: u32,
+	extensions: &[ExtensionName],
+	scene: &mut Scene,
+	buffers: &mut Vec<Vec<u8>>,
+) -> Result<Device<'a>> {
+	let queue_priorities = [1.0_f32];
+	let queues = maplit::hashmap! {
+			queue_family_index => &queue_priorities[..]
+	};
+
+	let index_type_uint8 = index_type_uint8_support(instance, physical_device);
+
+	let mut device_extensions = extensions.to_vec();
+
+	let mut index_type_uint8_features =
+		vk::PhysicalDeviceIndexTypeUint8FeaturesEXT::builder()
+			.index_type_uint8(true);
+
+	let mut device_features = vk::PhysicalDeviceFeatures2::builder();
+
+	if index_type_uint8 {
+		device_extensions.push(vk::ExtIndexTypeUint8Fn::name().as_ptr());
+		device_features =
+			device_features.push_next(&mut index_type_uint8_features);
+	} else {
+		log::info!(
+			"{:?} is not supported, u8 indices are widened to u16",
+			vk::ExtIndexTypeUint8Fn::name()
+		);
+		scene.widen_u8_indices(buffers)?;
+	}
+
+	Device::new(
+		instance,
+		physical_device,
+		&device_extensions,
+		&mut device_features,
+		&queues,
+	)
+}
+
+fn index_type_uint8_support(
+	instance: &Instance,
+	physical_device: &PhysicalDevice,
+) -> bool {
+	if !physical_device.supports_extension(vk::ExtIndexTypeUint8Fn::name()) {
+		return false;
+	}
+
+	let mut index_type_uint8_features =
+		vk::PhysicalDeviceIndexTypeUint8FeaturesEXT::default();
+
+	let mut features = vk::PhysicalDeviceFeatures2::builder()
+		.push_next(&mut index_type_uint8_features);
+
+	instance.physical_device_features2(physical_device, &mut features);
+
+	index_type_uint8_features.index_type_uint8 == vk::TRUE
+}
@@ -0,0 +1,309 @@
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{
+	Allocator, CommandPool, Device, Fence, FrameBuffer, Image, ImageView,
+	Queue, RenderPass, Sampler,
+};
+use anyhow::{anyhow, Result};
+use ash::vk;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShadowQuality {
+	Off,
+	Low,
+	High,
+}
+
+impl ShadowQuality {
+	pub const VARIANTS: [&'static str; 3] = ["off", "low", "high"];
+
+	pub fn enabled(self) -> bool {
+		self != ShadowQuality::Off
+	}
+
+	// Cycles through qualities in increasing order, wrapping to off
+	pub fn next(self) -> Self {
+		match self {
+			ShadowQuality::Off => ShadowQuality::Low,
+			ShadowQuality::Low => ShadowQuality::High,
+			ShadowQuality::High => ShadowQuality::Off,
+		}
+	}
+
+	fn map_size(self) -> u32 {
+		match self {
+			ShadowQuality::Off => 1,
+			ShadowQuality::Low => 1024,
+			ShadowQuality::High => 2048,
+		}
+	}
+
+	// PCF kernel spans this many texels on each side of the center one
+	pub fn filter_radius(self) -> u32 {
+		match self {
+			ShadowQuality::Off => 0,
+			ShadowQuality::Low => 1,
+			ShadowQuality::High => 2,
+		}
+	}
+}
+
+impl FromStr for ShadowQuality {
+	type Err = anyhow::Error;
+
+	fn from_str(quality: &str) -> Result<Self> {
+		match quality {
+			"off" => Ok(ShadowQuality::Off),
+			"low" => Ok(ShadowQuality::Low),
+			"high" => Ok(ShadowQuality::High),
+			_ => Err(anyhow!("Unknown shadow quality: {quality}")),
+		}
+	}
+}
+
+// Depth map array with a layer per shadow casting light, each rendered by
+// depth only pass into its own framebuffer
+pub struct ShadowMaps<'a> {
+	framebuffers: Vec<FrameBuffer<'a>>,
+	render_pass: RenderPass<'a>,
+	image_info: vk::DescriptorImageInfo,
+	_sampler: Sampler<'a>,
+	_layer_views: Vec<ImageView<'a>>,
+	_image_view: ImageView<'a>,
+	_image: Image<'a>,
+	quality: ShadowQuality,
+}
+
+impl<'a> ShadowMaps<'a> {
+	// Map array is never empty to keep descriptor valid, all layers start
+	// ready for sampling
+	pub fn new(
+		device: &'a Device,
+		allocator: &'a Allocator,
+		command_pool: &CommandPool,
+		queue: &Queue,
+		layer_count: usize,
+		quality: ShadowQuality,
+	) -> Result<Self> {
+		let layer_count = layer_count.max(1) as u32;
+		let map_size = quality.map_size();
+
+		let format_candidates = [vk::Format::D32_SFLOAT, vk::Format::D16_UNORM];
+
+		let format = device
+			.find_supported_format(
+				&format_candidates,
+				vk::ImageTiling::OPTIMAL,
+				vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT
+					| vk::FormatFeatureFlags::SAMPLED_IMAGE,
+			)
+			.ok_or_else(|| anyhow!("Suitable shadow map format not found"))?;
+
+		// render pass
+		let attachment_description = vk::AttachmentDescription::builder()
+			.format(format)
+			.samples(vk::SampleCountFlags::TYPE_1)
+			.load_op(vk::AttachmentLoadOp::CLEAR)
+			.store_op(vk::AttachmentStoreOp::STORE)
+			.stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+			.stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+			.initial_layout(vk::ImageLayout::UNDEFINED)
+			.final_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+			.build();
+
+		let depth_attachment_reference = vk::AttachmentReference::builder()
+			.attachment(0)
+			.layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+			.build();
+
+		let subpass_description = vk::SubpassDescription::builder()
+			.pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+			.depth_stencil_attachment(&depth_attachment_reference)
+			.build();
+
+		let depth_test_stages = vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+			| vk::PipelineStageFlags::LATE_FRAGMENT_TESTS;
+
+		// Previous frame reads finish before writes, writes finish before
+		// material shader reads
+		let subpass_dependencies = [
+			vk::SubpassDependency::builder()
+				.src_subpass(vk::SUBPASS_EXTERNAL)
+				.dst_subpass(0)
+				.src_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+				.src_access_mask(vk::AccessFlags::SHADER_READ)
+				.dst_stage_mask(depth_test_stages)
+				.dst_access_mask(
+					vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+						| vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+				)
+				.build(),
+			vk::SubpassDependency::builder()
+				.src_subpass(0)
+				.dst_subpass(vk::SUBPASS_EXTERNAL)
+				.src_stage_mask(depth_test_stages)
+				.src_access_mask(
+					vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+				)
+				.dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+				.dst_access_mask(vk::AccessFlags::SHADER_READ)
+				.build(),
+		];
+
+		let render_pass_create_info = vk::RenderPassCreateInfo::builder()
+			.attachments(slice_from_ref(&attachment_description))
+			.subpasses(slice_from_ref(&subpass_description))
+			.dependencies(&subpass_dependencies)
+			.build();
+
+		let render_pass = RenderPass::new(device, &render_pass_create_info)?;
+
+		// image
+		let image_create_info = vk::ImageCreateInfo::builder()
+			.image_type(vk::ImageType::TYPE_2D)
+			.extent(vk::Extent3D {
+				width: map_size,
+				height: map_size,
+				depth: 1,
+			})
+			.mip_levels(1)
+			.array_layers(layer_count)
+			.format(format)
+			.tiling(vk::ImageTiling::OPTIMAL)
+			.initial_layout(vk::ImageLayout::UNDEFINED)
+			.usage(
+				vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+					| vk::ImageUsageFlags::SAMPLED,
+			)
+			.samples(vk::SampleCountFlags::TYPE_1)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+		let image =
+			Image::new(device, allocator, &image_create_info, "shadow maps")?;
+
+		let create_view = |view_type, base_layer, layer_count| {
+			let subresource_range = vk::ImageSubresourceRange::builder()
+				.aspect_mask(vk::ImageAspectFlags::DEPTH)
+				.level_count(1)
+				.base_array_layer(base_layer)
+				.layer_count(layer_count)
+				.build();
+
+			let image_view_info = vk::ImageViewCreateInfo::builder()
+				.image(image.handle())
+				.view_type(view_type)
+				.format(format)
+				.subresource_range(subresource_range);
+
+			ImageView::new(device, &image_view_info)
+		};
+
+		let image_view =
+			create_view(vk::ImageViewType::TYPE_2D_ARRAY, 0, layer_count)?;
+
+		let layer_views = (0..layer_count)
+			.map(|layer| create_view(vk::ImageViewType::TYPE_2D, layer, 1))
+			.collect::<Result<Vec<_>>>()?;
+
+		let framebuffers = layer_views
+			.iter()
+			.map(|layer_view| {
+				let attachments = [layer_view.handle()];
+
+				let frame_buffer_create_info =
+					vk::FramebufferCreateInfo::builder()
+						.render_pass(render_pass.handle())
+						.attachments(&attachments)
+						.width(map_size)
+						.height(map_size)
+						.layers(1);
+
+				FrameBuffer::new(device, &frame_buffer_create_info)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		// Linear filtering of comparison results smooths PCF kernel taps,
+		// outside of maps everything is lit
+		let sampler_info = vk::SamplerCreateInfo::builder()
+			.mag_filter(vk::Filter::LINEAR)
+			.min_filter(vk::Filter::LINEAR)
+			.mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+			.address_mode_u(vk::SamplerAddressMode::CLAMP_TO_BORDER)
+			.address_mode_v(vk::SamplerAddressMode::CLAMP_TO_BORDER)
+			.address_mode_w(vk::SamplerAddressMode::CLAMP_TO_BORDER)
+			.border_color(vk::BorderColor::FLOAT_OPAQUE_WHITE)
+			.compare_enable(true)
+			.compare_op(vk::CompareOp::LESS_OR_EQUAL)
+			.build();
+
+		let sampler = Sampler::new(device, &sampler_info)?;
+
+		let image_info = vk::DescriptorImageInfo::builder()
+			.sampler(sampler.handle())
+			.image_view(image_view.handle())
+			.image_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+			.build();
+
+		// Layers not rendered this frame are still bound
+		let command_buffer = command_pool
+			.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?;
+
+		let begin_info = vk::CommandBufferBeginInfo::builder()
+			.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+		command_buffer.begin(&begin_info)?;
+
+		let read_barrier = image.level_barrier(
+			0,
+			1,
+			(vk::ImageLayout::UNDEFINED, vk::AccessFlags::empty()),
+			(
+				vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+				vk::AccessFlags::SHADER_READ,
+			),
+		);
+
+		command_buffer.pipeline_barrier(
+			vk::PipelineStageFlags::TOP_OF_PIPE,
+			vk::PipelineStageFlags::FRAGMENT_SHADER,
+			&[],
+			&[],
+			slice_from_ref(&read_barrier),
+		);
+
+		command_buffer.end()?;
+
+		let fence = Fence::new(device, false)?;
+		queue.submit(&command_buffer, &[], &[], &[], &fence)?;
+		fence.wait_max_timeout()?;
+
+		let shadow_maps = Self {
+			framebuffers,
+			render_pass,
+			image_info,
+			_sampler: sampler,
+			_layer_views: layer_views,
+			_image_view: image_view,
+			_image: image,
+			quality,
+		};
+
+		Ok(shadow_maps)
+	}
+
+	pub fn render_pass(&self) -> &RenderPass<'a> {
+		&self.render_pass
+	}
+
+	pub fn framebuffer(&self, layer: usize) -> &FrameBuffer<'a> {
+		&self.framebuffers[layer]
+	}
+
+	pub fn image_info(&self) -> vk::DescriptorImageInfo {
+		self.image_info
+	}
+
+	pub fn quality(&self) -> ShadowQuality {
+		self.quality
+	}
+}
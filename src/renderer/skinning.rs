@@ -0,0 +1,34 @@
+use crate::gltf_wrapper::Scene;
+
+// Start of each scene skin in joint matrices, skins are laid out in order
+pub fn joint_offsets(scene: &Scene) -> Vec<usize> {
+	scene
+		.skins()
+		.iter()
+		.scan(0, |offset, skin| {
+			let skin_offset = *offset;
+			*offset += skin.joints().len();
+
+			Some(skin_offset)
+		})
+		.collect()
+}
+
+pub fn joint_count(scene: &Scene) -> usize {
+	scene.skins().iter().map(|skin| skin.joints().len()).sum()
+}
+
+// Joint matrices take vertices from bind pose straight to world space
+pub fn joint_matrices(scene: &Scene) -> Vec<glm::Mat4> {
+	scene
+		.skins()
+		.iter()
+		.flat_map(|skin| {
+			skin.joints().iter().zip(skin.inverse_bind_matrices()).map(
+				|(&joint, inverse_bind_matrix)| {
+					scene.node(joint).world_transform() * inverse_bind_matrix
+				},
+			)
+		})
+		.collect()
+}
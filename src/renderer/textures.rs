@@ -0,0 +1,330 @@
+use crate::gltf_wrapper::{Scene, Texture};
+use crate::slice_from_ref;
+use crate::vulkan_wrapper::{
+	Allocator, Buffer, CommandPool, Device, Fence, Image, ImageView, Queue,
+	Sampler,
+};
+use anyhow::{Context, Result};
+use ash::vk;
+use image::{imageops, RgbaImage};
+use std::collections::HashMap;
+
+// Bound in place of absent material textures, neutral for factors
+const DEFAULT_TEXTURE_PIXEL: [u8; 4] = [255, 255, 255, 255];
+
+// Mip levels are generated with blits when format supports it
+const BLIT_FORMAT_FEATURES: vk::FormatFeatureFlags =
+	vk::FormatFeatureFlags::from_raw(
+		vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR.as_raw()
+			| vk::FormatFeatureFlags::BLIT_SRC.as_raw()
+			| vk::FormatFeatureFlags::BLIT_DST.as_raw(),
+	);
+
+// Mip levels in order, starting from the full size one
+struct ImageSource {
+	format: vk::Format,
+	mip_levels: u32,
+	levels: Vec<RgbaImage>,
+}
+
+impl ImageSource {
+	fn new(device: &Device, image: RgbaImage, format: vk::Format) -> Self {
+		let mip_levels = 32 - image.width().max(image.height()).leading_zeros();
+
+		let blit_support = device
+			.find_supported_format(
+				slice_from_ref(&format),
+				vk::ImageTiling::OPTIMAL,
+				BLIT_FORMAT_FEATURES,
+			)
+			.is_some();
+
+		let mut levels = vec![image];
+
+		// CPU fallback, each level is downsampled from the previous one
+		if !blit_support {
+			for _ in 1..mip_levels {
+				let previous_level = levels.last().unwrap();
+
+				let level = imageops::resize(
+					previous_level,
+					(previous_level.width() >> 1).max(1),
+					(previous_level.height() >> 1).max(1),
+					imageops::FilterType::Triangle,
+				);
+
+				levels.push(level);
+			}
+		}
+
+		Self {
+			format,
+			mip_levels,
+			levels,
+		}
+	}
+}
+
+// Sampled images of scene textures, images shared by textures are uploaded
+// once per color space
+pub struct SceneTextures<'a> {
+	texture_image_infos: Vec<vk::DescriptorImageInfo>,
+	default_image_info: vk::DescriptorImageInfo,
+	_samplers: Vec<Sampler<'a>>,
+	_default_sampler: Sampler<'a>,
+	_image_views: Vec<ImageView<'a>>,
+	_images: Vec<Image<'a>>,
+}
+
+impl<'a> SceneTextures<'a> {
+	pub fn new(
+		device: &'a Device,
+		allocator: &'a Allocator,
+		command_pool: &'a CommandPool,
+		queue: &Queue,
+		scene: &Scene,
+	) -> Result<Self> {
+		let mut image_indices = HashMap::new();
+		let mut image_sources = Vec::new();
+
+		for texture in scene.textures() {
+			let key = (texture.image(), texture.srgb());
+
+			if image_indices.contains_key(&key) {
+				continue;
+			}
+
+			let image =
+				scene.image(texture.image()).decode().with_context(|| {
+					format!(
+						"Unable to decode texture {} image",
+						texture.index()
+					)
+				})?;
+
+			let format = if texture.srgb() {
+				vk::Format::R8G8B8A8_SRGB
+			} else {
+				vk::Format::R8G8B8A8_UNORM
+			};
+
+			image_indices.insert(key, image_sources.len());
+			image_sources.push(ImageSource::new(device, image, format));
+		}
+
+		// default texture is the last one
+		let default_image =
+			RgbaImage::from_pixel(1, 1, image::Rgba(DEFAULT_TEXTURE_PIXEL));
+
+		image_sources.push(ImageSource::new(
+			device,
+			default_image,
+			vk::Format::R8G8B8A8_UNORM,
+		));
+
+		let images = upload_images(
+			device,
+			allocator,
+			command_pool,
+			queue,
+			&image_sources,
+		)?;
+
+		let image_views = images
+			.iter()
+			.map(|image| {
+				let subresource_range = vk::ImageSubresourceRange::builder()
+					.aspect_mask(vk::ImageAspectFlags::COLOR)
+					.level_count(image.mip_levels())
+					.layer_count(1)
+					.build();
+
+				let image_view_info = vk::ImageViewCreateInfo::builder()
+					.image(image.handle())
+					.view_type(vk::ImageViewType::TYPE_2D)
+					.format(image.format())
+					.subresource_range(subresource_range);
+
+				ImageView::new(device, &image_view_info)
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let samplers = scene
+			.textures()
+			.iter()
+			.map(|texture| Sampler::new(device, &sampler_create_info(texture)))
+			.collect::<Result<Vec<_>>>()?;
+
+		let default_sampler_info = vk::SamplerCreateInfo::builder()
+			.mag_filter(vk::Filter::LINEAR)
+			.min_filter(vk::Filter::LINEAR)
+			.max_lod(vk::LOD_CLAMP_NONE)
+			.build();
+
+		let default_sampler = Sampler::new(device, &default_sampler_info)?;
+
+		let image_info = |sampler: &Sampler, image_view: &ImageView| {
+			vk::DescriptorImageInfo::builder()
+				.sampler(sampler.handle())
+				.image_view(image_view.handle())
+				.image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+				.build()
+		};
+
+		let texture_image_infos = scene
+			.textures()
+			.iter()
+			.zip(&samplers)
+			.map(|(texture, sampler)| {
+				let image = image_indices[&(texture.image(), texture.srgb())];
+				image_info(sampler, &image_views[image])
+			})
+			.collect();
+
+		let default_image_info =
+			image_info(&default_sampler, image_views.last().unwrap());
+
+		let scene_textures = Self {
+			texture_image_infos,
+			default_image_info,
+			_samplers: samplers,
+			_default_sampler: default_sampler,
+			_image_views: image_views,
+			_images: images,
+		};
+
+		Ok(scene_textures)
+	}
+
+	// Default texture is used when texture is None
+	pub fn image_info(
+		&self,
+		texture: Option<usize>,
+	) -> vk::DescriptorImageInfo {
+		match texture {
+			Some(texture) => self.texture_image_infos[texture],
+			None => self.default_image_info,
+		}
+	}
+}
+
+fn sampler_create_info(texture: &Texture) -> vk::SamplerCreateInfo {
+	// Level of detail clamped to the first level disables mipmapping
+	let (mipmap_mode, max_lod) = match texture.mipmap_mode() {
+		Some(mipmap_mode) => (mipmap_mode, vk::LOD_CLAMP_NONE),
+		None => (vk::SamplerMipmapMode::NEAREST, 0.0),
+	};
+
+	vk::SamplerCreateInfo::builder()
+		.mag_filter(texture.mag_filter())
+		.min_filter(texture.min_filter())
+		.mipmap_mode(mipmap_mode)
+		.address_mode_u(texture.address_mode_u())
+		.address_mode_v(texture.address_mode_v())
+		.address_mode_w(vk::SamplerAddressMode::REPEAT)
+		.max_lod(max_lod)
+		.build()
+}
+
+fn upload_images<'a>(
+	device: &'a Device,
+	allocator: &'a Allocator,
+	command_pool: &CommandPool,
+	queue: &Queue,
+	image_sources: &[ImageSource],
+) -> Result<Vec<Image<'a>>> {
+	let staging_buffers = image_sources
+		.iter()
+		.map(|image_source| {
+			let size = image_source
+				.levels
+				.iter()
+				.map(|level| level.as_raw().len())
+				.sum::<usize>();
+
+			let buffer_create_info = vk::BufferCreateInfo::builder()
+				.size(size as _)
+				.usage(vk::BufferUsageFlags::TRANSFER_SRC)
+				.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+			let mut staging_buffer = Buffer::new(
+				device,
+				allocator,
+				&buffer_create_info,
+				gpu_allocator::MemoryLocation::CpuToGpu,
+				"texture staging buffer",
+			)?;
+
+			let mut level_offsets = Vec::new();
+			let mut offset = 0;
+			let mapped_slice = staging_buffer.mapped_slice_mut()?;
+
+			for level in &image_source.levels {
+				let pixels = level.as_raw();
+				mapped_slice[offset..offset + pixels.len()]
+					.copy_from_slice(pixels);
+
+				level_offsets.push(offset);
+				offset += pixels.len();
+			}
+
+			staging_buffer.flush()?;
+
+			Ok((staging_buffer, level_offsets))
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	let transfer_command_buffer = command_pool
+		.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?;
+
+	let begin_info = vk::CommandBufferBeginInfo::builder()
+		.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+	transfer_command_buffer.begin(&begin_info)?;
+
+	let images = image_sources
+		.iter()
+		.zip(&staging_buffers)
+		.map(|(image_source, (staging_buffer, level_offsets))| {
+			let image_create_info = vk::ImageCreateInfo::builder()
+				.image_type(vk::ImageType::TYPE_2D)
+				.extent(vk::Extent3D {
+					width: image_source.levels[0].width(),
+					height: image_source.levels[0].height(),
+					depth: 1,
+				})
+				.mip_levels(image_source.mip_levels)
+				.array_layers(1)
+				.format(image_source.format)
+				.tiling(vk::ImageTiling::OPTIMAL)
+				.initial_layout(vk::ImageLayout::UNDEFINED)
+				.usage(
+					vk::ImageUsageFlags::SAMPLED
+						| vk::ImageUsageFlags::TRANSFER_SRC
+						| vk::ImageUsageFlags::TRANSFER_DST,
+				)
+				.samples(vk::SampleCountFlags::TYPE_1)
+				.sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+			let image =
+				Image::new(device, allocator, &image_create_info, "texture")?;
+
+			image.record_upload(
+				&transfer_command_buffer,
+				staging_buffer,
+				level_offsets,
+				vk::PipelineStageFlags::FRAGMENT_SHADER,
+			);
+
+			Ok(image)
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	transfer_command_buffer.end()?;
+
+	let transfer_fence = Fence::new(device, false)?;
+	queue.submit(&transfer_command_buffer, &[], &[], &[], &transfer_fence)?;
+	transfer_fence.wait_max_timeout()?;
+
+	Ok(images)
+}
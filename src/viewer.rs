@@ -1,32 +1,27 @@
-use super::gltf_wrapper::Scene;
-use super::vulkan_wrapper;
+use super::camera::MouseButton;
+use super::renderer;
+use super::renderer::AnimationPlayer;
+use super::renderer::Environment;
+use super::renderer::FrameUniforms;
+use super::renderer::RenderTargets;
+use super::renderer::SceneRenderer;
+use super::renderer::SceneView;
+use super::renderer::ShadowQuality;
 use super::vulkan_wrapper::Allocator;
-use super::vulkan_wrapper::Buffer;
+use super::vulkan_wrapper::CommandBuffer;
 use super::vulkan_wrapper::CommandPool;
 use super::vulkan_wrapper::CreateSurface;
-use super::vulkan_wrapper::DescriptorPool;
-use super::vulkan_wrapper::DescriptorSetLayout;
-use super::vulkan_wrapper::Device;
 use super::vulkan_wrapper::Fence;
-use super::vulkan_wrapper::FrameBuffer;
-use super::vulkan_wrapper::GraphicsPipeline;
-use super::vulkan_wrapper::Image;
 use super::vulkan_wrapper::ImageView;
-use super::vulkan_wrapper::Instance;
-use super::vulkan_wrapper::PhysicalDevice;
-use super::vulkan_wrapper::Pipeline;
-use super::vulkan_wrapper::PipelineLayout;
+use super::vulkan_wrapper::PipelineCache;
 use super::vulkan_wrapper::Queue;
-use super::vulkan_wrapper::RenderPass;
 use super::vulkan_wrapper::Semaphore;
-use super::vulkan_wrapper::ShaderModule;
 use super::vulkan_wrapper::Surface;
 use super::vulkan_wrapper::Swapchain;
 use crate::slice_from_ref;
 use anyhow::anyhow;
 use anyhow::Result;
 use ash::vk;
-use gltf::Gltf;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
@@ -34,12 +29,29 @@ type RenderDoc = renderdoc::RenderDoc<renderdoc::V100>;
 
 pub enum Event {
 	Stop,
+	Resize,
+	CursorMoved { x: f32, y: f32 },
+	MouseButton { button: MouseButton, pressed: bool },
+	MouseWheel { steps: f32 },
+	ResetCamera,
+	FrameAll,
+	NextCamera,
+	ToggleAnimation,
+	NextAnimation,
+	ScrubAnimation { seconds: f32 },
+	ScaleAnimationSpeed { factor: f32 },
+	NextShadowQuality,
 	RenderDocFrameCapture,
 }
 
 pub struct LaunchConfig {
 	pub input_file: PathBuf,
 	pub scene_index: usize,
+	pub camera_index: Option<usize>,
+	pub environment_file: Option<PathBuf>,
+	pub skybox: bool,
+	pub shadow_quality: ShadowQuality,
+	pub animation: Option<String>,
 	pub renderdoc: bool,
 }
 
@@ -48,16 +60,11 @@ struct FrameResources<'a> {
 	pub image_available_semaphore: Semaphore<'a>,
 	pub render_done_semaphore: Semaphore<'a>,
 	pub render_done_fence: Fence<'a>,
+	pub command_buffer: CommandBuffer<'a>,
+	pub frame_uniforms: FrameUniforms<'a>,
 }
 
-const UNIFORM_BUFFER_VK_DESCRIPTOR_COUNT: usize = 64;
-const DESCRIPTOR_SET_COUNT: usize = 512;
-const SHADER_ENTRY_POINT: &std::ffi::CStr = cstr::cstr!("main");
-
-#[repr(C)]
-struct ViewProjectionUBO {
-	mt: glm::TMat4<f32>,
-}
+const FRAMES_IN_FLIGHT: usize = 2;
 
 pub fn run<SurfaceOwner: CreateSurface>(
 	present_target: SurfaceOwner,
@@ -81,39 +88,20 @@ pub fn run<SurfaceOwner: CreateSurface>(
 		None
 	};
 
-	let input_file = Path::new(&config.input_file);
-	let gltf = Gltf::open(input_file)?;
-
-	let scenes = gltf
-		.scenes()
-		.map(|scene| Scene::new(&scene))
-		.collect::<Result<Vec<_>>>()?;
-
-	let scene = scenes.get(config.scene_index).ok_or_else(|| {
-		anyhow!("glTF file has no requested index: {}", config.scene_index)
-	})?;
+	let (mut scene, mut buffers) =
+		renderer::load_scene(&config.input_file, config.scene_index)?;
 
 	let surface_required_extension = present_target.required_extensions()?;
 
 	// Instance
-	let instance_extensions = [
-		#[cfg(debug_assertions)]
-		ash::extensions::ext::DebugUtils::name().as_ptr(),
-	];
-
-	let instance_extensions =
-		[&instance_extensions[..], &surface_required_extension[..]].concat();
-
-	let required_instance_version = vulkan_wrapper::make_api_version(1, 1, 0);
-	let instance =
-		Instance::new(&instance_extensions, required_instance_version)?;
+	let instance = renderer::create_instance(&surface_required_extension)?;
 
 	// Debug Messenger
 	#[cfg(debug_assertions)]
 	let _debug_messenger =
 		super::vulkan_wrapper::debug_messanger::DebugMessenger::new(
 			&instance,
-			Some(vulkan_debug_callback),
+			Some(renderer::vulkan_debug_callback),
 		)?;
 
 	//Surface
@@ -121,23 +109,25 @@ pub fn run<SurfaceOwner: CreateSurface>(
 
 	// Physical device and main queue
 	let (physical_device, graphics_queue_family_index) =
-		find_suitable_physical_device(&instance, &surface).ok_or_else(
-			|| anyhow!("The suitable physical device is not found"),
-		)?;
+		renderer::find_physical_device(&instance, |physical_device, index| {
+			surface.physical_device_support(physical_device, index)
+		})
+		.ok_or_else(|| anyhow!("The suitable physical device is not found"))?;
 
 	// Device
-	let queue_priorities = [1.0_f32];
-	let queues = maplit::hashmap! {
-			graphics_queue_family_index => &queue_priorities[..]
-	};
-
 	let device_extensions = [
 		ash::extensions::khr::Swapchain::name().as_ptr(),
 		ash::extensions::khr::TimelineSemaphore::name().as_ptr(),
 	];
 
-	let device =
-		Device::new(&instance, physical_device, &device_extensions, &queues)?;
+	let device = renderer::create_device(
+		&instance,
+		physical_device,
+		graphics_queue_family_index,
+		&device_extensions,
+		&mut scene,
+		&mut buffers,
+	)?;
 
 	// queue
 	let queue = Queue::new(&device, graphics_queue_family_index);
@@ -146,443 +136,125 @@ pub fn run<SurfaceOwner: CreateSurface>(
 	let allocator = Allocator::new(&instance, &device, physical_device, false)?;
 
 	// Swapchain
-	let swapchain = Swapchain::new(&instance, &device, &surface)?;
-	let surface_extent = swapchain.extent();
-	let swapchain_image_count = swapchain.image_count();
+	let mut swapchain = Swapchain::new(&instance, &device, &surface)?;
 
 	// command pool
 	let command_pool_create_info = vk::CommandPoolCreateInfo::builder()
+		.flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
 		.queue_family_index(graphics_queue_family_index);
 	let command_pool = CommandPool::new(&device, &command_pool_create_info)?;
 
-	// command buffers
-	let command_buffers = command_pool.allocate_command_buffers(
-		swapchain_image_count as _,
-		vk::CommandBufferLevel::PRIMARY,
-	)?;
+	let pipeline_cache_path = renderer::pipeline_cache_path();
+	let pipeline_cache =
+		PipelineCache::new(&device, pipeline_cache_path.as_deref())?;
 
-	let vertex_buffers = create_vertex_buffers(
-		&gltf,
-		input_file,
-		&command_pool,
+	let environment = Environment::new(
+		&device,
 		&allocator,
+		&command_pool,
 		&queue,
+		&pipeline_cache,
+		config.environment_file.as_deref(),
+		config.skybox,
 	)?;
 
-	// vertex shader
-	let vertex_shader_module =
-		ShaderModule::new(&device, &gen_shader_path("geometry.vert"))?;
-
-	// fragment shader
-	let frag_shader_module =
-		ShaderModule::new(&device, &gen_shader_path("geometry.frag"))?;
-
-	let vertex_shader_state_create_info =
-		vk::PipelineShaderStageCreateInfo::builder()
-			.module(vertex_shader_module.handle())
-			.name(SHADER_ENTRY_POINT)
-			.stage(vk::ShaderStageFlags::VERTEX)
-			.build();
-
-	let frag_shader_state_create_info =
-		vk::PipelineShaderStageCreateInfo::builder()
-			.module(frag_shader_module.handle())
-			.name(SHADER_ENTRY_POINT)
-			.stage(vk::ShaderStageFlags::FRAGMENT)
-			.build();
-
-	let shader_stage_create_infos = [
-		vertex_shader_state_create_info,
-		frag_shader_state_create_info,
-	];
-
-	// viewport state
-	let height = surface_extent.height as f32;
-	let view_ports = [vk::Viewport::builder()
-		.width(surface_extent.width as _)
-		.height(-height)
-		.y(height)
-		.max_depth(1.0)
-		.build()];
-
-	let scissors = [vk::Rect2D::builder().extent(surface_extent).build()];
-
-	let viewport_state_info = vk::PipelineViewportStateCreateInfo::builder()
-		.viewports(&view_ports)
-		.scissors(&scissors);
-
-	// multisample_state
-	let multisample_state_info =
-		vk::PipelineMultisampleStateCreateInfo::builder()
-			.rasterization_samples(vk::SampleCountFlags::TYPE_1);
-
-	// rasterization_info
-	let rasterization_info =
-		vk::PipelineRasterizationStateCreateInfo::builder()
-			.polygon_mode(vk::PolygonMode::FILL)
-			.cull_mode(vk::CullModeFlags::BACK)
-			.front_face(vk::FrontFace::COUNTER_CLOCKWISE)
-			.line_width(1.0);
-
-	// color_blend_state
-	let color_blend_attachments =
-		[vk::PipelineColorBlendAttachmentState::builder()
-			.color_write_mask(
-				vk::ColorComponentFlags::R
-					| vk::ColorComponentFlags::G
-					| vk::ColorComponentFlags::B
-					| vk::ColorComponentFlags::A,
-			)
-			.blend_enable(false)
-			.build()];
-
-	let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
-		.attachments(&color_blend_attachments);
-
-	// depth
-	let depth_format_candidates = [
-		vk::Format::D24_UNORM_S8_UINT,
-		vk::Format::D32_SFLOAT,
-		vk::Format::D32_SFLOAT_S8_UINT,
-	];
+	let mut scene_renderer = SceneRenderer::new(
+		&device,
+		&allocator,
+		&command_pool,
+		&queue,
+		&pipeline_cache,
+		&scene,
+		&buffers,
+		swapchain.format(),
+		vk::ImageLayout::PRESENT_SRC_KHR,
+		environment,
+		config.shadow_quality,
+	)?;
 
-	let depth_format_tiling = vk::ImageTiling::OPTIMAL;
-	let depth_format_features =
-		vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT;
-
-	let depth_format = device
-		.find_supported_format(
-			&depth_format_candidates,
-			depth_format_tiling,
-			depth_format_features,
-		)
-		.ok_or_else(|| anyhow!("Suitable depth format not found"))?;
-
-	let depth_extent = vk::Extent3D::builder()
-		.width(surface_extent.width)
-		.height(surface_extent.height)
-		.depth(1)
-		.build();
-
-	let depth_image_info = vk::ImageCreateInfo::builder()
-		.image_type(vk::ImageType::TYPE_2D)
-		.extent(depth_extent)
-		.mip_levels(1)
-		.array_layers(1)
-		.format(depth_format)
-		.tiling(depth_format_tiling)
-		.initial_layout(vk::ImageLayout::UNDEFINED)
-		.usage(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT)
-		.samples(vk::SampleCountFlags::TYPE_1)
-		.sharing_mode(vk::SharingMode::EXCLUSIVE);
-
-	let depth_image =
-		Image::new(&device, &allocator, &depth_image_info, "depth")?;
-
-	let depth_image_subresource_range = vk::ImageSubresourceRange::builder()
-		.level_count(1)
-		.layer_count(1)
-		.aspect_mask(vk::ImageAspectFlags::DEPTH)
-		.build();
-
-	let depth_image_view_info = vk::ImageViewCreateInfo::builder()
-		.image(depth_image.handle())
-		.view_type(vk::ImageViewType::TYPE_2D)
-		.format(depth_format)
-		.subresource_range(depth_image_subresource_range);
-
-	let depth_image_view = ImageView::new(&device, &depth_image_view_info)?;
-
-	// render pass
-	let attachment_descriptions = [
-		vk::AttachmentDescription::builder()
-			.format(swapchain.format())
-			.samples(vk::SampleCountFlags::TYPE_1)
-			.load_op(vk::AttachmentLoadOp::CLEAR)
-			.store_op(vk::AttachmentStoreOp::STORE)
-			.stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-			.stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-			.initial_layout(vk::ImageLayout::UNDEFINED)
-			.final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
-			.build(),
-		vk::AttachmentDescription::builder()
-			.format(depth_format)
-			.samples(vk::SampleCountFlags::TYPE_1)
-			.load_op(vk::AttachmentLoadOp::CLEAR)
-			.store_op(vk::AttachmentStoreOp::DONT_CARE)
-			.stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-			.stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-			.initial_layout(vk::ImageLayout::UNDEFINED)
-			.final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
-			.build(),
-	];
+	let create_render_targets =
+		|swapchain: &Swapchain<_>, scene_renderer: &SceneRenderer| {
+			let image_views = swapchain
+				.image_views()
+				.iter()
+				.map(ImageView::handle)
+				.collect::<Vec<_>>();
 
-	let attachment_references = [vk::AttachmentReference::builder()
-		.attachment(0)
-		.layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
-		.build()];
-
-	let depth_attachment_reference = vk::AttachmentReference::builder()
-		.attachment(1)
-		.layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
-		.build();
-
-	let subpass_descriptions = [vk::SubpassDescription::builder()
-		.pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-		.color_attachments(&attachment_references)
-		.depth_stencil_attachment(&depth_attachment_reference)
-		.build()];
-
-	let subpass_dependency = [vk::SubpassDependency::builder()
-		.src_subpass(vk::SUBPASS_EXTERNAL)
-		.dst_subpass(0)
-		.src_stage_mask(
-			vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
-				| vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
-		)
-		.src_access_mask(vk::AccessFlags::empty())
-		.dst_stage_mask(
-			vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
-				| vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
-		)
-		.dst_access_mask(
-			vk::AccessFlags::COLOR_ATTACHMENT_WRITE
-				| vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
-		)
-		.build()];
-
-	let render_pass_create_info = vk::RenderPassCreateInfo::builder()
-		.attachments(&attachment_descriptions)
-		.subpasses(&subpass_descriptions)
-		.dependencies(&subpass_dependency)
-		.build();
-
-	let render_pass = RenderPass::new(&device, &render_pass_create_info)?;
-
-	// layout
-	let bindings = [vk::DescriptorSetLayoutBinding::builder()
-		.binding(0)
-		.descriptor_count(1)
-		.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-		.stage_flags(vk::ShaderStageFlags::VERTEX)
-		.build()];
-
-	let descriptor_set_layout = DescriptorSetLayout::new(&device, &bindings)?;
-
-	let descriptor_set_layouts = [descriptor_set_layout.handle()];
-
-	let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo::builder()
-		.set_layouts(&descriptor_set_layouts);
-
-	let pipeline_layout =
-		PipelineLayout::new(&device, &pipeline_layout_create_info)?;
-
-	// framebuffers
-	let framebuffers = swapchain
-		.image_views()
-		.iter()
-		.map(|image_view| {
-			let attachments = [image_view.handle(), depth_image_view.handle()];
-
-			let extent = surface_extent;
-
-			let frame_buffer_create_info = vk::FramebufferCreateInfo::builder()
-				.render_pass(render_pass.handle())
-				.attachments(&attachments)
-				.width(extent.width)
-				.height(extent.height)
-				.layers(1);
-
-			FrameBuffer::new(&device, &frame_buffer_create_info)
-		})
-		.collect::<Result<Vec<_>>>()?;
-
-	// descriptor pool
-	let pool_sizes = [vk::DescriptorPoolSize::builder()
-		.ty(vk::DescriptorType::UNIFORM_BUFFER)
-		.descriptor_count(UNIFORM_BUFFER_VK_DESCRIPTOR_COUNT as _)
-		.build()];
-
-	let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo::builder()
-		.pool_sizes(&pool_sizes)
-		.max_sets(DESCRIPTOR_SET_COUNT as _)
-		.build();
-
-	let descriptor_pool =
-		DescriptorPool::new(&device, &descriptor_pool_create_info)?;
-
-	let descriptor_sets =
-		descriptor_pool.allocate_descriptor_sets(&descriptor_set_layouts)?;
-
-	let view_projection_buffer_size = std::mem::size_of::<ViewProjectionUBO>();
-	let mut view_projection_buffers =
-		std::iter::repeat_with(|| -> Result<_> {
-			let buffer_create_info = vk::BufferCreateInfo::builder()
-				.size(view_projection_buffer_size as _)
-				.usage(vk::BufferUsageFlags::UNIFORM_BUFFER)
-				.sharing_mode(vk::SharingMode::EXCLUSIVE);
-
-			let buffer = Buffer::new(
+			RenderTargets::new(
 				&device,
 				&allocator,
-				&buffer_create_info,
-				gpu_allocator::MemoryLocation::CpuToGpu,
-				"",
-			)?;
-
-			Ok(buffer)
-		})
-		.take(swapchain_image_count)
-		.collect::<Result<Vec<_>>>()?;
-
-	for buffer in view_projection_buffers.iter() {
-		let descriptor_buffer_info = vk::DescriptorBufferInfo::builder()
-			.buffer(buffer.handle())
-			.range(view_projection_buffer_size as _)
-			.build();
-
-		let descriptor_buffer_info = slice_from_ref(&descriptor_buffer_info);
-
-		let descriptor_set = descriptor_sets[0];
-		let descriptor_write_sets = vk::WriteDescriptorSet::builder()
-			.dst_set(descriptor_set)
-			.dst_binding(0)
-			.dst_array_element(0)
-			.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-			.buffer_info(descriptor_buffer_info)
-			.build();
-
-		let descriptor_write_sets = slice_from_ref(&descriptor_write_sets);
-
-		device.update_descriptor_sets(&descriptor_write_sets, &[]);
-	}
-
-	// depth stencil state
-	let depth_stencil_state_info =
-		vk::PipelineDepthStencilStateCreateInfo::builder()
-			.depth_test_enable(true)
-			.depth_write_enable(true)
-			.depth_compare_op(vk::CompareOp::LESS);
-
-	// vertex_input_state
-	let vertex_input_state_info =
-		vk::PipelineVertexInputStateCreateInfo::builder();
-
-	// input assembly
-	let vertex_input_assembly_state_info =
-		vk::PipelineInputAssemblyStateCreateInfo::builder()
-			.topology(vk::PrimitiveTopology::TRIANGLE_LIST);
-
-	let graphics_pipeline_create_info =
-		vk::GraphicsPipelineCreateInfo::builder()
-			.stages(&shader_stage_create_infos)
-			.input_assembly_state(&vertex_input_assembly_state_info)
-			.vertex_input_state(&vertex_input_state_info)
-			.rasterization_state(&rasterization_info)
-			.color_blend_state(&color_blend_state)
-			.multisample_state(&multisample_state_info)
-			.viewport_state(&viewport_state_info)
-			.render_pass(render_pass.handle())
-			.layout(pipeline_layout.handle())
-			.depth_stencil_state(&depth_stencil_state_info);
-
-	let pipeline =
-		GraphicsPipeline::new(&device, &graphics_pipeline_create_info)?;
-
-	// record command buffers
-	for (command_buffer, frame_buffer) in
-		(&command_buffers).iter().zip(&framebuffers)
-	{
-		let begin_info = vk::CommandBufferBeginInfo::builder();
-
-		command_buffer.begin(&begin_info)?;
-
-		let render_area = ash::vk::Rect2D::builder()
-			.extent(frame_buffer.extent())
-			.build();
-
-		let clear_color_value = vk::ClearColorValue {
-			float32: [0.1, 0.1, 0.1, 1.0],
-		};
-
-		let clear_color_value = vk::ClearValue {
-			color: clear_color_value,
-		};
-
-		let clear_depth_stencil_value = vk::ClearDepthStencilValue {
-			depth: 1.0,
-			stencil: 0,
-		};
-
-		let clear_depth_stencil_value = vk::ClearValue {
-			depth_stencil: clear_depth_stencil_value,
+				scene_renderer.render_pass(),
+				scene_renderer.depth_format(),
+				&image_views,
+				swapchain.extent(),
+			)
 		};
 
-		let clear_values = [clear_color_value, clear_depth_stencil_value];
-
-		let render_pass_begin_info = vk::RenderPassBeginInfo::builder()
-			.clear_values(&clear_values)
-			.render_pass(render_pass.handle())
-			.framebuffer(frame_buffer.handle())
-			.render_area(render_area);
-
-		command_buffer.begin_render_pass(
-			&render_pass_begin_info,
-			vk::SubpassContents::INLINE,
-		);
-
-		command_buffer.bind_pipeline(&pipeline);
-
-		// command_buffer.bind_vertex_buffers(
-		// 	vertex_buffers.handles(),
-		// 	vertex_buffers.offsets(),
-		// );
-
-		let first_descriptor_set = 0;
-		command_buffer.bind_descriptor_sets(
-			pipeline.bind_point(),
-			pipeline_layout.handle(),
-			first_descriptor_set as _,
-			&descriptor_sets,
-			&[],
-		);
-
-		command_buffer.draw(6, 1, 0, 0);
-		command_buffer.end_render_pass();
-		command_buffer.end()?;
-	}
-
-	let pipeline =
-		GraphicsPipeline::new(&device, &graphics_pipeline_create_info)?;
+	let mut render_targets =
+		create_render_targets(&swapchain, &scene_renderer)?;
 
-	let frame_resources = std::iter::repeat_with(|| -> Result<_> {
+	let mut frame_resources = std::iter::repeat_with(|| -> Result<_> {
 		let frame_resources = FrameResources {
 			image_available_semaphore: Semaphore::new(&device)?,
 			render_done_semaphore: Semaphore::new(&device)?,
 			render_done_fence: Fence::new(&device, true)?,
+			command_buffer: command_pool
+				.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?,
+			frame_uniforms: scene_renderer.create_frame_uniforms()?,
 		};
 
 		Ok(frame_resources)
 	})
-	.take(swapchain_image_count)
+	.take(FRAMES_IN_FLIGHT)
 	.collect::<Result<Vec<_>>>()?;
 
-	let mut iter = frame_resources.iter().cycle();
+	let mut frame_index = 0_usize;
 
-	let mut camera_dolly = 2.0_f32;
-	let mut azimuth = 0.0_f32;
-	let mut altitude = std::f32::consts::FRAC_PI_2;
-	let mut delta_time = 0.0_f32;
-	let mut start = Instant::now();
+	let mut scene_view = SceneView::new(&scene, config.camera_index)?;
+	let mut animation_player =
+		AnimationPlayer::new(&scene, config.animation.as_deref())?;
+	let mut last_frame_time = Instant::now();
 
-	let mut current_index = 0_usize;
-	loop {
-		let mut stop = false;
-		let frame_resources = iter.next().unwrap();
+	let mut swapchain_outdated = false;
+	let mut next_shadow_quality = false;
+	let mut stop = false;
+	while !stop {
+		let mut events = rx.try_iter().collect::<Vec<_>>();
+
+		// Nothing to render into while minimized, so block until next event
+		let surface_extent = surface.extent();
+		if surface_extent.width == 0 || surface_extent.height == 0 {
+			events.push(rx.recv()?);
+		}
+
+		for event in events {
+			let camera_controller = scene_view.camera_controller_mut();
 
-		for event in rx.try_iter() {
 			match event {
 				Event::Stop => stop = true, // Defer break to be sure all events processed
+				Event::Resize => swapchain_outdated = true,
+				Event::CursorMoved { x, y } => {
+					let viewport_height = swapchain.extent().height as f32;
+					camera_controller.cursor_moved(x, y, viewport_height);
+				}
+				Event::MouseButton { button, pressed } => {
+					camera_controller.mouse_button(button, pressed)
+				}
+				Event::MouseWheel { steps } => {
+					camera_controller.mouse_wheel(steps)
+				}
+				Event::ResetCamera => camera_controller.reset(),
+				Event::FrameAll => scene_view.frame_all(),
+				Event::NextCamera => scene_view.next_camera(),
+				Event::ToggleAnimation => animation_player.toggle_playback(),
+				Event::NextAnimation => animation_player.next_animation(),
+				Event::ScrubAnimation { seconds } => {
+					animation_player.scrub(seconds)
+				}
+				Event::ScaleAnimationSpeed { factor } => {
+					animation_player.scale_speed(factor)
+				}
+				Event::NextShadowQuality => next_shadow_quality = true,
 				Event::RenderDocFrameCapture => {
 					if let Some(ref mut renderdoc) = renderdoc {
 						renderdoc.trigger_capture();
@@ -591,9 +263,54 @@ pub fn run<SurfaceOwner: CreateSurface>(
 			}
 		}
 
+		if stop {
+			break;
+		}
+
+		let surface_extent = surface.extent();
+		if surface_extent.width == 0 || surface_extent.height == 0 {
+			continue;
+		}
+
+		if swapchain_outdated {
+			device.wait_idle()?;
+
+			// Old framebuffers reference swapchain image views
+			drop(render_targets);
+			swapchain.recreate()?;
+			render_targets =
+				create_render_targets(&swapchain, &scene_renderer)?;
+
+			swapchain_outdated = false;
+		}
+
+		if next_shadow_quality {
+			device.wait_idle()?;
+
+			let shadow_quality = scene_renderer.shadow_quality().next();
+			log::info!("Shadow quality: {shadow_quality:?}");
+
+			// Frame descriptor sets reference old shadow maps
+			scene_renderer.set_shadow_quality(
+				&command_pool,
+				&queue,
+				&scene,
+				shadow_quality,
+			)?;
+
+			for frame_resources in &mut frame_resources {
+				scene_renderer
+					.rebind_shadow_maps(&mut frame_resources.frame_uniforms);
+			}
+
+			next_shadow_quality = false;
+		}
+
+		let frame_resources = &mut frame_resources[frame_index];
+		frame_index = (frame_index + 1) % FRAMES_IN_FLIGHT;
+
 		let render_done_fence = &frame_resources.render_done_fence;
 		render_done_fence.wait_max_timeout()?;
-		render_done_fence.reset()?;
 
 		let image_available_semaphore =
 			&frame_resources.image_available_semaphore;
@@ -601,273 +318,80 @@ pub fn run<SurfaceOwner: CreateSurface>(
 			.acquire_next_image_max_timeout(
 				image_available_semaphore,
 				vk::Fence::null(),
-			)?;
+			);
 
-		// TODO: handle acruire result
-		let next_image = acquire_next_image_result.0;
+		let next_image = match acquire_next_image_result {
+			Ok((next_image, suboptimal)) => {
+				swapchain_outdated |= suboptimal;
+				next_image
+			}
+			Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+				swapchain_outdated = true;
+				continue;
+			}
+			Err(error) => return Err(error.into()),
+		};
+
+		// Reset only when the submission is guaranteed
+		render_done_fence.reset()?;
 
 		let render_done_semaphore = &frame_resources.render_done_semaphore;
 
-		let y = altitude.cos();
-		let altitude_sin = altitude.sin();
-		let x = azimuth.sin() * altitude_sin;
-		let z = azimuth.cos() * altitude_sin;
-		let camera_pos = glm::vec3::<f32>(x, y, z) * camera_dolly;
+		let frame_time = Instant::now();
+		let elapsed = frame_time - last_frame_time;
+		last_frame_time = frame_time;
 
-		let center = glm::vec3::<f32>(0.0, 0.0, 0.0);
-		let up = glm::vec3::<f32>(0.0, 1.0, 0.0);
+		animation_player.update(elapsed.as_secs_f32(), &mut scene);
 
-		let view = glm::look_at(&camera_pos, &center, &up);
-		let fov = 90.0_f32;
-		let fov = fov.to_radians();
+		let (view, projection, viewport_area) =
+			scene_view.camera_matrices(&scene, swapchain.extent());
 
-		let width = surface_extent.width as f32;
-		let height = surface_extent.height as f32;
-		let near = 0.1_f32;
-		let far = 10.0_f32;
-		let projection =
-			glm::perspective_fov_rh_zo(fov, width, height, near, far);
+		frame_resources
+			.frame_uniforms
+			.update(&view, &projection, &scene)?;
 
-		let view_projection = ViewProjectionUBO {
-			mt: projection * view,
-		};
+		let command_buffer = &frame_resources.command_buffer;
+		let frame_buffer = render_targets.framebuffer(next_image as usize);
+
+		let begin_info = vk::CommandBufferBeginInfo::builder()
+			.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
 
-		view_projection_buffers[current_index]
-			.copy_into_n_flush(&view_projection)?;
+		command_buffer.begin(&begin_info)?;
+
+		scene_renderer.record(
+			command_buffer,
+			frame_buffer,
+			viewport_area,
+			&frame_resources.frame_uniforms,
+			&scene,
+		);
+
+		command_buffer.end()?;
 
 		queue.submit(
-			&command_buffers[next_image as usize],
+			command_buffer,
 			slice_from_ref(&image_available_semaphore.handle()),
 			slice_from_ref(&vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT),
 			slice_from_ref(&render_done_semaphore.handle()),
 			render_done_fence,
 		)?;
 
-		// TODO: handle result
 		let present_result = swapchain.present(
 			&queue,
 			next_image,
 			slice_from_ref(&render_done_semaphore.handle()),
-		)?;
+		);
 
-		if stop {
-			break;
+		match present_result {
+			Ok(suboptimal) => swapchain_outdated |= suboptimal,
+			Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => swapchain_outdated = true,
+			Err(error) => return Err(error.into()),
 		}
-
-		current_index += 1;
-		current_index %= swapchain_image_count;
-
-		let end = Instant::now();
-		delta_time = (end - start).as_secs_f32();
-		start = end;
-
-		azimuth += delta_time;
 	}
 
 	device.wait_idle()?;
-	Ok(())
-}
-
-struct VertexDataBuffers<'a> {
-	_buffers: Vec<Buffer<'a>>,
-	handles: Vec<vk::Buffer>,
-	offsets: Vec<vk::DeviceSize>,
-}
-
-impl<'a> VertexDataBuffers<'a> {
-	pub fn new(buffers: Vec<Buffer<'a>>) -> Self {
-		let (handles, offsets): (Vec<_>, Vec<_>) = buffers
-			.iter()
-			.map(|buffer| (buffer.handle(), buffer.offset() as vk::DeviceSize))
-			.unzip();
-
-		Self {
-			_buffers: buffers,
-			handles,
-			offsets,
-		}
-	}
-
-	pub fn handles(&self) -> &[vk::Buffer] {
-		self.handles.as_slice()
-	}
-
-	pub fn offsets(&self) -> &[vk::DeviceSize] {
-		self.offsets.as_slice()
-	}
-}
-
-fn create_vertex_buffers<'a>(
-	gltf: &Gltf,
-	input_file: &Path,
-	command_pool: &'a CommandPool,
-	allocator: &'a Allocator,
-	queue: &Queue,
-) -> Result<VertexDataBuffers<'a>> {
-	let transfer_command_buffer = command_pool
-		.allocate_command_buffer(vk::CommandBufferLevel::PRIMARY)?;
-
-	let device = command_pool.device();
-
-	// buffers
-	let staging_buffers = gltf
-		.buffers()
-		.map(|buffer| {
-			let buffer_create_info = vk::BufferCreateInfo::builder()
-				.size(buffer.length() as _)
-				.usage(vk::BufferUsageFlags::TRANSFER_SRC)
-				.sharing_mode(vk::SharingMode::EXCLUSIVE);
-
-			let mut staging_buffer = Buffer::new(
-				device,
-				allocator,
-				&buffer_create_info,
-				gpu_allocator::MemoryLocation::CpuToGpu,
-				"staging buffer",
-			)?;
-
-			let data = staging_buffer.mapped_slice_mut()?;
-
-			match buffer.source() {
-				gltf::buffer::Source::Uri(uri) => {
-					use std::io::Read;
-
-					let buffer_path = input_file.parent().unwrap().join(uri);
-					let mut file = std::fs::File::open(buffer_path)?;
-					file.read_exact(data)?;
-				}
-				gltf::buffer::Source::Bin => {
-					//TODO: handle BIN source
-					unimplemented!();
-				}
-			}
 
-			staging_buffer.flush()?;
+	pipeline_cache.save_or_warn();
 
-			Ok(staging_buffer)
-		})
-		.collect::<Result<Vec<_>>>()?;
-
-	let begin_info = vk::CommandBufferBeginInfo::builder();
-	transfer_command_buffer.begin(&begin_info)?;
-	let vertex_buffers = staging_buffers
-		.iter()
-		.map(|stagin_buffer| -> Result<_> {
-			let buffer_create_info = vk::BufferCreateInfo::builder()
-				.size(stagin_buffer.size() as _)
-				.usage(
-					vk::BufferUsageFlags::INDEX_BUFFER
-						| vk::BufferUsageFlags::VERTEX_BUFFER
-						| vk::BufferUsageFlags::TRANSFER_DST,
-				)
-				.sharing_mode(vk::SharingMode::EXCLUSIVE)
-				.build();
-
-			let vertex_buffer = Buffer::new(
-				device,
-				allocator,
-				&buffer_create_info,
-				gpu_allocator::MemoryLocation::CpuToGpu,
-				"vertex buffer",
-			)?;
-
-			transfer_command_buffer.copy_buffer(stagin_buffer, &vertex_buffer);
-
-			Ok(vertex_buffer)
-		})
-		.collect::<Result<Vec<_>>>()?;
-
-	transfer_command_buffer.end()?;
-	let transfer_fence = Fence::new(device, false)?;
-	queue.submit(&transfer_command_buffer, &[], &[], &[], &transfer_fence)?;
-	transfer_fence.wait_max_timeout()?;
-
-	let vertex_buffers = VertexDataBuffers::new(vertex_buffers);
-	Ok(vertex_buffers)
-}
-
-fn find_suitable_physical_device<'a, SurfaceOwner>(
-	instance: &'a Instance,
-	surface: &Surface<SurfaceOwner>,
-) -> Option<(&'a PhysicalDevice, u32)> {
-	instance
-		.physical_devices()
-		.iter()
-		.find_map(|physical_device| {
-			let queue_families_properties =
-				&physical_device.queue_families_properties;
-
-			let graphics_queue_index = queue_families_properties
-				.iter()
-				.enumerate()
-				.find_map(|(queue_index, &queue_family_properties)| {
-					let queue_index = queue_index as _;
-
-					let surface_support = surface
-						.physical_device_support(physical_device, queue_index);
-
-					let graphic_support = queue_family_properties
-						.queue_flags
-						.contains(vk::QueueFlags::GRAPHICS);
-
-					if graphic_support && surface_support {
-						Some(queue_index)
-					} else {
-						None
-					}
-				});
-
-			graphics_queue_index
-				.map(|queue_index| (physical_device, queue_index))
-		})
-}
-
-fn gen_shader_path(name: &str) -> PathBuf {
-	PathBuf::from("gen")
-		.join(if cfg!(debug_assertions) {
-			"debug"
-		} else {
-			"release"
-		})
-		.join("shaders")
-		.join(name)
-}
-
-#[cfg(debug_assertions)]
-unsafe extern "system" fn vulkan_debug_callback(
-	severity_flags: vk::DebugUtilsMessageSeverityFlagsEXT,
-	message_type_flags: vk::DebugUtilsMessageTypeFlagsEXT,
-	callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
-	_: *mut std::os::raw::c_void,
-) -> vk::Bool32 {
-	use log::{error, info, trace, warn};
-
-	use vk::DebugUtilsMessageSeverityFlagsEXT;
-	use vk::DebugUtilsMessageTypeFlagsEXT;
-
-	let callback_data = *callback_data;
-
-	if let Ok(message) =
-		std::ffi::CStr::from_ptr(callback_data.p_message).to_str()
-	{
-		let message_type = match message_type_flags {
-			DebugUtilsMessageTypeFlagsEXT::GENERAL => "[General]",
-			DebugUtilsMessageTypeFlagsEXT::PERFORMANCE => "[Performance]",
-			DebugUtilsMessageTypeFlagsEXT::VALIDATION => "[Validation]",
-			_ => "[Unknown]",
-		};
-
-		let message = format!("{message_type} {message}");
-
-		match severity_flags {
-			DebugUtilsMessageSeverityFlagsEXT::WARNING => warn!("{message}"),
-			DebugUtilsMessageSeverityFlagsEXT::ERROR => error!("{message}"),
-			DebugUtilsMessageSeverityFlagsEXT::INFO => info!("{message}"),
-			_ => trace!("{message}"),
-		};
-	} else {
-		error!("Vulkan debug callback: unable to get message data!");
-	}
-
-	vk::FALSE
+	Ok(())
 }
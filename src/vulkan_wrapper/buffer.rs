@@ -92,6 +92,22 @@ impl<'a> Buffer<'a> {
 		Ok(&mut mapped_slice[0..self.size])
 	}
 
+	pub fn mapped_slice(&self) -> Result<&[u8]> {
+		let mapped_slice = self
+			.allocation
+			.as_ref()
+			.unwrap()
+			.mapped_slice()
+			.ok_or_else(|| {
+				anyhow::anyhow!(
+					"Attempt to access non mapped memory,
+				 i.e. memory is not host visible"
+				)
+			})?;
+
+		Ok(&mapped_slice[0..self.size])
+	}
+
 	pub fn copy_into<T: Sized>(&mut self, data: &T) -> Result<()> {
 		let slice = unsafe {
 			std::slice::from_raw_parts(
@@ -105,6 +121,20 @@ impl<'a> Buffer<'a> {
 		Ok(())
 	}
 
+	// Copies elements to the start of buffer, rest of it is left as is
+	pub fn copy_slice_into<T: Sized>(&mut self, data: &[T]) -> Result<()> {
+		let slice = unsafe {
+			std::slice::from_raw_parts(
+				data.as_ptr() as *const u8,
+				std::mem::size_of_val(data),
+			)
+		};
+
+		self.mapped_slice_mut()?[..slice.len()].copy_from_slice(slice);
+
+		Ok(())
+	}
+
 	pub fn flush(&self) -> Result<()> {
 		let ranges = vk::MappedMemoryRange::builder()
 			.memory(self.device_memory())
@@ -121,6 +151,23 @@ impl<'a> Buffer<'a> {
 		Ok(())
 	}
 
+	// Makes device writes visible to mapped memory
+	pub fn invalidate(&self) -> Result<()> {
+		let ranges = vk::MappedMemoryRange::builder()
+			.memory(self.device_memory())
+			.offset(self.offset() as _)
+			.size(self.size() as _)
+			.build();
+
+		unsafe {
+			self.device
+				.inner()
+				.invalidate_mapped_memory_ranges(slice_from_ref(&ranges))?
+		};
+
+		Ok(())
+	}
+
 	pub fn copy_into_n_flush<T: Sized>(&mut self, data: &T) -> Result<()> {
 		self.copy_into(data)?;
 		self.flush()?;
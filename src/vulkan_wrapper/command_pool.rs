@@ -135,6 +135,42 @@ impl<'a> CommandBuffer<'a> {
 		}
 	}
 
+	pub fn draw_indexed(
+		&self,
+		index_count: u32,
+		instance_count: u32,
+		first_index: u32,
+		vertex_offset: i32,
+		first_instance: u32,
+	) {
+		unsafe {
+			self.device.inner().cmd_draw_indexed(
+				self.handle,
+				index_count,
+				instance_count,
+				first_index,
+				vertex_offset,
+				first_instance,
+			)
+		}
+	}
+
+	pub fn dispatch(
+		&self,
+		group_count_x: u32,
+		group_count_y: u32,
+		group_count_z: u32,
+	) {
+		unsafe {
+			self.device.inner().cmd_dispatch(
+				self.handle,
+				group_count_x,
+				group_count_y,
+				group_count_z,
+			)
+		}
+	}
+
 	pub fn end(&self) -> Result<()> {
 		unsafe { self.device.inner().end_command_buffer(self.handle)? }
 
@@ -156,6 +192,22 @@ impl<'a> CommandBuffer<'a> {
 		}
 	}
 
+	pub fn bind_index_buffer(
+		&self,
+		buffer: vk::Buffer,
+		offset: vk::DeviceSize,
+		index_type: vk::IndexType,
+	) {
+		unsafe {
+			self.device.inner().cmd_bind_index_buffer(
+				self.handle,
+				buffer,
+				offset,
+				index_type,
+			);
+		}
+	}
+
 	pub fn bind_pipeline<T: Pipeline>(&self, pipeline: &T) {
 		unsafe {
 			self.device.inner().cmd_bind_pipeline(
@@ -170,8 +222,8 @@ impl<'a> CommandBuffer<'a> {
 		debug_assert!(src_buffer.size() <= dst_buffer.size());
 
 		let regions = [vk::BufferCopy::builder()
-			.src_offset(src_buffer.offset() as _)
-			.dst_offset(dst_buffer.offset() as _)
+			.src_offset(0)
+			.dst_offset(0)
 			.size(src_buffer.size() as _)
 			.build()];
 
@@ -185,6 +237,85 @@ impl<'a> CommandBuffer<'a> {
 		}
 	}
 
+	pub fn copy_buffer_to_image(
+		&self,
+		src_buffer: &Buffer,
+		dst_image: vk::Image,
+		dst_image_layout: vk::ImageLayout,
+		regions: &[vk::BufferImageCopy],
+	) {
+		unsafe {
+			self.device.inner().cmd_copy_buffer_to_image(
+				self.handle,
+				src_buffer.handle(),
+				dst_image,
+				dst_image_layout,
+				regions,
+			)
+		}
+	}
+
+	pub fn blit_image(
+		&self,
+		src_image: vk::Image,
+		src_image_layout: vk::ImageLayout,
+		dst_image: vk::Image,
+		dst_image_layout: vk::ImageLayout,
+		regions: &[vk::ImageBlit],
+		filter: vk::Filter,
+	) {
+		unsafe {
+			self.device.inner().cmd_blit_image(
+				self.handle,
+				src_image,
+				src_image_layout,
+				dst_image,
+				dst_image_layout,
+				regions,
+				filter,
+			)
+		}
+	}
+
+	pub fn copy_image_to_buffer(
+		&self,
+		src_image: vk::Image,
+		src_image_layout: vk::ImageLayout,
+		dst_buffer: &Buffer,
+		regions: &[vk::BufferImageCopy],
+	) {
+		unsafe {
+			self.device.inner().cmd_copy_image_to_buffer(
+				self.handle,
+				src_image,
+				src_image_layout,
+				dst_buffer.handle(),
+				regions,
+			)
+		}
+	}
+
+	pub fn pipeline_barrier(
+		&self,
+		src_stage_mask: vk::PipelineStageFlags,
+		dst_stage_mask: vk::PipelineStageFlags,
+		memory_barriers: &[vk::MemoryBarrier],
+		buffer_memory_barriers: &[vk::BufferMemoryBarrier],
+		image_memory_barriers: &[vk::ImageMemoryBarrier],
+	) {
+		unsafe {
+			self.device.inner().cmd_pipeline_barrier(
+				self.handle,
+				src_stage_mask,
+				dst_stage_mask,
+				vk::DependencyFlags::empty(),
+				memory_barriers,
+				buffer_memory_barriers,
+				image_memory_barriers,
+			)
+		}
+	}
+
 	pub fn begin_render_pass(
 		&self,
 		info: &vk::RenderPassBeginInfo,
@@ -203,6 +334,22 @@ impl<'a> CommandBuffer<'a> {
 		unsafe { self.device.inner().cmd_end_render_pass(self.handle) }
 	}
 
+	pub fn set_viewport(&self, viewports: &[vk::Viewport]) {
+		unsafe {
+			self.device
+				.inner()
+				.cmd_set_viewport(self.handle, 0, viewports)
+		}
+	}
+
+	pub fn set_scissor(&self, scissors: &[vk::Rect2D]) {
+		unsafe {
+			self.device
+				.inner()
+				.cmd_set_scissor(self.handle, 0, scissors)
+		}
+	}
+
 	pub fn bind_descriptor_sets(
 		&self,
 		bind_poit: vk::PipelineBindPoint,
@@ -223,6 +370,31 @@ impl<'a> CommandBuffer<'a> {
 		}
 	}
 
+	pub fn push_constants<T: Sized>(
+		&self,
+		layout: vk::PipelineLayout,
+		stage_flags: vk::ShaderStageFlags,
+		offset: u32,
+		data: &T,
+	) {
+		let constants = unsafe {
+			std::slice::from_raw_parts(
+				(data as *const T) as *const u8,
+				std::mem::size_of::<T>(),
+			)
+		};
+
+		unsafe {
+			self.device.inner().cmd_push_constants(
+				self.handle,
+				layout,
+				stage_flags,
+				offset,
+				constants,
+			)
+		}
+	}
+
 	pub fn handle(&self) -> vk::CommandBuffer {
 		self.handle
 	}
@@ -0,0 +1,56 @@
+use crate::slice_from_ref;
+use ash::vk;
+use {
+	super::{Device, Pipeline, PipelineCache},
+	anyhow::Result,
+};
+
+pub struct ComputePipeline<'a> {
+	handle: vk::Pipeline,
+	device: &'a Device<'a>,
+}
+
+impl<'a> ComputePipeline<'a> {
+	pub fn new(
+		device: &'a Device,
+		pipeline_cache: &PipelineCache,
+		create_info: &vk::ComputePipelineCreateInfo,
+	) -> Result<Self> {
+		let pipelines = unsafe {
+			device.inner().create_compute_pipelines(
+				pipeline_cache.handle(),
+				slice_from_ref(create_info),
+				None,
+			)
+		};
+
+		let handle = match pipelines {
+			Ok(mut pipelines) => pipelines.pop().unwrap(),
+			Err((_, err)) => return Err(err.into()),
+		};
+
+		let compute_pipeline = Self { handle, device };
+
+		Ok(compute_pipeline)
+	}
+}
+
+impl<'a> Drop for ComputePipeline<'a> {
+	fn drop(&mut self) {
+		unsafe {
+			self.device.inner().destroy_pipeline(self.handle, None);
+		}
+	}
+}
+
+impl<'a> Pipeline for ComputePipeline<'a> {
+	const BIND_POINT: vk::PipelineBindPoint = vk::PipelineBindPoint::COMPUTE;
+
+	fn handle(&self) -> vk::Pipeline {
+		self.handle
+	}
+
+	fn bind_point(&self) -> vk::PipelineBindPoint {
+		Self::BIND_POINT
+	}
+}
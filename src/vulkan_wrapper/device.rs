@@ -16,7 +16,7 @@ impl<'a> Device<'a> {
 		instance: &'a Instance,
 		physical_device: &'a PhysicalDevice,
 		extensions: &[ExtensionName],
-		// features: &vk::PhysicalDeviceFeatures,
+		features: &mut vk::PhysicalDeviceFeatures2,
 		queues: &HashMap<u32, &[f32]>,
 	) -> Result<Self> {
 		let device_queue_create_info = queues
@@ -31,7 +31,8 @@ impl<'a> Device<'a> {
 
 		let device_create_info = vk::DeviceCreateInfo::builder()
 			.queue_create_infos(&device_queue_create_info)
-			.enabled_extension_names(extensions);
+			.enabled_extension_names(extensions)
+			.push_next(features);
 
 		let inner = unsafe {
 			instance.inner().create_device(
@@ -1,7 +1,7 @@
 use crate::slice_from_ref;
 use ash::vk;
 use {
-	super::{Device, Pipeline},
+	super::{Device, Pipeline, PipelineCache},
 	anyhow::Result,
 };
 
@@ -13,11 +13,12 @@ pub struct GraphicsPipeline<'a> {
 impl<'a> GraphicsPipeline<'a> {
 	pub fn new(
 		device: &'a Device,
+		pipeline_cache: &PipelineCache,
 		create_info: &vk::GraphicsPipelineCreateInfo,
 	) -> Result<Self> {
 		let pipelines = unsafe {
 			device.inner().create_graphics_pipelines(
-				vk::PipelineCache::null(),
+				pipeline_cache.handle(),
 				slice_from_ref(create_info),
 				None,
 			)
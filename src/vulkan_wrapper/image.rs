@@ -1,4 +1,6 @@
 use super::Allocator;
+use super::{Buffer, CommandBuffer};
+use crate::slice_from_ref;
 use gpu_allocator::vulkan::{Allocation, AllocationCreateDesc};
 use gpu_allocator::MemoryLocation;
 use {super::Device, anyhow::Result, ash::vk};
@@ -7,6 +9,10 @@ pub struct Image<'a> {
 	allocation: Option<Allocation>,
 	allocator: &'a Allocator<'a>,
 	handle: vk::Image,
+	format: vk::Format,
+	extent: vk::Extent3D,
+	mip_levels: u32,
+	array_layers: u32,
 	device: &'a Device<'a>,
 }
 
@@ -44,6 +50,10 @@ impl<'a> Image<'a> {
 
 		let image = Self {
 			handle,
+			format: create_info.format,
+			extent: create_info.extent,
+			mip_levels: create_info.mip_levels,
+			array_layers: create_info.array_layers,
 			allocator,
 			device,
 			allocation,
@@ -55,6 +65,229 @@ impl<'a> Image<'a> {
 	pub fn handle(&self) -> vk::Image {
 		self.handle
 	}
+
+	pub fn format(&self) -> vk::Format {
+		self.format
+	}
+
+	pub fn extent(&self) -> vk::Extent3D {
+		self.extent
+	}
+
+	pub fn mip_levels(&self) -> u32 {
+		self.mip_levels
+	}
+
+	pub fn array_layers(&self) -> u32 {
+		self.array_layers
+	}
+
+	// Copies tightly packed staging data of consecutive mip levels starting
+	// from the first one, remaining levels are generated by linear blits.
+	// Leaves whole image ready for sampling in given shader stages
+	pub fn record_upload(
+		&self,
+		command_buffer: &CommandBuffer,
+		staging_buffer: &Buffer,
+		level_offsets: &[usize],
+		dst_stage_mask: vk::PipelineStageFlags,
+	) {
+		let transfer_barrier = self.level_barrier(
+			0,
+			self.mip_levels,
+			(vk::ImageLayout::UNDEFINED, vk::AccessFlags::empty()),
+			(
+				vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+				vk::AccessFlags::TRANSFER_WRITE,
+			),
+		);
+
+		command_buffer.pipeline_barrier(
+			vk::PipelineStageFlags::TOP_OF_PIPE,
+			vk::PipelineStageFlags::TRANSFER,
+			&[],
+			&[],
+			slice_from_ref(&transfer_barrier),
+		);
+
+		let copy_regions = level_offsets
+			.iter()
+			.enumerate()
+			.map(|(level, &offset)| {
+				vk::BufferImageCopy::builder()
+					.buffer_offset(offset as _)
+					.image_subresource(self.subresource_layers(level as _))
+					.image_extent(self.level_extent(level as _))
+					.build()
+			})
+			.collect::<Vec<_>>();
+
+		command_buffer.copy_buffer_to_image(
+			staging_buffer,
+			self.handle,
+			vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+			&copy_regions,
+		);
+
+		self.record_mip_generation(
+			command_buffer,
+			level_offsets.len() as _,
+			dst_stage_mask,
+		);
+	}
+
+	// Expects all levels in TRANSFER_DST_OPTIMAL layout with levels before
+	// the first generated one filled. Leaves whole image ready for sampling
+	pub fn record_mip_generation(
+		&self,
+		command_buffer: &CommandBuffer,
+		first_generated_level: u32,
+		dst_stage_mask: vk::PipelineStageFlags,
+	) {
+		for level in first_generated_level..self.mip_levels {
+			let src_level = level - 1;
+
+			let blit_src_barrier = self.level_barrier(
+				src_level,
+				1,
+				(
+					vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+					vk::AccessFlags::TRANSFER_WRITE,
+				),
+				(
+					vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+					vk::AccessFlags::TRANSFER_READ,
+				),
+			);
+
+			command_buffer.pipeline_barrier(
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::TRANSFER,
+				&[],
+				&[],
+				slice_from_ref(&blit_src_barrier),
+			);
+
+			let blit_region = vk::ImageBlit::builder()
+				.src_subresource(self.subresource_layers(src_level))
+				.src_offsets([
+					vk::Offset3D::default(),
+					Self::extent_to_offset(self.level_extent(src_level)),
+				])
+				.dst_subresource(self.subresource_layers(level))
+				.dst_offsets([
+					vk::Offset3D::default(),
+					Self::extent_to_offset(self.level_extent(level)),
+				])
+				.build();
+
+			command_buffer.blit_image(
+				self.handle,
+				vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+				self.handle,
+				vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+				slice_from_ref(&blit_region),
+				vk::Filter::LINEAR,
+			);
+		}
+
+		// Blit sources were moved to TRANSFER_SRC_OPTIMAL layout
+		let read_barriers = (0..self.mip_levels)
+			.map(|level| {
+				let blit_source = level + 1 >= first_generated_level
+					&& level + 1 < self.mip_levels;
+
+				let old_layout = if blit_source {
+					vk::ImageLayout::TRANSFER_SRC_OPTIMAL
+				} else {
+					vk::ImageLayout::TRANSFER_DST_OPTIMAL
+				};
+
+				self.level_barrier(
+					level,
+					1,
+					(old_layout, vk::AccessFlags::TRANSFER_WRITE),
+					(
+						vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+						vk::AccessFlags::SHADER_READ,
+					),
+				)
+			})
+			.collect::<Vec<_>>();
+
+		command_buffer.pipeline_barrier(
+			vk::PipelineStageFlags::TRANSFER,
+			dst_stage_mask,
+			&[],
+			&[],
+			&read_barriers,
+		);
+	}
+
+	pub fn level_extent(&self, level: u32) -> vk::Extent3D {
+		vk::Extent3D {
+			width: (self.extent.width >> level).max(1),
+			height: (self.extent.height >> level).max(1),
+			depth: (self.extent.depth >> level).max(1),
+		}
+	}
+
+	fn subresource_layers(&self, level: u32) -> vk::ImageSubresourceLayers {
+		vk::ImageSubresourceLayers::builder()
+			.aspect_mask(vk::ImageAspectFlags::COLOR)
+			.mip_level(level)
+			.layer_count(self.array_layers)
+			.build()
+	}
+
+	// Layout transition of mip levels across all array layers
+	pub fn level_barrier(
+		&self,
+		base_level: u32,
+		level_count: u32,
+		(old_layout, src_access_mask): (vk::ImageLayout, vk::AccessFlags),
+		(new_layout, dst_access_mask): (vk::ImageLayout, vk::AccessFlags),
+	) -> vk::ImageMemoryBarrier {
+		let subresource_range = vk::ImageSubresourceRange::builder()
+			.aspect_mask(self.aspect_mask())
+			.base_mip_level(base_level)
+			.level_count(level_count)
+			.layer_count(self.array_layers)
+			.build();
+
+		vk::ImageMemoryBarrier::builder()
+			.src_access_mask(src_access_mask)
+			.dst_access_mask(dst_access_mask)
+			.old_layout(old_layout)
+			.new_layout(new_layout)
+			.src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+			.dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+			.image(self.handle)
+			.subresource_range(subresource_range)
+			.build()
+	}
+
+	fn aspect_mask(&self) -> vk::ImageAspectFlags {
+		match self.format {
+			vk::Format::D16_UNORM
+			| vk::Format::X8_D24_UNORM_PACK32
+			| vk::Format::D32_SFLOAT => vk::ImageAspectFlags::DEPTH,
+			vk::Format::D16_UNORM_S8_UINT
+			| vk::Format::D24_UNORM_S8_UINT
+			| vk::Format::D32_SFLOAT_S8_UINT => {
+				vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+			}
+			_ => vk::ImageAspectFlags::COLOR,
+		}
+	}
+
+	fn extent_to_offset(extent: vk::Extent3D) -> vk::Offset3D {
+		vk::Offset3D {
+			x: extent.width as _,
+			y: extent.height as _,
+			z: extent.depth as _,
+		}
+	}
 }
 
 impl<'a> Drop for Image<'a> {
@@ -96,9 +96,9 @@ impl Instance {
 		let physical_devices =
 			unsafe { instance.enumerate_physical_devices()? };
 
-		let physical_devices: Vec<_> = physical_devices
+		let physical_devices = physical_devices
 			.iter()
-			.map(|&handle| -> PhysicalDevice {
+			.map(|&handle| -> Result<PhysicalDevice> {
 				unsafe {
 					let properties =
 						instance.get_physical_device_properties(handle);
@@ -106,6 +106,8 @@ impl Instance {
 						.get_physical_device_queue_family_properties(handle);
 					let features =
 						instance.get_physical_device_features(handle);
+					let extensions_properties = instance
+						.enumerate_device_extension_properties(handle)?;
 
 					log::info!(
 						"Physical device collected; name: {}",
@@ -116,15 +118,16 @@ impl Instance {
 						.unwrap_or("unknown")
 					);
 
-					PhysicalDevice {
+					Ok(PhysicalDevice {
 						handle,
 						queue_families_properties,
 						properties,
 						features,
-					}
+						extensions_properties,
+					})
 				}
 			})
-			.collect();
+			.collect::<Result<Vec<_>>>()?;
 
 		Ok(physical_devices)
 	}
@@ -132,6 +135,17 @@ impl Instance {
 	pub fn physical_devices(&self) -> &Vec<PhysicalDevice> {
 		&self.physical_devices
 	}
+
+	pub fn physical_device_features2(
+		&self,
+		physical_device: &PhysicalDevice,
+		features: &mut vk::PhysicalDeviceFeatures2,
+	) {
+		unsafe {
+			self.inner
+				.get_physical_device_features2(physical_device.handle, features)
+		}
+	}
 }
 
 impl Drop for Instance {
@@ -147,4 +161,17 @@ pub struct PhysicalDevice {
 	pub queue_families_properties: Vec<vk::QueueFamilyProperties>,
 	pub properties: vk::PhysicalDeviceProperties,
 	pub features: vk::PhysicalDeviceFeatures,
+	pub extensions_properties: Vec<vk::ExtensionProperties>,
+}
+
+impl PhysicalDevice {
+	pub fn supports_extension(&self, name: &std::ffi::CStr) -> bool {
+		self.extensions_properties.iter().any(|properties| {
+			let extension_name = unsafe {
+				std::ffi::CStr::from_ptr(properties.extension_name.as_ptr())
+			};
+
+			extension_name == name
+		})
+	}
 }
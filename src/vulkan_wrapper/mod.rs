@@ -1,6 +1,7 @@
 mod allocator;
 mod buffer;
 mod command_pool;
+mod compute_pipeline;
 #[cfg(debug_assertions)]
 pub mod debug_messanger;
 mod descriptor_pool;
@@ -13,9 +14,11 @@ mod image;
 mod image_view;
 mod instance;
 mod pipeline;
+mod pipeline_cache;
 mod pipeline_layout;
 mod queue;
 mod render_pass;
+mod sampler;
 mod semaphore;
 mod shader_module;
 mod surface;
@@ -26,6 +29,7 @@ pub use allocator::Allocator;
 pub use buffer::Buffer;
 pub use command_pool::CommandBuffer;
 pub use command_pool::CommandPool;
+pub use compute_pipeline::ComputePipeline;
 pub use descriptor_pool::DescriptorPool;
 pub use descriptor_set_layout::DescriptorSetLayout;
 pub use device::Device;
@@ -37,9 +41,11 @@ pub use image_view::ImageView;
 pub use instance::Instance;
 pub use instance::PhysicalDevice;
 pub use pipeline::Pipeline;
+pub use pipeline_cache::PipelineCache;
 pub use pipeline_layout::PipelineLayout;
 pub use queue::Queue;
 pub use render_pass::RenderPass;
+pub use sampler::Sampler;
 pub use semaphore::Semaphore;
 pub use shader_module::ShaderModule;
 pub use surface::CreateSurface;
@@ -0,0 +1,134 @@
+use {
+	super::Device,
+	anyhow::Result,
+	ash::vk,
+	std::path::{Path, PathBuf},
+};
+
+const HEADER_MAGIC: [u8; 4] = *b"OKOP";
+// Magic, vendor id, device id, driver version and pipeline cache UUID
+const HEADER_SIZE: usize = 16 + vk::UUID_SIZE;
+
+// Cache data on disk is prefixed with device identification, data of other
+// devices or drivers is discarded rather than handed to the driver
+pub struct PipelineCache<'a> {
+	handle: vk::PipelineCache,
+	path: Option<PathBuf>,
+	device: &'a Device<'a>,
+}
+
+impl<'a> PipelineCache<'a> {
+	// Without path the cache lives in memory only
+	pub fn new(device: &'a Device, path: Option<&Path>) -> Result<Self> {
+		let header = header(device);
+
+		let initial_data = path
+			.and_then(|path| match std::fs::read(path) {
+				Ok(data) => Some(data),
+				Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+				Err(err) => {
+					log::warn!(
+						"Failed to read pipeline cache {}: {err}",
+						path.display()
+					);
+					None
+				}
+			})
+			.filter(|data| {
+				let valid = data.starts_with(&header);
+
+				if !valid {
+					log::info!("Pipeline cache is stale, starting empty");
+				}
+
+				valid
+			});
+
+		let initial_data = initial_data
+			.as_deref()
+			.map_or(&[][..], |data| &data[HEADER_SIZE..]);
+
+		log::info!("Pipeline cache initial size: {}", initial_data.len());
+
+		let create_info =
+			vk::PipelineCacheCreateInfo::builder().initial_data(initial_data);
+
+		let handle = unsafe {
+			device.inner().create_pipeline_cache(&create_info, None)?
+		};
+
+		let pipeline_cache = Self {
+			handle,
+			path: path.map(Path::to_owned),
+			device,
+		};
+
+		Ok(pipeline_cache)
+	}
+
+	pub fn handle(&self) -> vk::PipelineCache {
+		self.handle
+	}
+
+	// Failing to persist the cache only costs startup time of the next run
+	pub fn save_or_warn(&self) {
+		if let Err(err) = self.save() {
+			log::warn!("Unable to save pipeline cache: {err}");
+		}
+	}
+
+	// Writes cache data behind device header, does nothing without path.
+	// Data goes to a temporary file renamed over the old one, so readers
+	// never see it half written
+	fn save(&self) -> Result<()> {
+		let path = match &self.path {
+			Some(path) => path,
+			None => return Ok(()),
+		};
+
+		let data = unsafe {
+			self.device.inner().get_pipeline_cache_data(self.handle)?
+		};
+
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+
+		let file_data = [header(self.device).as_slice(), &data].concat();
+		let temp_path =
+			path.with_extension(format!("tmp.{}", std::process::id()));
+		std::fs::write(&temp_path, file_data)?;
+		std::fs::rename(&temp_path, path)?;
+
+		log::info!(
+			"Pipeline cache of size {} saved to {}",
+			data.len(),
+			path.display()
+		);
+
+		Ok(())
+	}
+}
+
+impl<'a> Drop for PipelineCache<'a> {
+	fn drop(&mut self) {
+		unsafe {
+			self.device
+				.inner()
+				.destroy_pipeline_cache(self.handle, None);
+		}
+	}
+}
+
+fn header(device: &Device) -> [u8; HEADER_SIZE] {
+	let properties = &device.physical_device().properties;
+
+	let mut header = [0; HEADER_SIZE];
+	header[..4].copy_from_slice(&HEADER_MAGIC);
+	header[4..8].copy_from_slice(&properties.vendor_id.to_le_bytes());
+	header[8..12].copy_from_slice(&properties.device_id.to_le_bytes());
+	header[12..16].copy_from_slice(&properties.driver_version.to_le_bytes());
+	header[16..].copy_from_slice(&properties.pipeline_cache_uuid);
+
+	header
+}
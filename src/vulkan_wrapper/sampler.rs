@@ -0,0 +1,30 @@
+use {super::Device, anyhow::Result, ash::vk};
+
+pub struct Sampler<'a> {
+	handle: vk::Sampler,
+	device: &'a Device<'a>,
+}
+
+impl<'a> Sampler<'a> {
+	pub fn new(
+		device: &'a Device,
+		create_info: &vk::SamplerCreateInfo,
+	) -> Result<Self> {
+		let handle =
+			unsafe { device.inner().create_sampler(create_info, None)? };
+
+		let sampler = Self { handle, device };
+
+		Ok(sampler)
+	}
+
+	pub fn handle(&self) -> vk::Sampler {
+		self.handle
+	}
+}
+
+impl<'a> Drop for Sampler<'a> {
+	fn drop(&mut self) {
+		unsafe { self.device.inner().destroy_sampler(self.handle, None) }
+	}
+}
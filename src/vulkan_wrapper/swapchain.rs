@@ -16,8 +16,8 @@ pub struct Swapchain<'a, T> {
 	handle: vk::SwapchainKHR,
 	image_views: Vec<ImageView<'a>>,
 	extension: ash::extensions::khr::Swapchain,
-	_device: &'a Device<'a>,
-	_surface: &'a Surface<'a, T>,
+	device: &'a Device<'a>,
+	surface: &'a Surface<'a, T>,
 }
 
 impl<'a, T> Swapchain<'a, T> {
@@ -34,6 +34,30 @@ impl<'a, T> Swapchain<'a, T> {
 			device.inner(),
 		);
 
+		let mut swapchain = Self {
+			format: Format::UNDEFINED,
+			extent: Extent2D::default(),
+			handle: vk::SwapchainKHR::null(),
+			image_views: Vec::new(),
+			extension,
+			device,
+			surface,
+		};
+
+		swapchain.recreate()?;
+
+		Ok(swapchain)
+	}
+
+	// The caller must ensure that the swapchain images are no longer in use
+	pub fn recreate(&mut self) -> Result<()>
+	where
+		T: SurfaceExtent,
+	{
+		let device = self.device;
+		let surface = self.surface;
+		let extension = &self.extension;
+
 		let physical_device = device.physical_device();
 		let surface_capabilities = surface.capabilities(physical_device)?;
 		let min_images_count =
@@ -46,6 +70,7 @@ impl<'a, T> Swapchain<'a, T> {
 		let present_mode = Self::get_present_mode(&surface_present_modes);
 		let format = surface_format.format;
 		let color_space = surface_format.color_space;
+		let old_swapchain = self.handle;
 
 		let create_info = ash::vk::SwapchainCreateInfoKHR::builder()
 			.surface(surface.handle())
@@ -59,10 +84,19 @@ impl<'a, T> Swapchain<'a, T> {
 			.image_sharing_mode(ash::vk::SharingMode::EXCLUSIVE)
 			.pre_transform(surface_capabilities.current_transform)
 			.composite_alpha(ash::vk::CompositeAlphaFlagsKHR::OPAQUE)
-			.clipped(true);
+			.clipped(true)
+			.old_swapchain(old_swapchain);
 
 		let handle = unsafe { extension.create_swapchain(&create_info, None)? };
 
+		self.image_views.clear();
+
+		if old_swapchain != vk::SwapchainKHR::null() {
+			unsafe { extension.destroy_swapchain(old_swapchain, None) };
+		}
+
+		self.handle = handle;
+
 		let images = unsafe { extension.get_swapchain_images(handle)? };
 
 		let image_views = images
@@ -90,17 +124,18 @@ impl<'a, T> Swapchain<'a, T> {
 			})
 			.collect::<Result<Vec<_>>>()?;
 
-		let swapchain = Self {
-			format,
-			extent,
-			handle,
-			image_views,
-			extension,
-			_device: device,
-			_surface: surface,
-		};
+		self.format = format;
+		self.extent = extent;
+		self.image_views = image_views;
 
-		Ok(swapchain)
+		log::info!(
+			"Swapchain created; extent: {}x{}, image count: {}",
+			extent.width,
+			extent.height,
+			self.image_views.len()
+		);
+
+		Ok(())
 	}
 
 	pub fn extent(&self) -> Extent2D {
@@ -157,7 +192,8 @@ impl<'a, T> Swapchain<'a, T> {
 	fn get_surface_format(
 		formats: &[SurfaceFormatKHR],
 	) -> Option<SurfaceFormatKHR> {
-		let desired_format = ash::vk::Format::B8G8R8A8_UNORM;
+		// Shaders output linear color, encoding is left to the format
+		let desired_format = ash::vk::Format::B8G8R8A8_SRGB;
 		let desired_color_space = ash::vk::ColorSpaceKHR::SRGB_NONLINEAR;
 
 		let format = formats.iter().copied().find(|format| {
@@ -225,7 +261,7 @@ impl<'a, T> Swapchain<'a, T> {
 		queue: &Queue,
 		image_index: u32,
 		wait_semaphores: &[vk::Semaphore],
-	) -> Result<bool> {
+	) -> ash::prelude::VkResult<bool> {
 		let image_indices = [image_index];
 		let swapchains = [self.handle];
 
@@ -234,17 +270,14 @@ impl<'a, T> Swapchain<'a, T> {
 			.swapchains(&swapchains)
 			.wait_semaphores(wait_semaphores);
 
-		let result = unsafe {
-			self.extension
-				.queue_present(queue.handle(), &present_info)?
-		};
-
-		Ok(result)
+		unsafe { self.extension.queue_present(queue.handle(), &present_info) }
 	}
 }
 
 impl<'a, T> Drop for Swapchain<'a, T> {
 	fn drop(&mut self) {
+		self.image_views.clear();
+
 		unsafe {
 			self.extension.destroy_swapchain(self.handle, None);
 		}
@@ -0,0 +1,216 @@
+// Renders every glTF asset in res/gltf headlessly and compares the result
+// against reference images in tests/golden. Set OKO_BLESS=1 to (re)write
+// references from the current output.
+
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ASSETS_DIR: &str = "res/gltf";
+const REFERENCES_DIR: &str = "tests/golden";
+const OUTPUT_DIR: &str = "target/golden";
+const RENDER_SIZE: &str = "256x256";
+const BLESS_VAR: &str = "OKO_BLESS";
+// Pipeline cache of test runs is kept away from user cache directory
+const CACHE_DIR: &str = "target/golden/cache";
+// Render error without lavapipe, matches the one of headless renderer
+const NO_CPU_DEVICE: &str = "No CPU Vulkan device found";
+
+// Squared YIQ delta above which pixels are perceived as different, matches
+// pixelmatch default threshold of 0.1
+const MAX_YIQ_DELTA: f32 = 35215.0;
+const PIXEL_THRESHOLD: f32 = 0.1 * 0.1 * MAX_YIQ_DELTA;
+// Fraction of differing pixels tolerated, e.g. rasterization differences
+const MISMATCH_TOLERANCE: f32 = 0.002;
+
+#[test]
+fn golden_images() {
+	let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let bless = std::env::var_os(BLESS_VAR).is_some();
+
+	let mut assets = Vec::new();
+	collect_assets(&manifest_dir.join(ASSETS_DIR), &mut assets);
+	assets.sort();
+
+	assert!(!assets.is_empty(), "No assets found in {ASSETS_DIR}");
+
+	let failures = assets
+		.iter()
+		.filter_map(|asset| {
+			let name = asset
+				.strip_prefix(manifest_dir.join(ASSETS_DIR))
+				.unwrap()
+				.with_extension(format!(
+					"{}.png",
+					asset.extension().unwrap().to_string_lossy()
+				));
+
+			check_asset(manifest_dir, asset, &name, bless)
+				.err()
+				.map(|error| format!("{}: {error}", name.display()))
+		})
+		.collect::<Vec<_>>();
+
+	// References are rendered by lavapipe, other devices can't check them
+	if failures
+		.iter()
+		.any(|failure| failure.contains(NO_CPU_DEVICE))
+	{
+		eprintln!(
+			"Skipping golden images: {NO_CPU_DEVICE}, install lavapipe, \
+			 e.g. mesa-vulkan-drivers"
+		);
+		return;
+	}
+
+	assert!(
+		failures.is_empty(),
+		"Golden image mismatches:\n{}",
+		failures.join("\n")
+	);
+}
+
+fn collect_assets(dir: &Path, assets: &mut Vec<PathBuf>) {
+	for entry in std::fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+
+		if path.is_dir() {
+			collect_assets(&path, assets);
+		} else if matches!(
+			path.extension().and_then(|extension| extension.to_str()),
+			Some("gltf" | "glb")
+		) {
+			assets.push(path);
+		}
+	}
+}
+
+fn check_asset(
+	manifest_dir: &Path,
+	asset: &Path,
+	name: &Path,
+	bless: bool,
+) -> Result<(), String> {
+	let output_path = manifest_dir.join(OUTPUT_DIR).join(name);
+	let reference_path = manifest_dir.join(REFERENCES_DIR).join(name);
+	let diff_path = output_path.with_extension("diff.png");
+
+	std::fs::create_dir_all(output_path.parent().unwrap())
+		.map_err(|error| error.to_string())?;
+
+	// Shaders are looked up relative to working directory. References are
+	// rendered by lavapipe, other devices rasterize differently
+	let output = Command::new(env!("CARGO_BIN_EXE_oko"))
+		.current_dir(manifest_dir)
+		.env("XDG_CACHE_HOME", manifest_dir.join(CACHE_DIR))
+		.arg("render")
+		.arg(asset)
+		.arg("--output")
+		.arg(&output_path)
+		.arg("--size")
+		.arg(RENDER_SIZE)
+		.arg("--cpu-device")
+		.output()
+		.map_err(|error| error.to_string())?;
+
+	if !output.status.success() {
+		return Err(format!(
+			"render failed: {}",
+			String::from_utf8_lossy(&output.stderr)
+		));
+	}
+
+	if bless {
+		std::fs::create_dir_all(reference_path.parent().unwrap())
+			.map_err(|error| error.to_string())?;
+		std::fs::copy(&output_path, &reference_path)
+			.map_err(|error| error.to_string())?;
+
+		return Ok(());
+	}
+
+	if !reference_path.exists() {
+		return Err(format!(
+			"no reference image, inspect {} and rerun with {BLESS_VAR}=1",
+			output_path.display()
+		));
+	}
+
+	let output = open_image(&output_path)?;
+	let reference = open_image(&reference_path)?;
+
+	if output.dimensions() != reference.dimensions() {
+		return Err(format!(
+			"size {:?} differs from reference size {:?}",
+			output.dimensions(),
+			reference.dimensions()
+		));
+	}
+
+	let (mismatches, diff) = compare(&output, &reference);
+	let pixel_count = output.width() * output.height();
+	let mismatch_ratio = mismatches as f32 / pixel_count as f32;
+
+	if mismatch_ratio > MISMATCH_TOLERANCE {
+		diff.save(&diff_path).map_err(|error| error.to_string())?;
+
+		return Err(format!(
+			"{:.2}% pixels differ, see {}",
+			mismatch_ratio * 100.0,
+			diff_path.display()
+		));
+	}
+
+	Ok(())
+}
+
+fn open_image(path: &Path) -> Result<RgbaImage, String> {
+	let image = image::open(path)
+		.map_err(|error| format!("{}: {error}", path.display()))?;
+
+	Ok(image.to_rgba8())
+}
+
+// Counts perceptually different pixels and marks them red over faded
+// reference
+fn compare(output: &RgbaImage, reference: &RgbaImage) -> (usize, RgbaImage) {
+	let mut mismatches = 0;
+	let mut diff = RgbaImage::new(reference.width(), reference.height());
+
+	for (x, y, reference_pixel) in reference.enumerate_pixels() {
+		let output_pixel = output.get_pixel(x, y);
+
+		let diff_pixel =
+			if yiq_delta(output_pixel, reference_pixel) > PIXEL_THRESHOLD {
+				mismatches += 1;
+				Rgba([255, 0, 0, 255])
+			} else {
+				let luma = 255.0 - (255.0 - luma(reference_pixel)) * 0.1;
+				let luma = luma as u8;
+				Rgba([luma, luma, luma, 255])
+			};
+
+		diff.put_pixel(x, y, diff_pixel);
+	}
+
+	(mismatches, diff)
+}
+
+fn luma(pixel: &Rgba<u8>) -> f32 {
+	let [r, g, b, _] = pixel.0.map(f32::from);
+	r * 0.298_895_3 + g * 0.586_622_5 + b * 0.11448223
+}
+
+// Squared YIQ distance, as used by pixelmatch
+fn yiq_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+	let [r0, g0, b0, _] = a.0.map(f32::from);
+	let [r1, g1, b1, _] = b.0.map(f32::from);
+
+	let (dr, dg, db) = (r0 - r1, g0 - g1, b0 - b1);
+
+	let y = dr * 0.298_895_3 + dg * 0.586_622_5 + db * 0.11448223;
+	let i = dr * 0.59597799 - dg * 0.274_176_1 - db * 0.321_801_9;
+	let q = dr * 0.21147017 - dg * 0.522_617_1 + db * 0.31114694;
+
+	0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}